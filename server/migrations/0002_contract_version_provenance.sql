@@ -0,0 +1,6 @@
+-- Upgrade provenance for each contract version
+ALTER TABLE contract_versions
+    ADD COLUMN IF NOT EXISTS deploy_hash TEXT,
+    ADD COLUMN IF NOT EXISTS block_height BIGINT,
+    ADD COLUMN IF NOT EXISTS deployer_public_key TEXT,
+    ADD COLUMN IF NOT EXISTS upgrade_cost TEXT;
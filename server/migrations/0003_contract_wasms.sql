@@ -0,0 +1,7 @@
+-- Cache of contract wasm bytes, keyed by raw contract wasm hash
+CREATE TABLE IF NOT EXISTS contract_wasms (
+    wasm_hash TEXT PRIMARY KEY,
+    wasm BYTEA NOT NULL,
+    size INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
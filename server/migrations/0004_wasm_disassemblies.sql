@@ -0,0 +1,6 @@
+-- Cache of WAT disassembly text, keyed by raw contract wasm hash
+CREATE TABLE IF NOT EXISTS wasm_disassemblies (
+    wasm_hash TEXT PRIMARY KEY,
+    wat TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
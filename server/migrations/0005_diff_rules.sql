@@ -0,0 +1,13 @@
+-- Rules applied to version diffs, global for a user when package_hash is NULL
+CREATE TABLE IF NOT EXISTS diff_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    package_hash TEXT,
+    target TEXT NOT NULL,
+    action TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    replacement TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS diff_rules_user_package ON diff_rules (user_id, package_hash);
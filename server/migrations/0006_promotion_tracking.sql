@@ -0,0 +1,31 @@
+-- Links a testnet contract package to the mainnet package it is promoted to
+CREATE TABLE IF NOT EXISTS contract_package_links (
+    user_id UUID NOT NULL,
+    testnet_package_hash TEXT NOT NULL,
+    mainnet_package_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, testnet_package_hash),
+    UNIQUE (user_id, mainnet_package_hash),
+    FOREIGN KEY (testnet_package_hash, user_id)
+        REFERENCES contract_packages (package_hash, user_id)
+        ON DELETE CASCADE,
+    FOREIGN KEY (mainnet_package_hash, user_id)
+        REFERENCES contract_packages (package_hash, user_id)
+        ON DELETE CASCADE
+);
+
+-- Alerts raised for tracked contract packages, dedup_key keeps a condition from being raised twice
+CREATE TABLE IF NOT EXISTS alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    package_hash TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    message TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    dedup_key TEXT NOT NULL,
+    acknowledged BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, dedup_key)
+);
+
+CREATE INDEX IF NOT EXISTS alerts_user_created ON alerts (user_id, created_at DESC);
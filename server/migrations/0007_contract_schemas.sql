@@ -0,0 +1,13 @@
+-- Odra contract schemas attached to contract versions, uploaded or discovered in the contract wasm
+CREATE TABLE IF NOT EXISTS contract_schemas (
+    contract_package_hash TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    user_id UUID NOT NULL,
+    contract_schema JSONB NOT NULL,
+    source TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (contract_package_hash, version, user_id),
+    FOREIGN KEY (contract_package_hash, version, user_id)
+        REFERENCES contract_versions (contract_package_hash, version, user_id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,24 @@
+-- Reproducible build verifications of contract versions against their on-chain Wasm
+CREATE TABLE IF NOT EXISTS contract_verifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_package_hash TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    user_id UUID NOT NULL,
+    commit_id TEXT NOT NULL,
+    contract_name TEXT NOT NULL,
+    toolchain TEXT NOT NULL,
+    cargo_odra_version TEXT NOT NULL,
+    features TEXT[] NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL,
+    expected_wasm_hash TEXT NOT NULL,
+    built_wasm_hash TEXT,
+    build_log TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    completed_at TIMESTAMPTZ,
+    FOREIGN KEY (contract_package_hash, version, user_id)
+        REFERENCES contract_versions (contract_package_hash, version, user_id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS contract_verifications_version
+    ON contract_verifications (contract_package_hash, version, user_id, created_at DESC);
@@ -0,0 +1,36 @@
+-- Transactions targeting tracked contract packages, shared by every user tracking the package
+CREATE TABLE IF NOT EXISTS transactions (
+    network TEXT NOT NULL,
+    deploy_hash TEXT NOT NULL,
+    contract_package_hash TEXT NOT NULL,
+    contract_hash TEXT,
+    block_hash TEXT,
+    block_height BIGINT,
+    caller_public_key TEXT NOT NULL,
+    execution_type_id BIGINT,
+    entry_point_id BIGINT,
+    args JSONB,
+    payment_amount NUMERIC(78, 0) NOT NULL DEFAULT 0,
+    cost NUMERIC(78, 0) NOT NULL DEFAULT 0,
+    status TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    error_message TEXT,
+    PRIMARY KEY (network, deploy_hash)
+);
+
+CREATE INDEX IF NOT EXISTS transactions_package_timestamp
+    ON transactions (network, contract_package_hash, timestamp DESC);
+CREATE INDEX IF NOT EXISTS transactions_package_caller
+    ON transactions (network, contract_package_hash, caller_public_key);
+
+-- Paging position of the indexer per package or contract hash it pages through
+CREATE TABLE IF NOT EXISTS transaction_cursors (
+    network TEXT NOT NULL,
+    target_hash TEXT NOT NULL,
+    contract_package_hash TEXT NOT NULL,
+    next_page INTEGER NOT NULL DEFAULT 1,
+    backfill_complete BOOLEAN NOT NULL DEFAULT FALSE,
+    newest_timestamp TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (network, target_hash)
+);
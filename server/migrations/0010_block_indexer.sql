@@ -0,0 +1,37 @@
+-- Entry point name resolved by the block indexer, cspr.live only reports an entry point id
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS entry_point_name TEXT;
+
+-- Next block height the block indexer reads per network
+CREATE TABLE IF NOT EXISTS block_index_cursors (
+    network TEXT PRIMARY KEY,
+    next_height BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Blocks the block indexer failed to read, retried until they succeed or run out of attempts
+CREATE TABLE IF NOT EXISTS block_index_gaps (
+    network TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (network, height)
+);
+
+-- Transactions that installed or upgraded any contract package, the source of package and version
+-- metadata when no explorer API is used
+CREATE TABLE IF NOT EXISTS contract_package_writes (
+    network TEXT NOT NULL,
+    contract_package_hash TEXT NOT NULL,
+    deploy_hash TEXT NOT NULL,
+    block_height BIGINT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    caller_public_key TEXT NOT NULL,
+    cost NUMERIC(78, 0) NOT NULL DEFAULT 0,
+    contract_hashes TEXT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY (network, contract_package_hash, deploy_hash)
+);
+
+CREATE INDEX IF NOT EXISTS contract_package_writes_contract_hashes
+    ON contract_package_writes USING GIN (contract_hashes);
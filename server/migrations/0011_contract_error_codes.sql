@@ -0,0 +1,32 @@
+-- Names of the user error codes of a package, taking precedence over the errors of its Odra schemas
+CREATE TABLE IF NOT EXISTS contract_error_codes (
+    contract_package_hash TEXT NOT NULL,
+    user_id UUID NOT NULL,
+    code INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    PRIMARY KEY (contract_package_hash, user_id, code),
+    FOREIGN KEY (contract_package_hash, user_id)
+        REFERENCES contract_packages (package_hash, user_id)
+        ON DELETE CASCADE
+);
+
+-- Contract version an indexed transaction called: the version of its contract hash, otherwise the
+-- latest version installed before it for calls through the package
+CREATE OR REPLACE FUNCTION transaction_contract_version(
+    package_hash TEXT,
+    owner_user_id UUID,
+    called_contract_hash TEXT,
+    called_at TIMESTAMPTZ
+) RETURNS INTEGER
+LANGUAGE SQL STABLE AS $$
+    SELECT v.version
+    FROM contract_versions v
+    WHERE v.contract_package_hash = package_hash AND v.user_id = owner_user_id
+        AND CASE
+            WHEN called_contract_hash IS NULL THEN v.age <= called_at
+            ELSE regexp_replace(v.contract_hash, '^[a-z-]*-', '') = regexp_replace(called_contract_hash, '^[a-z-]*-', '')
+        END
+    ORDER BY v.version DESC
+    LIMIT 1
+$$;
@@ -0,0 +1,38 @@
+-- Baselines of the transaction activity of a package, refreshed by the anomaly detection
+CREATE TABLE IF NOT EXISTS transaction_baselines (
+    contract_package_hash TEXT NOT NULL,
+    user_id UUID NOT NULL,
+    metric TEXT NOT NULL,
+    mean DOUBLE PRECISION NOT NULL,
+    stddev DOUBLE PRECISION NOT NULL,
+    samples BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (contract_package_hash, user_id, metric),
+    FOREIGN KEY (contract_package_hash, user_id)
+        REFERENCES contract_packages (package_hash, user_id)
+        ON DELETE CASCADE
+);
+
+-- Windows of transaction activity deviating from the baselines of a package
+CREATE TABLE IF NOT EXISTS transaction_anomalies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_package_hash TEXT NOT NULL,
+    user_id UUID NOT NULL,
+    metric TEXT NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    window_end TIMESTAMPTZ NOT NULL,
+    observed DOUBLE PRECISION NOT NULL,
+    expected DOUBLE PRECISION NOT NULL,
+    p_value DOUBLE PRECISION,
+    message TEXT NOT NULL,
+    evidence JSONB NOT NULL DEFAULT '{}'::jsonb,
+    dedup_key TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, dedup_key),
+    FOREIGN KEY (contract_package_hash, user_id)
+        REFERENCES contract_packages (package_hash, user_id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS transaction_anomalies_package_window
+    ON transaction_anomalies (contract_package_hash, user_id, window_start DESC);
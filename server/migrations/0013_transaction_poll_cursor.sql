@@ -0,0 +1,2 @@
+-- Timestamp polling has to reach before it starts over from the first page, set while it is behind
+ALTER TABLE transaction_cursors ADD COLUMN IF NOT EXISTS poll_until TIMESTAMPTZ;
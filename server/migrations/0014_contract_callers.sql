@@ -0,0 +1,16 @@
+-- Transactions that reached a tracked package through another contract or session code, found by
+-- the block indexer in their execution effects
+CREATE TABLE IF NOT EXISTS indirect_calls (
+    network TEXT NOT NULL,
+    deploy_hash TEXT NOT NULL,
+    contract_package_hash TEXT NOT NULL,
+    -- Contract the transaction called, NULL for session code
+    caller_contract_hash TEXT,
+    caller_public_key TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    error_message TEXT,
+    PRIMARY KEY (network, deploy_hash, contract_package_hash)
+);
+
+CREATE INDEX IF NOT EXISTS indirect_calls_package_timestamp
+    ON indirect_calls (network, contract_package_hash, timestamp DESC);
@@ -0,0 +1,19 @@
+-- Time the explorer indexer started the sweep of a target it is in, and the start of the last sweep
+-- it completed, all transactions up to which are indexed
+ALTER TABLE transaction_cursors ADD COLUMN IF NOT EXISTS sweep_started_at TIMESTAMPTZ;
+ALTER TABLE transaction_cursors ADD COLUMN IF NOT EXISTS indexed_until TIMESTAMPTZ;
+
+-- Timestamp of the last block the block indexer read per network
+ALTER TABLE block_index_cursors ADD COLUMN IF NOT EXISTS indexed_until TIMESTAMPTZ;
+
+-- End of the last hour the anomaly detection checked per package
+CREATE TABLE IF NOT EXISTS anomaly_check_cursors (
+    contract_package_hash TEXT NOT NULL,
+    user_id UUID NOT NULL,
+    checked_until TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (contract_package_hash, user_id),
+    FOREIGN KEY (contract_package_hash, user_id)
+        REFERENCES contract_packages (package_hash, user_id)
+        ON DELETE CASCADE
+);
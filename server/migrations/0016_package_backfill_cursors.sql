@@ -0,0 +1,11 @@
+-- Blocks the block indexer rescans for a package registered after it passed them, from the install
+-- of the package or the configured backfill height up to the block the indexer was at
+CREATE TABLE IF NOT EXISTS package_backfill_cursors (
+    network TEXT NOT NULL,
+    contract_package_hash TEXT NOT NULL,
+    next_height BIGINT NOT NULL,
+    end_height BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (network, contract_package_hash)
+);
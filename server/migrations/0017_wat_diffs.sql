@@ -0,0 +1,10 @@
+-- Cache of the unified diff lines between the WAT disassembly of two contract wasms, keyed by raw
+-- wasm hashes and the function the diff is restricted to, empty for the whole module
+CREATE TABLE IF NOT EXISTS wat_diffs (
+    from_wasm_hash TEXT NOT NULL,
+    to_wasm_hash TEXT NOT NULL,
+    function TEXT NOT NULL DEFAULT '',
+    lines TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (from_wasm_hash, to_wasm_hash, function)
+);
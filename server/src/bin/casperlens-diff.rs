@@ -0,0 +1,147 @@
+//! Diff a locally built Odra contract against the deployed version tracked by CasperLens.
+//!
+//! Exits with status 1 when the upgrade is at least as severe as `--fail-on`, so CI can block
+//! pull requests that would break integrators.
+
+use std::process::ExitCode;
+
+use base64::Engine;
+
+const USAGE: &str = "Usage: casperlens-diff --user <user-id> --package <package-hash> --wasm <path> --schema <path> [--server <url>] [--fail-on breaking|potentially-breaking|never]";
+
+struct Args {
+    server: String,
+    user: String,
+    package: String,
+    wasm: String,
+    schema: String,
+    fail_on: Option<&'static str>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut server = std::env::var("CASPERLENS_SERVER_URL")
+        .unwrap_or_else(|_| "http://localhost:8000".to_string());
+    let (mut user, mut package, mut wasm, mut schema) = (None, None, None, None);
+    let mut fail_on = Some("Breaking");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--server" => server = value,
+            "--user" => user = Some(value),
+            "--package" => package = Some(value),
+            "--wasm" => wasm = Some(value),
+            "--schema" => schema = Some(value),
+            "--fail-on" => {
+                fail_on = match value.as_str() {
+                    "breaking" => Some("Breaking"),
+                    "potentially-breaking" => Some("PotentiallyBreaking"),
+                    "never" => None,
+                    other => return Err(format!("Unknown --fail-on level: {}", other)),
+                }
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(Args {
+        server: server.trim_end_matches('/').to_string(),
+        user: user.ok_or("Missing --user")?,
+        package: package.ok_or("Missing --package")?,
+        wasm: wasm.ok_or("Missing --wasm")?,
+        schema: schema.ok_or("Missing --schema")?,
+        fail_on,
+    })
+}
+
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "Breaking" => 2,
+        "PotentiallyBreaking" => 1,
+        _ => 0,
+    }
+}
+
+async fn run(args: Args) -> Result<bool, String> {
+    let wasm = std::fs::read(&args.wasm).map_err(|e| format!("Failed to read Wasm: {}", e))?;
+    let schema = std::fs::read_to_string(&args.schema)
+        .map_err(|e| format!("Failed to read schema: {}", e))?;
+    let schema: serde_json::Value =
+        serde_json::from_str(&schema).map_err(|e| format!("Invalid schema JSON: {}", e))?;
+
+    let url = format!(
+        "{}/api/v1/u/{}/contract-package/{}/diff/local",
+        args.server, args.user, args.package
+    );
+    let body = serde_json::json!({
+        "wasm": base64::engine::general_purpose::STANDARD.encode(wasm),
+        "schema": schema,
+    });
+    let response: serde_json::Value = reqwest::Client::new()
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response: {}", e))?;
+
+    if response["success"] != serde_json::Value::Bool(true) {
+        return Err(format!(
+            "{}: {}",
+            response["message"].as_str().unwrap_or("Request failed"),
+            response["error"].as_str().unwrap_or_default()
+        ));
+    }
+
+    let assessment = &response["data"]["assessment"];
+    let severity = assessment["severity"].as_str().unwrap_or("Compatible");
+    println!(
+        "Upgrade severity: {}, suggested {} version bump",
+        severity,
+        assessment["semver_bump"]
+            .as_str()
+            .unwrap_or("Patch")
+            .to_lowercase()
+    );
+    for change in assessment["changes"].as_array().into_iter().flatten() {
+        println!(
+            "  [{}] {}: {} ({})",
+            change["compatibility"].as_str().unwrap_or_default(),
+            change["subject"].as_str().unwrap_or_default(),
+            change["change"].as_str().unwrap_or_default(),
+            change["reason"].as_str().unwrap_or_default()
+        );
+    }
+
+    Ok(args
+        .fail_on
+        .is_some_and(|level| severity_rank(severity) >= severity_rank(level)))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args).await {
+        Ok(false) => ExitCode::SUCCESS,
+        Ok(true) => {
+            eprintln!("Upgrade fails the configured compatibility level");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(2)
+        }
+    }
+}
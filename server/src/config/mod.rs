@@ -1,4 +1,4 @@
-use std::{env, fs::File, io::Write};
+use std::{collections::HashMap, env, fs::File, io::Write};
 
 use base64::Engine;
 use dotenvy::dotenv;
@@ -7,40 +7,169 @@ use sqlx::PgPool;
 
 use serde::Deserialize;
 
+/// Where transactions and package metadata come from
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum IndexerMode {
+    /// cspr.live explorer API
+    Explorer,
+    /// Blocks read from the configured nodes, for networks without an explorer
+    Node,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub(crate) database_url: String,
     pub(crate) _jwt_secret: String,
     pub(crate) web_url: String,
-    pub(crate) mainnet_node_address: String,
-    pub(crate) testnet_node_address: String,
+    /// RPC node address per network, from `{NETWORK}_NODE_ADDRESS`
+    pub(crate) node_addresses: HashMap<String, String>,
     pub(crate) huggingface_token: String,
-    pub(crate) secret_key: String,
+    pub(crate) _secret_key: String,
     pub(crate) observability_package_hash: String,
+    pub(crate) named_key_resolution_depth: usize,
+    pub(crate) named_key_value_size_cap: usize,
+    pub(crate) build_work_dir: String,
+    /// Vendored crates used by verification builds whose archive ships none
+    pub(crate) build_vendor_dir: Option<String>,
+    /// Command the verification build is wrapped in, e.g. `bwrap` or `unshare` with their arguments.
+    /// Verification builds are refused while it is unset.
+    pub(crate) build_sandbox_command: Option<String>,
+    pub(crate) build_timeout_secs: u64,
+    pub(crate) transaction_index_interval_secs: u64,
+    pub(crate) indexer_mode: IndexerMode,
+    /// Block height the node indexer starts from per network it has not indexed yet, from
+    /// `{NETWORK}_INDEXER_START_HEIGHT`
+    pub(crate) indexer_start_heights: HashMap<String, u64>,
+    /// Block height the node indexer rescans from per network for packages installed before the
+    /// start height, from `{NETWORK}_INDEXER_BACKFILL_HEIGHT`
+    pub(crate) indexer_backfill_heights: HashMap<String, u64>,
+    pub(crate) indexer_networks: Vec<String>,
+    pub(crate) gas_regression_interval_secs: u64,
+    pub(crate) anomaly_detection_interval_secs: u64,
+    pub(crate) promotion_check_interval_secs: u64,
+}
+
+impl Config {
+    /// RPC address of the node of a network, `None` when the network has no node configured
+    pub(crate) fn node_address(&self, network: &str) -> Option<&str> {
+        self.node_addresses.get(network).map(String::as_str)
+    }
+}
+
+/// Prefix of the variables holding the settings of a network, e.g. `NCTL` for `nctl`
+pub(crate) fn network_env_prefix(network: &str) -> String {
+    network.to_uppercase().replace('-', "_")
+}
+
+/// Block heights set per network with `{NETWORK}_{name}`, networks without one are left out
+fn get_network_heights(networks: &[String], name: &str) -> HashMap<String, u64> {
+    networks
+        .iter()
+        .filter_map(|network| {
+            env::var(format!("{}_{}", network_env_prefix(network), name))
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(|height| (network.clone(), height))
+        })
+        .collect()
 }
 
 pub fn load_config() -> Config {
     dotenv().ok();
     write_secret_file();
+    let indexer_networks: Vec<String> = env::var("INDEXER_NETWORKS")
+        .unwrap_or("mainnet,testnet".to_string())
+        .split(',')
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .collect();
+
+    let mut node_addresses = HashMap::from([
+        (
+            "mainnet".to_string(),
+            env::var("MAINNET_NODE_ADDRESS")
+                .unwrap_or("https://node.mainnet.casper.network".to_string()),
+        ),
+        (
+            "testnet".to_string(),
+            env::var("TESTNET_NODE_ADDRESS")
+                .unwrap_or("https://node.testnet.casper.network".to_string()),
+        ),
+    ]);
+    // Private networks and NCTL setups come with their own node, there is no public one to default to
+    for network in &indexer_networks {
+        if !node_addresses.contains_key(network) {
+            let name = format!("{}_NODE_ADDRESS", network_env_prefix(network));
+            let address = env::var(&name).unwrap_or_else(|_| panic!("{} is not set", name));
+            node_addresses.insert(network.clone(), address);
+        }
+    }
+    let indexer_start_heights = get_network_heights(&indexer_networks, "INDEXER_START_HEIGHT");
+    let indexer_backfill_heights =
+        get_network_heights(&indexer_networks, "INDEXER_BACKFILL_HEIGHT");
+
     Config {
         database_url: env::var("DATABASE_URL").expect("DATABASE_URL is not set in .env"),
         _jwt_secret: env::var("JWT_SECRET").expect("JWT_SECRET is not set in .env"),
         web_url: env::var("WEB_URL").unwrap_or("http://localhost:3000".to_string()),
-        mainnet_node_address: env::var("MAINNET_NODE_ADDRESS")
-            .unwrap_or("https://node.mainnet.casper.network".to_string()),
-        testnet_node_address: env::var("TESTNET_NODE_ADDRESS")
-            .unwrap_or("https://node.testnet.casper.network".to_string()),
+        node_addresses,
         huggingface_token: env::var("HUGGINGFACE_TOKEN").expect("HUGGINGFACE_TOKEN is not set"),
-        secret_key: env::var("SECRET_KEY").expect("SECRET_KEY is not set"),
+        _secret_key: env::var("SECRET_KEY").expect("SECRET_KEY is not set"),
         observability_package_hash: env::var("OBSERVABILITY_PACKAGE_HASH")
             .expect("OBSERVABILITY_PACKAGE_HASH is not set"),
+        named_key_resolution_depth: env::var("NAMED_KEY_RESOLUTION_DEPTH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2),
+        named_key_value_size_cap: env::var("NAMED_KEY_VALUE_SIZE_CAP")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(4096),
+        build_work_dir: env::var("BUILD_WORK_DIR").unwrap_or_else(|_| {
+            env::temp_dir()
+                .join("casperlens-builds")
+                .to_string_lossy()
+                .to_string()
+        }),
+        build_vendor_dir: env::var("BUILD_VENDOR_DIR").ok(),
+        build_sandbox_command: env::var("BUILD_SANDBOX_COMMAND")
+            .ok()
+            .filter(|c| !c.trim().is_empty()),
+        build_timeout_secs: env::var("BUILD_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(900),
+        transaction_index_interval_secs: env::var("TRANSACTION_INDEX_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60),
+        indexer_mode: match env::var("INDEXER_MODE").as_deref() {
+            Ok("node") => IndexerMode::Node,
+            Ok("explorer") | Err(_) => IndexerMode::Explorer,
+            Ok(other) => panic!("Unknown INDEXER_MODE: {}", other),
+        },
+        indexer_start_heights,
+        indexer_backfill_heights,
+        indexer_networks,
+        gas_regression_interval_secs: env::var("GAS_REGRESSION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600),
+        anomaly_detection_interval_secs: env::var("ANOMALY_DETECTION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(900),
+        promotion_check_interval_secs: env::var("PROMOTION_CHECK_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(900),
     }
 }
 
 fn write_secret_file() {
     match env::var("SECRET_KEY") {
         Ok(base64_string) => {
-            let cleaned_string = base64_string.replace('\n', "").replace(' ', "");
+            let cleaned_string = base64_string.replace(['\n', ' '], "");
             match base64::engine::general_purpose::STANDARD.decode(cleaned_string) {
                 Ok(decoded_bytes) => {
                     let mut file = match File::create("./secret-key.pem") {
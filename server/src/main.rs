@@ -10,10 +10,17 @@ use sqlx::migrate::Migrator;
 use std::io;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::trace::TraceLayer;
 use tracing::Level;
 
-use crate::config::AppState;
+use crate::config::{AppState, IndexerMode};
+use crate::services::database::verification::fail_interrupted_contract_verifications;
+use crate::services::tasks::anomaly::run_anomaly_detection;
+use crate::services::tasks::block::{IndexedNetwork, run_block_indexer};
+use crate::services::tasks::gas::run_gas_regression_checks;
+use crate::services::tasks::promotion::run_promotion_drift_checks;
+use crate::services::tasks::transaction::run_transaction_indexer;
 use crate::utils::{create_db_pool, create_router};
 
 static _MIGRATOR: Migrator = sqlx::migrate!("./migrations");
@@ -30,9 +37,57 @@ async fn main() -> io::Result<()> {
         .run(&pool)
         .await
         .expect("Failed to run migrations");
+    match fail_interrupted_contract_verifications(&pool).await {
+        Ok(0) => {}
+        Ok(count) => log::warn!("Marked {} interrupted verification builds as failed", count),
+        Err(e) => log::error!("Failed to clean up interrupted verification builds: {}", e),
+    }
+    let config = config::load_config();
+    let index_interval = Duration::from_secs(config.transaction_index_interval_secs);
+    match config.indexer_mode {
+        IndexerMode::Explorer => {
+            tokio::spawn(run_transaction_indexer(pool.clone(), index_interval));
+        }
+        IndexerMode::Node => {
+            let networks = config
+                .indexer_networks
+                .iter()
+                .map(|network| IndexedNetwork {
+                    network: network.clone(),
+                    // Loading the config fails for indexed networks without a node address
+                    node_address: config.node_address(network).unwrap_or_default().to_string(),
+                    start_height: config.indexer_start_heights.get(network).copied(),
+                })
+                .collect();
+            tokio::spawn(run_block_indexer(pool.clone(), networks, index_interval));
+        }
+    }
+    tokio::spawn(run_gas_regression_checks(
+        pool.clone(),
+        Duration::from_secs(config.gas_regression_interval_secs),
+    ));
+    tokio::spawn(run_anomaly_detection(
+        pool.clone(),
+        config.indexer_mode,
+        Duration::from_secs(config.anomaly_detection_interval_secs),
+    ));
+    tokio::spawn(run_promotion_drift_checks(
+        pool.clone(),
+        (
+            config
+                .node_address("testnet")
+                .unwrap_or_default()
+                .to_string(),
+            config
+                .node_address("mainnet")
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        Duration::from_secs(config.promotion_check_interval_secs),
+    ));
     let app = create_router(Arc::new(AppState {
         db: pool.clone(),
-        config: config::load_config(),
+        config,
     }));
     let app = app.layer(TraceLayer::new_for_http());
     let addr = SocketAddr::from(([0, 0, 0, 0], 8000));
@@ -8,7 +8,13 @@ pub fn get_cors_config() -> CorsLayer {
     let web_url = config.web_url.clone();
     let allowed_origins = vec![web_url.parse().unwrap()];
 
-    let allowed_methods = vec![Method::GET, Method::POST, Method::OPTIONS];
+    let allowed_methods = vec![
+        Method::GET,
+        Method::POST,
+        Method::PUT,
+        Method::DELETE,
+        Method::OPTIONS,
+    ];
 
     let allowed_headers = vec![
         header::COOKIE,
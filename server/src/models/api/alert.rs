@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct AlertsQuery {
+    pub package_hash: Option<String>,
+    pub unacknowledged: Option<bool>,
+}
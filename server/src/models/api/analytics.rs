@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::schema::anomaly::AnomalyMetric;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalyticsBucket {
+    Hour,
+    Day,
+}
+
+impl AnalyticsBucket {
+    /// Unit as `date_trunc` and intervals expect it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalyticsBucket::Hour => "hour",
+            AnalyticsBucket::Day => "day",
+        }
+    }
+}
+
+/// Grouping of call statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsGroup {
+    None,
+    EntryPoint,
+    Version,
+}
+
+impl AnalyticsGroup {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalyticsGroup::None => "none",
+            AnalyticsGroup::EntryPoint => "entry_point",
+            AnalyticsGroup::Version => "version",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Bucket of the call time series, `day` by default
+    pub bucket: Option<AnalyticsBucket>,
+    /// Limit the time series to one entry point
+    pub entry_point: Option<String>,
+    /// Number of top callers
+    pub limit: Option<u64>,
+}
+
+/// Call statistics of the indexed transactions of a package, costs are in motes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallStats {
+    pub calls: u64,
+    pub failures: u64,
+    pub success_rate: f64,
+    pub unique_callers: u64,
+    pub cost_avg: Option<f64>,
+    pub cost_p50: Option<f64>,
+    pub cost_p90: Option<f64>,
+    pub cost_p99: Option<f64>,
+}
+
+/// Call statistics of one entry point or contract version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedCallStats {
+    /// Entry point name or contract version, `None` for transactions it could not be resolved for
+    pub group: Option<String>,
+    #[serde(flatten)]
+    pub stats: CallStats,
+}
+
+/// Calls in one bucket of the time series, empty buckets included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallBucket {
+    pub bucket: DateTime<Utc>,
+    pub calls: u64,
+    pub failures: u64,
+    pub unique_callers: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallSeries {
+    pub bucket: AnalyticsBucket,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub data: Vec<CallBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopCaller {
+    pub caller_public_key: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub total_cost: String,
+    pub first_call: DateTime<Utc>,
+    pub last_call: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImpactQuery {
+    pub v1: u32,
+    pub v2: u32,
+    /// Days before the upgrade to look for callers, 30 by default
+    pub lookback_days: Option<u32>,
+}
+
+/// Entry point of the older version that is removed or changed in a breaking way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffectedEntryPoint {
+    pub name: String,
+    /// Breaking changes of the entry point, as classified by the upgrade assessment
+    pub changes: Vec<String>,
+}
+
+/// Account that called an affected entry point before the upgrade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactedCaller {
+    pub caller_public_key: String,
+    pub entry_point_name: String,
+    /// Calls within the lookback window
+    pub calls: u64,
+    pub last_call: DateTime<Utc>,
+    pub calls_after_upgrade: u64,
+    pub failures_after_upgrade: u64,
+    pub first_failure_after_upgrade: Option<DateTime<Utc>>,
+}
+
+/// Contract, or session code, that called into the package before the upgrade.
+///
+/// Found in the execution effects by the block indexer, which do not tell the entry point called,
+/// so the caller is not attributed to any affected entry point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageContractCaller {
+    /// Unset for session code
+    pub caller_contract_hash: Option<String>,
+    /// Calls within the lookback window
+    pub calls: u64,
+    pub last_call: DateTime<Utc>,
+    pub calls_after_upgrade: u64,
+    pub failures_after_upgrade: u64,
+    pub first_failure_after_upgrade: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactReport {
+    pub contract_package_hash: String,
+    pub v1: u32,
+    pub v2: u32,
+    pub upgraded_at: DateTime<Utc>,
+    pub lookback_from: DateTime<Utc>,
+    pub affected_entry_points: Vec<AffectedEntryPoint>,
+    /// Callers per affected entry point, the most frequent first
+    pub callers: Vec<ImpactedCaller>,
+    /// Distinct accounts among the callers
+    pub impacted_accounts: u64,
+    /// Distinct accounts whose calls failed after the upgrade
+    pub failing_accounts: u64,
+    /// Contracts and session code calling any entry point of the package, the most frequent
+    /// first, only reported by the block indexer. They are not counted as impacted, the entry
+    /// points they call are unknown.
+    pub package_contract_callers: Vec<PackageContractCaller>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntryPointUsageQuery {
+    /// Days without calls after which an entry point counts as idle, 30 by default
+    pub idle_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryPointUsageStatus {
+    /// Never called on this version
+    Unused,
+    /// Not called within the idle window
+    Idle,
+    /// Only called by the package owner or the deployers of its versions
+    OwnerOnly,
+    Active,
+}
+
+/// Calls of one entry point of a contract version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryPointUsage {
+    pub name: String,
+    pub public: bool,
+    pub calls: u64,
+    pub callers: u64,
+    pub owner_calls: u64,
+    pub last_call: Option<DateTime<Utc>>,
+    pub status: EntryPointUsageStatus,
+    /// Public entry point only the owner calls, likely an admin function without access control
+    pub risky: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryPointUsageReport {
+    pub contract_package_hash: String,
+    pub contract_version: u32,
+    pub idle_days: u32,
+    /// Accounts whose calls count as owner calls
+    pub owners: Vec<String>,
+    pub entry_points: Vec<EntryPointUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnomaliesQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub metric: Option<AnomalyMetric>,
+    /// Most recent anomalies returned, 50 by default
+    pub limit: Option<u64>,
+}
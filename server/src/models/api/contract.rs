@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::schema::{
+    odra::{OdraContractSchema, OdraStorageField},
+    verification::ContractVerificationSchema,
+};
+
 #[derive(Debug, Deserialize)]
 pub struct RegisterContractRequest {
     pub(crate) package_hash: String,
@@ -36,6 +41,8 @@ pub struct ContractVersionData {
     pub entry_points: Vec<String>,
     pub disabled: bool,
     pub age: String,
+    /// Latest reproducible build verification of the version
+    pub verification: Option<ContractVerificationSchema>,
 }
 
 #[derive(Debug, Serialize)]
@@ -48,3 +55,76 @@ pub struct ContractData {
     pub age: i64,
     pub versions: Vec<ContractVersionData>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ContractHistoryQuery {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    /// Comma separated block heights to sample instead of `from`/`to`
+    pub heights: Option<String>,
+    pub bisect: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContractWatDiffQuery {
+    pub v1: u32,
+    pub v2: u32,
+    /// Export or debug name of a single function to restrict the diff to
+    pub function: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+/// Storage field to read by name, with the mapping key to read for `Mapping` fields
+#[derive(Debug, Deserialize)]
+pub struct OdraStorageRead {
+    pub name: String,
+    pub key: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OdraStorageQuery {
+    /// Fields to read from the storage layout, every `Var` when empty
+    #[serde(default)]
+    pub reads: Vec<OdraStorageRead>,
+    /// Fields laid out by hand, overriding the schema field of the same name
+    #[serde(default)]
+    pub fields: Vec<OdraStorageField>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContractChangelogQuery {
+    pub from: u32,
+    pub to: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContractCompareQuery {
+    pub from_version: u32,
+    pub to_package: String,
+    pub to_version: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkContractPackageRequest {
+    pub mainnet_package_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocalDiffRequest {
+    /// Base64 encoded Wasm built locally
+    pub wasm: String,
+    pub schema: OdraContractSchema,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyBuildRequest {
+    /// Base64 encoded `.tar.gz` archive of the contract source and its vendored dependencies
+    pub source: String,
+    pub commit_id: String,
+    pub contract_name: String,
+    pub toolchain: String,
+    pub cargo_odra_version: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
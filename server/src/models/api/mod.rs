@@ -1,4 +1,7 @@
+pub mod alert;
+pub mod analytics;
 pub mod contract;
+pub mod rules;
 pub mod transaction;
 
 use serde::{Deserialize, Serialize};
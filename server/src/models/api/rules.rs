@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+use crate::models::schema::rules::{DiffRuleAction, DiffRuleTarget};
+
+#[derive(Debug, Deserialize)]
+pub struct DiffRuleRequest {
+    /// Package to scope the rule to, omitted for a rule applied to every package
+    pub package_hash: Option<String>,
+    pub target: DiffRuleTarget,
+    pub action: DiffRuleAction,
+    pub pattern: String,
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffRulesQuery {
+    pub package_hash: Option<String>,
+}
@@ -1,5 +1,9 @@
+use casper_types::CLType;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::schema::transaction::{TransactionFailure, TransactionFailureKind};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Transaction {
     pub deploy_hash: String,
@@ -10,12 +14,51 @@ pub struct Transaction {
     pub contract_package_hash: Option<String>,
     pub contract_hash: Option<String>,
     pub entry_point_id: Option<u64>,
+    /// Only known for transactions read from a node
+    #[serde(default)]
+    pub entry_point_name: Option<String>,
     pub args: Option<serde_json::Value>,
     pub payment_amount: String,
     pub cost: String,
     pub status: String,
     pub timestamp: String,
     pub error_message: Option<String>,
+    /// Decoded against the called contract version, only set in API responses
+    #[serde(default)]
+    pub call: Option<DecodedCall>,
+    /// Parsed from `error_message`, only set in API responses
+    #[serde(default)]
+    pub failure: Option<TransactionFailure>,
+}
+
+/// Runtime argument of a transaction, decoded with the type of the matching entry point parameter
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DecodedArg {
+    pub name: String,
+    /// Declared type, or the type passed by the caller when the entry point has no such parameter
+    pub cl_type: Option<CLType>,
+    pub value: serde_json::Value,
+    /// Whether the argument is a parameter of the entry point and was passed with its type
+    pub matches_signature: bool,
+}
+
+/// Contract version and entry point a transaction called, with its named arguments
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DecodedCall {
+    pub contract_version: u32,
+    pub entry_point_name: String,
+    pub args: Vec<DecodedArg>,
+    /// Parameters of the entry point the transaction did not pass
+    pub missing_args: Vec<String>,
+    /// Call rendered as `name(arg=value, ..)`
+    pub display: String,
+}
+
+/// Entry point of a contract as listed by cspr.live, transactions only refer to it by id
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContractEntryPoint {
+    pub id: u64,
+    pub name: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,3 +67,49 @@ pub struct TransactionsResponse {
     pub page_count: u64,
     pub data: Vec<Transaction>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionsQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub caller: Option<String>,
+    pub status: Option<String>,
+    pub contract_hash: Option<String>,
+    pub entry_point_id: Option<u64>,
+    pub entry_point_name: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionFailuresQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Failures of one kind and error code
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FailureCount {
+    pub kind: TransactionFailureKind,
+    pub code: Option<u32>,
+    pub error_name: Option<String>,
+    pub count: u64,
+}
+
+/// Calls and failures of one entry point or contract version, most frequent failures first
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FailureBreakdown {
+    /// Entry point name or contract version, `None` for transactions it could not be resolved for
+    pub group: Option<String>,
+    pub calls: u64,
+    pub failures: u64,
+    pub failure_rate: f64,
+    pub breakdown: Vec<FailureCount>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContractErrorCodeRequest {
+    pub code: u32,
+    pub name: String,
+    pub description: Option<String>,
+}
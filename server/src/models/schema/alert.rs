@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub package_hash: String,
+    pub kind: String,
+    pub message: String,
+    pub details: serde_json::Value,
+    /// Identifies the condition behind the alert, the same condition is only raised once
+    pub dedup_key: String,
+    pub acknowledged: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AlertSchema {
+    pub fn new(
+        user_id: Uuid,
+        package_hash: String,
+        kind: &str,
+        message: String,
+        details: serde_json::Value,
+        dedup_key: String,
+    ) -> Self {
+        AlertSchema {
+            id: Uuid::new_v4(),
+            user_id,
+            package_hash,
+            kind: kind.to_string(),
+            message,
+            details,
+            dedup_key,
+            acknowledged: false,
+            created_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Transaction activity of a package the anomaly detection keeps a baseline of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnomalyMetric {
+    /// Calls per hour
+    CallRate,
+    /// Share of failed calls
+    FailureRate,
+    /// Callers per hour calling the package for the first time
+    NewCallerRate,
+    /// Cost of successful calls
+    Cost,
+    /// Calls of an admin entry point by callers unknown before
+    AdminCalls,
+}
+
+impl AnomalyMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyMetric::CallRate => "CallRate",
+            AnomalyMetric::FailureRate => "FailureRate",
+            AnomalyMetric::NewCallerRate => "NewCallerRate",
+            AnomalyMetric::Cost => "Cost",
+            AnomalyMetric::AdminCalls => "AdminCalls",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "CallRate" => Some(AnomalyMetric::CallRate),
+            "FailureRate" => Some(AnomalyMetric::FailureRate),
+            "NewCallerRate" => Some(AnomalyMetric::NewCallerRate),
+            "Cost" => Some(AnomalyMetric::Cost),
+            "AdminCalls" => Some(AnomalyMetric::AdminCalls),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionBaselineSchema {
+    pub contract_package_hash: String,
+    pub user_id: Uuid,
+    pub metric: AnomalyMetric,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Hours, calls or cost samples the baseline was computed from
+    pub samples: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionAnomalySchema {
+    pub id: Uuid,
+    pub contract_package_hash: String,
+    pub user_id: Uuid,
+    pub metric: AnomalyMetric,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub observed: f64,
+    pub expected: f64,
+    /// Probability of observing the window under the baseline, `None` for rule based anomalies
+    pub p_value: Option<f64>,
+    pub message: String,
+    pub evidence: serde_json::Value,
+    /// Identifies the window and subject of the anomaly, the same anomaly is only recorded once
+    pub dedup_key: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,8 +1,15 @@
-use casper_types::{Key, NamedKeys, contracts::EntryPoint};
+use casper_types::{
+    CLType, EntryPointAccess, EntryPointType, Key, NamedKeys, contracts::EntryPoint,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::models::schema::{
+    rules::{AppliedDiffRule, CollapsedDiff},
+    wasm::ContractWasmDiff,
+};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ContractPackageSchema {
     pub package_hash: String,
@@ -38,25 +45,8 @@ impl ContractPackageSchema {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct ContractPackageMeta {
-    pub account_info: Option<serde_json::Value>,
-    centralized_account_info: Option<serde_json::Value>,
-    coingecko_data: Option<serde_json::Value>,
-    coingecko_id: Option<String>,
-    contract_package_hash: String,
-    cspr_name: Option<String>,
-    csprtrade_data: Option<serde_json::Value>,
-    description: Option<String>,
-    has_ces_events: bool,
-    icon_url: Option<String>,
-    is_contract_info_approved: bool,
-    latest_version_contract_hash: Option<String>,
-    latest_version_contract_type_id: Option<u16>,
-    metadata: serde_json::Value,
-    name: Option<String>,
-    owner_hash: String,
     pub owner_public_key: String,
     pub timestamp: String,
-    website_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -77,24 +67,21 @@ pub struct ContractVersionSchema {
     pub named_keys: NamedKeys,
     pub disabled: bool,
     pub age: DateTime<Utc>,
+    pub deploy_hash: Option<String>,
+    pub block_height: Option<u64>,
+    pub deployer_public_key: Option<String>,
+    pub upgrade_cost: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ContractVersionMeta {
     pub contract_hash: String,
     pub contract_package_hash: String,
-    #[serde(skip)]
-    pub deploy_hash: String,
-    #[serde(skip)]
-    pub block_height: u64,
-    #[serde(skip)]
-    pub contract_type_id: Option<serde_json::Value>,
+    pub deploy_hash: Option<String>,
+    pub block_height: Option<u64>,
     pub timestamp: DateTime<Utc>,
     pub contract_version: u32,
     pub is_disabled: bool,
-
-    #[serde(skip)]
-    pub contract_package: Option<serde_json::Value>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -104,6 +91,84 @@ pub struct ContractVersionDiff {
     pub contract_package_hash: String,
     pub entry_points: Vec<ContractEntryPointDiff>,
     pub named_keys: Vec<ContractNamedKeysDiff>,
+    #[serde(default)]
+    pub wasm: Option<ContractWasmDiff>,
+    #[serde(default)]
+    pub named_key_values: Vec<ContractNamedKeyValueDiff>,
+    #[serde(default)]
+    pub assessment: Option<ContractUpgradeAssessment>,
+    #[serde(default)]
+    pub applied_rules: Vec<AppliedDiffRule>,
+    #[serde(default)]
+    pub collapsed: Vec<CollapsedDiff>,
+    /// Event changes, only known when both versions have a contract schema
+    #[serde(default)]
+    pub events: Vec<ContractEventDiff>,
+    /// Error enum changes, only known when both versions have a contract schema
+    #[serde(default)]
+    pub errors: Vec<ContractErrorDiff>,
+    /// Entry points that got more expensive, from the calls indexed so far
+    #[serde(default)]
+    pub gas_regressions: Vec<GasRegression>,
+}
+
+/// Entry point whose successful calls cost significantly more on the newer of two versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasRegression {
+    pub entry_point_name: String,
+    pub from_samples: u64,
+    pub to_samples: u64,
+    /// Median cost in motes
+    pub from_median: f64,
+    pub to_median: f64,
+    /// Relative increase of the median cost, 0.1 for 10%
+    pub increase: f64,
+    /// One-sided Mann-Whitney U test p-value
+    pub p_value: f64,
+}
+
+/// Event change between the contract schemas of two versions.
+///
+/// Events are compared by name, `from` and `to` hold the type definitions of the event structs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractEventDiff {
+    Added {
+        name: String,
+        ty: serde_json::Value,
+    },
+    Removed {
+        name: String,
+        ty: serde_json::Value,
+    },
+    Modified {
+        name: String,
+        from: serde_json::Value,
+        to: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractErrorDiff {
+    Added {
+        name: String,
+        discriminant: u16,
+    },
+    Removed {
+        name: String,
+        discriminant: u16,
+    },
+    /// Same error name with a different discriminant, callers matching on the code misread it
+    Renumbered {
+        name: String,
+        from: u16,
+        to: u16,
+    },
+    /// Same discriminant with a different error name
+    Renamed {
+        discriminant: u16,
+        from: String,
+        to: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -113,13 +178,65 @@ pub struct ContractVersionDiffMeta {
     pub contract_version: u32,
     pub is_disabled: bool,
     pub wasm_hash: String,
+    #[serde(default)]
+    pub deploy_hash: Option<String>,
+    #[serde(default)]
+    pub block_height: Option<u64>,
+    #[serde(default)]
+    pub deployer_public_key: Option<String>,
+    #[serde(default)]
+    pub upgrade_cost: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ContractEntryPointDiff {
     Added(EntryPoint),
     Removed(EntryPoint),
-    Modified { from: EntryPoint, to: EntryPoint },
+    Modified {
+        from: EntryPoint,
+        to: EntryPoint,
+        #[serde(default)]
+        changes: Vec<ContractEntryPointChange>,
+    },
+}
+
+/// Single change within a modified entry point.
+///
+/// Entry points of `contracts::Contract` carry no payment or entry point kind, so changes to them
+/// are covered by `EntryPointTypeChanged` only.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractEntryPointChange {
+    ParameterAdded {
+        name: String,
+        cl_type: CLType,
+    },
+    ParameterRemoved {
+        name: String,
+        cl_type: CLType,
+    },
+    /// Parameter kept its position and type under a new name
+    ParameterRenamed {
+        from: String,
+        to: String,
+        cl_type: CLType,
+    },
+    ParameterTypeChanged {
+        name: String,
+        from: CLType,
+        to: CLType,
+    },
+    ReturnTypeChanged {
+        from: CLType,
+        to: CLType,
+    },
+    AccessChanged {
+        from: EntryPointAccess,
+        to: EntryPointAccess,
+    },
+    EntryPointTypeChanged {
+        from: EntryPointType,
+        to: EntryPointType,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -128,3 +245,151 @@ pub enum ContractNamedKeysDiff {
     Removed { key: String, value: Key },
     Modified { key: String, from: Key, to: Key },
 }
+
+/// Value stored behind a key in global state, rendered as JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedKeyValue {
+    pub key: String,
+    pub stored_value_type: String,
+    pub cl_type: Option<String>,
+    pub value: serde_json::Value,
+    /// Set when the rendered value exceeded the configured size cap and was cut
+    pub truncated: bool,
+    /// Value behind this value, when it is itself a `Key` and resolution depth allows
+    pub resolved: Option<Box<ResolvedKeyValue>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContractNamedKeyValueDiff {
+    Added {
+        key: String,
+        value: ResolvedKeyValue,
+    },
+    Removed {
+        key: String,
+        value: ResolvedKeyValue,
+    },
+    Modified {
+        key: String,
+        from: ResolvedKeyValue,
+        to: ResolvedKeyValue,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContractPackageSnapshotVersion {
+    pub protocol_major_version: u32,
+    pub contract_version: u32,
+    pub contract_hash: String,
+    pub disabled: bool,
+}
+
+/// State of a contract package as seen in global state at a given block
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContractPackageSnapshot {
+    pub block_height: u64,
+    pub block_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub exists: bool,
+    pub lock_status: bool,
+    pub versions: Vec<ContractPackageSnapshotVersion>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ContractPackageHistoryEventKind {
+    Created,
+    VersionAdded {
+        protocol_major_version: u32,
+        contract_version: u32,
+        contract_hash: String,
+    },
+    VersionDisabled {
+        protocol_major_version: u32,
+        contract_version: u32,
+    },
+    VersionEnabled {
+        protocol_major_version: u32,
+        contract_version: u32,
+    },
+    Locked,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContractPackageHistoryEvent {
+    pub block_height: u64,
+    pub block_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub event: ContractPackageHistoryEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContractPackageHistory {
+    pub contract_package_hash: String,
+    pub snapshots: Vec<ContractPackageSnapshot>,
+    pub events: Vec<ContractPackageHistoryEvent>,
+    /// Height to request the rest of the history `from` when the query budget ran out
+    pub continue_from_height: Option<u64>,
+}
+
+/// Impact of a change on existing callers, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ContractChangeCompatibility {
+    Compatible,
+    PotentiallyBreaking,
+    Breaking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SemverBump {
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractChangeClassification {
+    /// Entry point, named key, event or error the change applies to
+    pub subject: String,
+    pub change: String,
+    pub compatibility: ContractChangeCompatibility,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractUpgradeAssessment {
+    /// Most severe compatibility across all changes
+    pub severity: ContractChangeCompatibility,
+    pub semver_bump: SemverBump,
+    pub changes: Vec<ContractChangeClassification>,
+}
+
+/// Changelog of a range of contract versions, disabled versions included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractChangelog {
+    pub contract_package_hash: String,
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Versions in the range the changelog steps through, in ascending order
+    pub versions: Vec<u32>,
+    /// Diff of every pair of consecutive versions
+    pub steps: Vec<ContractVersionDiff>,
+    /// Diff from the first to the last version of the range
+    pub net: ContractVersionDiff,
+    /// Entry points added within the range and removed again before its end
+    pub transient_entry_points: Vec<String>,
+    /// Named keys added within the range and removed again before its end
+    pub transient_named_keys: Vec<String>,
+}
+
+/// Comparison of two contract versions that may belong to different packages and networks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractCrossPackageDiff {
+    pub from_package_hash: String,
+    pub from_network: String,
+    pub to_package_hash: String,
+    pub to_network: String,
+    /// Both versions were installed from byte-identical Wasm, unset when either module could not be
+    /// fetched
+    pub wasm_hash_equal: Option<bool>,
+    pub diff: ContractVersionDiff,
+}
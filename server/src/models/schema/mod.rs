@@ -1 +1,9 @@
+pub(crate) mod alert;
+pub(crate) mod anomaly;
 pub(crate) mod contract;
+pub(crate) mod odra;
+pub(crate) mod promotion;
+pub(crate) mod rules;
+pub(crate) mod transaction;
+pub(crate) mod verification;
+pub(crate) mod wasm;
@@ -0,0 +1,133 @@
+use casper_types::CLType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OdraStorageKind {
+    Var,
+    Mapping,
+}
+
+/// Storage field of an Odra module, as laid out in the `state` dictionary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OdraStorageField {
+    pub name: String,
+    /// Position of the field in the module struct, starting from 1
+    pub index: u32,
+    pub kind: OdraStorageKind,
+    pub value_type: CLType,
+    /// Type of the mapping key, required for `Mapping` fields
+    pub key_type: Option<CLType>,
+    /// Mapping key to read, in the JSON form of `key_type`
+    pub key: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OdraStorageValue {
+    pub name: String,
+    pub kind: OdraStorageKind,
+    pub key: Option<serde_json::Value>,
+    pub dictionary_item_key: Option<String>,
+    pub cl_type: String,
+    pub value: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// Contract schema generated by Odra (`casper_contract_schema`), only the parts CasperLens reads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OdraContractSchema {
+    pub casper_contract_schema_version: u8,
+    pub contract_name: String,
+    pub contract_version: String,
+    #[serde(default)]
+    pub types: serde_json::Value,
+    #[serde(default)]
+    pub entry_points: Vec<OdraSchemaEntryPoint>,
+    #[serde(default)]
+    pub events: Vec<OdraSchemaEvent>,
+    #[serde(default)]
+    pub errors: Vec<OdraSchemaError>,
+    /// Fields of the module struct in declaration order, the layout of the `state` dictionary
+    #[serde(default)]
+    pub storage: Vec<OdraSchemaStorageField>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OdraSchemaEntryPoint {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_mutable: bool,
+    #[serde(default)]
+    pub arguments: Vec<OdraSchemaArgument>,
+    /// Named CL type, e.g. `"U256"` or `{"Option": "Key"}`
+    pub return_ty: serde_json::Value,
+    #[serde(default)]
+    pub is_contract_context: bool,
+    /// `"public"` or `{"groups": [...]}`
+    pub access: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OdraSchemaArgument {
+    pub name: String,
+    pub description: Option<String>,
+    pub ty: serde_json::Value,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OdraSchemaEvent {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OdraSchemaError {
+    pub name: String,
+    pub description: Option<String>,
+    pub discriminant: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OdraSchemaStorageField {
+    pub name: String,
+    pub kind: OdraStorageKind,
+    /// Named CL type of the value
+    pub ty: serde_json::Value,
+    /// Named CL type of the key, for `Mapping` fields
+    pub key_ty: Option<serde_json::Value>,
+}
+
+/// Where the schema of a contract version came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OdraSchemaSource {
+    Upload,
+    /// Embedded in a custom section of the contract wasm
+    Wasm,
+}
+
+impl OdraSchemaSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OdraSchemaSource::Upload => "Upload",
+            OdraSchemaSource::Wasm => "Wasm",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Upload" => Some(OdraSchemaSource::Upload),
+            "Wasm" => Some(OdraSchemaSource::Wasm),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractVersionOdraSchema {
+    pub contract_package_hash: String,
+    pub contract_version: u32,
+    pub source: OdraSchemaSource,
+    pub schema: OdraContractSchema,
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractPackageLinkSchema {
+    pub user_id: Uuid,
+    pub testnet_package_hash: String,
+    pub mainnet_package_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a mainnet version was matched to the testnet version it was promoted from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromotionMatch {
+    /// Installed from byte-identical Wasm modules
+    WasmHash,
+    /// Same entry points, but built from different Wasm
+    EntryPoints,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionPromotion {
+    pub testnet_version: u32,
+    pub mainnet_version: u32,
+    pub matched_by: PromotionMatch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionStatus {
+    pub testnet_package_hash: String,
+    pub mainnet_package_hash: String,
+    pub promotions: Vec<VersionPromotion>,
+    /// Testnet versions that have not reached mainnet
+    pub unpromoted_testnet_versions: Vec<u32>,
+    /// Mainnet versions without a tested testnet counterpart
+    pub untested_mainnet_versions: Vec<u32>,
+    /// Every mainnet version matches a testnet version by Wasm hash
+    pub in_sync: bool,
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffRuleTarget {
+    NamedKey,
+    EntryPoint,
+}
+
+impl DiffRuleTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffRuleTarget::NamedKey => "NamedKey",
+            DiffRuleTarget::EntryPoint => "EntryPoint",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "NamedKey" => Some(DiffRuleTarget::NamedKey),
+            "EntryPoint" => Some(DiffRuleTarget::EntryPoint),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffRuleAction {
+    /// Drop matching entries from the diff
+    Ignore,
+    /// Treat a removed entry matching `pattern` and an added entry named `replacement` as the same entry
+    Rename,
+    /// Replace matching entries with a single summary per rule
+    Collapse,
+}
+
+impl DiffRuleAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffRuleAction::Ignore => "Ignore",
+            DiffRuleAction::Rename => "Rename",
+            DiffRuleAction::Collapse => "Collapse",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Ignore" => Some(DiffRuleAction::Ignore),
+            "Rename" => Some(DiffRuleAction::Rename),
+            "Collapse" => Some(DiffRuleAction::Collapse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffRuleSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Package the rule is scoped to, `None` for rules applied to every package of the user
+    pub package_hash: Option<String>,
+    pub target: DiffRuleTarget,
+    pub action: DiffRuleAction,
+    /// Glob pattern over entry point or named key names, `*` and `?` are supported
+    pub pattern: String,
+    /// New name for `Rename` rules, a `*` in it is substituted with the text matched by the pattern
+    pub replacement: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedDiffRule {
+    pub rule_id: Uuid,
+    pub target: DiffRuleTarget,
+    pub action: DiffRuleAction,
+    pub pattern: String,
+    /// Number of diff entries affected by the rule
+    pub matched: u32,
+}
+
+/// Summary of diff entries folded together by a `Collapse` rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollapsedDiff {
+    pub rule_id: Uuid,
+    pub target: DiffRuleTarget,
+    pub pattern: String,
+    pub added: u32,
+    pub removed: u32,
+    pub modified: u32,
+}
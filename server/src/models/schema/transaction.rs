@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Hash the transaction indexer pages through, calls by contract hash are not always attributed to the package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionTarget {
+    ContractPackage(String),
+    Contract(String),
+}
+
+impl TransactionTarget {
+    pub fn hash(&self) -> &str {
+        match self {
+            TransactionTarget::ContractPackage(hash) | TransactionTarget::Contract(hash) => hash,
+        }
+    }
+}
+
+/// Tracked package and the contract hashes of its versions, as indexed per network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedContractPackage {
+    pub network: String,
+    pub contract_package_hash: String,
+    pub contract_hashes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionCursorSchema {
+    pub network: String,
+    pub target_hash: String,
+    pub contract_package_hash: String,
+    /// Next page of the backfill, newest transactions come first
+    pub next_page: u32,
+    pub backfill_complete: bool,
+    pub newest_timestamp: Option<DateTime<Utc>>,
+    /// Newest timestamp known before polling fell behind, it resumes from `next_page` until reaching it
+    pub poll_until: Option<DateTime<Utc>>,
+    /// When the sweep in progress fetched its first page
+    pub sweep_started_at: Option<DateTime<Utc>>,
+    /// Start of the last completed sweep, every transaction up to it is indexed
+    pub indexed_until: Option<DateTime<Utc>>,
+}
+
+/// Transaction that wrote a contract package, i.e. installed it or added a version to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractPackageWrite {
+    pub network: String,
+    pub contract_package_hash: String,
+    pub deploy_hash: String,
+    pub block_height: u64,
+    pub timestamp: DateTime<Utc>,
+    pub caller_public_key: String,
+    pub cost: String,
+    /// Contracts written by the same transaction, the versions it added
+    pub contract_hashes: Vec<String>,
+}
+
+/// Blocks the block indexer still has to rescan for a package registered after it passed them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageBackfillCursor {
+    pub contract_package_hash: String,
+    pub next_height: u64,
+    /// Last block to rescan, the indexer read every later block with the package tracked
+    pub end_height: u64,
+}
+
+/// Transaction that reached a tracked package through another contract or session code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndirectCall {
+    pub network: String,
+    pub deploy_hash: String,
+    pub contract_package_hash: String,
+    /// Contract the transaction called, unset for session code
+    pub caller_contract_hash: Option<String>,
+    pub caller_public_key: String,
+    pub timestamp: DateTime<Utc>,
+    pub error_message: Option<String>,
+}
+
+/// Category of an execution failure, parsed from the error message of a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TransactionFailureKind {
+    OutOfGas,
+    /// Revert with a user error code, Odra contract errors revert with their discriminant
+    UserError,
+    MissingKey,
+    InvalidArgs,
+    Other,
+}
+
+/// Structured execution failure of a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionFailure {
+    pub kind: TransactionFailureKind,
+    pub code: Option<u32>,
+    /// Name of the user error, from the package error codes or the Odra schema of the called version
+    pub error_name: Option<String>,
+    pub message: String,
+}
+
+/// Manually named user error code of a package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractErrorCodeSchema {
+    pub code: u32,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Number of indexed transactions with the same called version, entry point and outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionOutcomeCount {
+    pub contract_version: Option<u32>,
+    pub entry_point_name: Option<String>,
+    /// `None` for successful transactions
+    pub error_message: Option<String>,
+    pub count: u64,
+}
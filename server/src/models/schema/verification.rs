@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractVerificationStatus {
+    Pending,
+    Building,
+    /// The built Wasm has the same bytes as the deployed module
+    Verified,
+    /// The build succeeded but produced different Wasm
+    Mismatch,
+    Failed,
+}
+
+impl ContractVerificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContractVerificationStatus::Pending => "Pending",
+            ContractVerificationStatus::Building => "Building",
+            ContractVerificationStatus::Verified => "Verified",
+            ContractVerificationStatus::Mismatch => "Mismatch",
+            ContractVerificationStatus::Failed => "Failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Pending" => Some(ContractVerificationStatus::Pending),
+            "Building" => Some(ContractVerificationStatus::Building),
+            "Verified" => Some(ContractVerificationStatus::Verified),
+            "Mismatch" => Some(ContractVerificationStatus::Mismatch),
+            "Failed" => Some(ContractVerificationStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractVerificationSchema {
+    pub id: Uuid,
+    pub contract_package_hash: String,
+    pub contract_version: u32,
+    pub user_id: Uuid,
+    /// Source commit the submitted archive was taken from
+    pub commit_id: String,
+    /// Odra module to build, as passed to `cargo odra build -c`
+    pub contract_name: String,
+    pub toolchain: String,
+    pub cargo_odra_version: String,
+    pub features: Vec<String>,
+    pub status: ContractVerificationStatus,
+    /// Wasm hash of the version, the address its deployed module is fetched from
+    pub expected_wasm_hash: String,
+    /// Hash of the built module bytes
+    pub built_wasm_hash: Option<String>,
+    /// Tail of the build output
+    pub build_log: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmImport {
+    pub module: String,
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmExport {
+    pub name: String,
+    pub kind: String,
+    pub index: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmFunctionSummary {
+    /// Index in the function index space, imported functions included
+    pub index: u32,
+    pub name: Option<String>,
+    pub body_size: u32,
+    pub instruction_count: u32,
+    pub export_names: Vec<String>,
+    /// Hash of the instruction sequence with function, global and constant immediates erased
+    pub normalized_hash: String,
+    /// Hash of the constant immediates in instruction order
+    #[serde(default)]
+    pub constants_hash: String,
+    #[serde(skip)]
+    pub opcode_histogram: BTreeMap<String, u32>,
+    /// Indices of directly called functions
+    #[serde(skip)]
+    pub calls: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmDataSegment {
+    pub index: u32,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmCustomSection {
+    pub name: String,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WasmModuleSummary {
+    pub size: u32,
+    /// Hash of the module bytes. Contract wasm hashes are addresses assigned at install, so only
+    /// this tells whether two modules are the same
+    pub module_hash: String,
+    /// Hash of all function bodies as stored, function indices and constants included
+    pub code_hash: String,
+    pub imports: Vec<WasmImport>,
+    pub exports: Vec<WasmExport>,
+    pub functions: Vec<WasmFunctionSummary>,
+    pub data_segments: Vec<WasmDataSegment>,
+    pub custom_sections: Vec<WasmCustomSection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WasmItemDiff<T> {
+    Added(T),
+    Removed(T),
+    Modified { from: T, to: T },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmCountDiff {
+    pub from: u64,
+    pub to: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractWasmDiff {
+    pub from_wasm_hash: String,
+    pub to_wasm_hash: String,
+    /// Whether both modules have the same bytes
+    pub is_identical: bool,
+    /// Whether a function was added, removed or changed its instructions
+    pub is_logic_change: bool,
+    pub module_size: WasmCountDiff,
+    pub function_count: WasmCountDiff,
+    pub code_size: WasmCountDiff,
+    pub imports: Vec<WasmItemDiff<WasmImport>>,
+    pub exports: Vec<WasmItemDiff<WasmExport>>,
+    pub data_segments: Vec<WasmItemDiff<WasmDataSegment>>,
+    pub custom_sections: Vec<WasmItemDiff<WasmCustomSection>>,
+    #[serde(default)]
+    pub function_summary: WasmFunctionDiffSummary,
+    /// Functions that are not unchanged, unchanged ones are only counted in `function_summary`
+    #[serde(default)]
+    pub functions: Vec<WasmFunctionDiff>,
+    #[serde(default)]
+    pub entry_points: Vec<WasmEntryPointLogicDiff>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WasmFunctionStatus {
+    Unchanged,
+    /// Same instructions, only constant immediates differ
+    ConstantsModified,
+    Modified,
+    Added,
+    Removed,
+}
+
+/// How a function in the older module was paired with one in the newer module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WasmFunctionMatch {
+    ExportName,
+    DebugName,
+    InstructionHash,
+    Similarity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmFunctionDiff {
+    pub name: String,
+    pub status: WasmFunctionStatus,
+    pub matched_by: Option<WasmFunctionMatch>,
+    pub from_index: Option<u32>,
+    pub to_index: Option<u32>,
+    pub from_instruction_count: Option<u32>,
+    pub to_instruction_count: Option<u32>,
+    pub instruction_delta: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmFunctionDiffSummary {
+    pub unchanged: u32,
+    #[serde(default)]
+    pub constants_modified: u32,
+    pub modified: u32,
+    pub added: u32,
+    pub removed: u32,
+}
+
+/// Logic change behind an exported entry point, following direct calls into internal functions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmEntryPointLogicDiff {
+    pub entry_point: String,
+    pub status: WasmFunctionStatus,
+    pub changed_functions: Vec<String>,
+}
+
+/// Page of a unified diff between the WAT disassembly of two contract versions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractWatDiff {
+    pub from_wasm_hash: String,
+    pub to_wasm_hash: String,
+    pub function: Option<String>,
+    pub page: u32,
+    pub page_size: u32,
+    pub total_lines: u32,
+    pub total_pages: u32,
+    pub lines: Vec<String>,
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    config::AppState,
+    models::api::{ApiResponse, alert::AlertsQuery},
+    services::database::alert::{acknowledge_alert, get_alerts},
+    utils::strip_hash_prefix,
+};
+
+#[axum::debug_handler]
+pub async fn list_alerts(
+    state: State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<AlertsQuery>,
+) -> impl IntoResponse {
+    let package_hash = query.package_hash.as_deref().map(strip_hash_prefix);
+
+    match get_alerts(
+        &state.db,
+        &user_id,
+        package_hash.as_deref(),
+        query.unacknowledged.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(alerts) => Json(ApiResponse {
+            success: true,
+            message: "Alerts fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(alerts),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn acknowledge_user_alert(
+    state: State<Arc<AppState>>,
+    Path((user_id, alert_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    match acknowledge_alert(&state.db, &user_id, &alert_id).await {
+        Ok(true) => Json(ApiResponse {
+            success: true,
+            message: "Alert acknowledged successfully".to_string(),
+            error: None::<String>,
+            data: None::<String>,
+        })
+        .into_response(),
+        Ok(false) => Json(ApiResponse {
+            success: false,
+            message: "Alert not found".to_string(),
+            error: Some("Alert not found".to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
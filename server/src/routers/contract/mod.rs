@@ -1,46 +1,101 @@
 use std::sync::Arc;
 
 use crate::{
-    config::AppState,
+    config::{AppState, Config, IndexerMode, network_env_prefix},
     models::{
         api::{
             ApiResponse,
             contract::{
-                ContractData, ContractDiffQuery, ContractOverview, ContractVersionData,
-                RegisterContractRequest,
+                ContractChangelogQuery, ContractCompareQuery, ContractData, ContractDiffQuery,
+                ContractHistoryQuery, ContractOverview, ContractVersionData, ContractWatDiffQuery,
+                LinkContractPackageRequest, LocalDiffRequest, OdraStorageQuery,
+                RegisterContractRequest, VerifyBuildRequest,
             },
+            transaction::{TransactionsQuery, TransactionsResponse},
+        },
+        schema::{
+            contract::{ContractPackageSchema, ContractVersionDiff},
+            odra::{ContractVersionOdraSchema, OdraContractSchema, OdraSchemaSource},
+            promotion::ContractPackageLinkSchema,
+            verification::{ContractVerificationSchema, ContractVerificationStatus},
         },
-        schema::contract::{ContractPackageSchema, ContractVersionDiff},
     },
     services::{
         contract::{
-            diff::{fetch_contract_diff_from_chain, get_contract_version_diff},
-            metadata::get_contract_package_metadata,
+            call::{EntryPointNames, decode_call, find_called_version},
+            changelog::get_contract_changelog as get_changelog,
+            compat::assess_upgrade,
+            diff::{
+                attach_wasm_diff, fetch_contract_diff_from_chain, get_contract_version_diff,
+                get_cross_package_diff,
+            },
+            disassembly::get_contract_wat_diff,
+            failure::{ErrorCodeNames, describe_failure},
+            gas::attach_gas_regressions,
+            history::get_contract_package_history,
+            metadata::{get_contract_package_metadata, get_indexed_package_metadata},
+            named_keys::{KeyResolutionLimits, attach_named_key_values_diff},
             package::{get_contract_package_details, get_contract_versions_details},
-            transaction::get_contract_transactions as fetch_transactions,
+            predeploy::get_local_version_diff,
+            promotion::evaluate_promotion,
+            rules::apply_diff_rules,
+            schema::{attach_schema_diff, get_version_schema},
+            storage::{get_odra_storage_values, get_schema_storage_fields, resolve_storage_fields},
+            verification::{
+                MAX_ENCODED_SOURCE_ARCHIVE_SIZE, MAX_SOURCE_ARCHIVE_SIZE, is_safe_build_argument,
+                queue_build, run_contract_verification,
+            },
         },
         database::contract::{
             get_all_contracts, get_contract_package, get_contract_version, get_contract_versions,
             insert_contract_package, insert_contract_package_versions,
         },
-        tasks::contract::{write_contract_diff_to_chain, write_contract_diff_versions_to_chain},
+        database::promotion::{
+            delete_contract_package_link, get_contract_package_link, insert_contract_package_link,
+        },
+        database::rules::get_diff_rules,
+        database::schema::upsert_contract_schema,
+        database::transaction::get_transactions,
+        database::verification::{get_contract_verifications, insert_contract_verification},
+        tasks::{
+            block::schedule_package_backfill,
+            contract::{write_contract_diff_to_chain, write_contract_diff_versions_to_chain},
+        },
     },
+    utils::strip_hash_prefix,
 };
 use axum::{
     extract::{Json, Path, Query, State},
     response::IntoResponse,
 };
+use base64::Engine;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-fn resolve_network(network: &str) -> Option<String> {
-    const MAINNET: &str = "mainnet";
-    const TESTNET: &str = "testnet";
-    match network {
-        MAINNET => Some("mainnet".to_string()),
-        TESTNET => Some("testnet".to_string()),
-        _ => None,
-    }
+const DEFAULT_TRANSACTIONS_LIMIT: u64 = 25;
+const MAX_TRANSACTIONS_LIMIT: u64 = 100;
+
+/// Resolve a network to itself and the address of its node, `None` when it has no node configured
+fn resolve_network(config: &Config, network: &str) -> Option<(String, String)> {
+    config
+        .node_address(network)
+        .map(|node_address| (network.to_string(), node_address.to_string()))
+}
+
+fn configured_networks(config: &Config) -> String {
+    let mut networks: Vec<&str> = config.node_addresses.keys().map(String::as_str).collect();
+    networks.sort_unstable();
+    networks.join(", ")
+}
+
+fn unconfigured_network_response(network: &str) -> axum::response::Response {
+    Json(ApiResponse {
+        success: false,
+        message: "Unsupported network".to_string(),
+        error: Some(format!("No node is configured for network {}", network)),
+        data: None::<String>,
+    })
+    .into_response()
 }
 
 fn normalize_hash(input: &str) -> String {
@@ -51,41 +106,109 @@ fn normalize_hash(input: &str) -> String {
     }
 }
 
-fn strip_hash_prefix(input: &str) -> String {
-    if let Some(stripped) = input.strip_prefix("hash-") {
-        stripped.to_string()
-    } else {
-        input.to_string()
-    }
-}
-
 #[axum::debug_handler]
 pub async fn get_contract_transactions(
     state: State<Arc<AppState>>,
     Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<TransactionsQuery>,
 ) -> impl IntoResponse {
     let package_hash = strip_hash_prefix(&package_hash);
 
-    // We need to know the network to fetch from the correct API.
-    // We can look up the contract in the DB first to get its network.
     let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
         Ok(Some(pkg)) => pkg.network,
-        Ok(None) => "testnet".to_string(), // Default fallback if not in DB yet (or error out)
-        Err(_) => "testnet".to_string(),
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Contract package not found".to_string(),
+                error: Some("Contract package not found".to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
     };
 
-    match fetch_transactions(&network, &package_hash).await {
-        Ok(transactions) => Json(ApiResponse {
-            success: true,
-            message: "Transactions fetched successfully".to_string(),
-            error: None::<String>,
-            data: Some(transactions),
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TRANSACTIONS_LIMIT)
+        .clamp(1, MAX_TRANSACTIONS_LIMIT);
+    // The offset is passed on as a signed database integer
+    let Some(offset) = (page - 1)
+        .checked_mul(limit)
+        .filter(|offset| i64::try_from(*offset).is_ok())
+    else {
+        return Json(ApiResponse {
+            success: false,
+            message: "Invalid page".to_string(),
+            error: Some(format!("Page {} is out of range", page)),
+            data: None::<String>,
         })
-        .into_response(),
+        .into_response();
+    };
+    match get_transactions(&state.db, &network, &package_hash, &query, offset, limit).await {
+        Ok((mut transactions, item_count)) => {
+            let versions = get_contract_versions(&state.db, &package_hash, &user_id)
+                .await
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to fetch versions of {}: {}", package_hash, e);
+                    vec![]
+                });
+            let error_code_names = ErrorCodeNames::load(
+                &state.db,
+                &package_hash,
+                &user_id,
+                &versions
+                    .iter()
+                    .map(|v| v.contract_version)
+                    .collect::<Vec<_>>(),
+            )
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load error codes of {}: {}", package_hash, e);
+                ErrorCodeNames::default()
+            });
+            let mut entry_point_names = EntryPointNames::default();
+            for transaction in &mut transactions {
+                transaction.entry_point_name =
+                    entry_point_names.resolve(&network, transaction).await;
+                transaction.call = transaction
+                    .entry_point_name
+                    .as_deref()
+                    .and_then(|name| decode_call(&versions, transaction, name));
+                let version =
+                    find_called_version(&versions, transaction).map(|v| v.contract_version);
+                transaction.failure = describe_failure(
+                    transaction.error_message.as_deref(),
+                    version,
+                    &error_code_names,
+                );
+            }
+
+            Json(ApiResponse {
+                success: true,
+                message: "Transactions fetched successfully".to_string(),
+                error: None::<String>,
+                data: Some(TransactionsResponse {
+                    item_count,
+                    page_count: item_count.div_ceil(limit),
+                    data: transactions,
+                }),
+            })
+            .into_response()
+        }
         Err(e) => Json(ApiResponse {
             success: false,
             message: "Failed to fetch transactions".to_string(),
-            error: Some(e),
+            error: Some(e.to_string()),
             data: None::<String>,
         })
         .into_response(),
@@ -102,6 +225,13 @@ pub async fn get_contract_details(
     match get_contract_package(&state.db, &user_id, &package_hash).await {
         Ok(Some(pkg)) => match get_contract_versions(&state.db, &package_hash, &user_id).await {
             Ok(versions) => {
+                let verifications =
+                    get_contract_verifications(&state.db, &package_hash, &user_id, None)
+                        .await
+                        .unwrap_or_else(|e| {
+                            log::warn!("Failed to fetch verifications of {}: {}", package_hash, e);
+                            vec![]
+                        });
                 let versions_data: Vec<ContractVersionData> = versions
                     .into_iter()
                     .map(|v| {
@@ -125,6 +255,11 @@ pub async fn get_contract_details(
                             entry_points,
                             disabled: v.disabled,
                             age: format!("{}d", (Utc::now() - v.age).num_days()),
+                            // Verifications come newest first
+                            verification: verifications
+                                .iter()
+                                .find(|ver| ver.contract_version == v.contract_version)
+                                .cloned(),
                         }
                     })
                     .collect();
@@ -261,20 +396,41 @@ pub async fn get_contract_diff(
         }
     };
 
+    let key_resolution_limits = KeyResolutionLimits {
+        depth: state.config.named_key_resolution_depth,
+        size_cap: state.config.named_key_value_size_cap,
+    };
+
+    let diff_rules = match get_diff_rules(&state.db, &user_id, Some(&package_hash)).await {
+        Ok(rules) => rules,
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
     // Try to fetch from chain first
     let contract_package = get_contract_package(&state.db, &user_id, &package_hash).await;
-    let mut resolved_node_address = state.config.mainnet_node_address.clone();
+    let mut resolved_node_address = state
+        .config
+        .node_address("mainnet")
+        .unwrap_or_default()
+        .to_string();
     let mut resolved_network = "mainnet".to_string();
 
     if let Ok(Some(pkg)) = contract_package {
-        resolved_network = pkg.network.clone();
-        resolved_node_address = if pkg.network == "testnet" {
-            state.config.testnet_node_address.clone()
-        } else {
-            state.config.mainnet_node_address.clone()
+        let Some(node_address) = state.config.node_address(&pkg.network) else {
+            return unconfigured_network_response(&pkg.network);
         };
+        resolved_node_address = node_address.to_string();
+        resolved_network = pkg.network.clone();
 
-        if let Ok(Some(diff)) = fetch_contract_diff_from_chain(
+        if let Ok(Some(mut diff)) = fetch_contract_diff_from_chain(
             &v1_db,
             &v2_db,
             &package_hash,
@@ -283,6 +439,30 @@ pub async fn get_contract_diff(
         )
         .await
         {
+            // Diffs stored before upgrade assessment existed carry none
+            if diff.assessment.is_none() {
+                diff.assessment = Some(assess_upgrade(&diff));
+            }
+            attach_wasm_diff(&state.db, &resolved_node_address, &mut diff).await;
+            attach_schema_diff(
+                &state.db,
+                &resolved_node_address,
+                &user_id,
+                &v1_db,
+                &v2_db,
+                &mut diff,
+            )
+            .await;
+            attach_named_key_values_diff(
+                &resolved_node_address,
+                &v1_db,
+                &v2_db,
+                key_resolution_limits,
+                &mut diff,
+            )
+            .await;
+            attach_gas_regressions(&state.db, &resolved_network, &user_id, &mut diff).await;
+            apply_diff_rules(&mut diff, &diff_rules);
             return Json(ApiResponse {
                 success: true,
                 message: "Diff fetched from chain successfully".to_string(),
@@ -293,8 +473,23 @@ pub async fn get_contract_diff(
         }
     }
 
-    match get_contract_version_diff(v1_db.clone(), v2_db.clone()).await {
-        Ok(diff) => {
+    // The diff is stored for every user of the package, so it is computed without rules here and
+    // they are applied once the parts left out of the stored diff are attached
+    match get_contract_version_diff(v1_db.clone(), v2_db.clone(), &[]).await {
+        Ok(mut diff) => {
+            attach_schema_diff(
+                &state.db,
+                &resolved_node_address,
+                &user_id,
+                &v1_db,
+                &v2_db,
+                &mut diff,
+            )
+            .await;
+
+            let network = resolved_network.clone();
+            let node_address = resolved_node_address.clone();
+
             // Spawn background task to store the calculated diff
             let observability_package_hash = state.config.observability_package_hash.clone();
             let package_hash_clone = package_hash.clone();
@@ -330,6 +525,19 @@ pub async fn get_contract_diff(
                 }
             });
 
+            // The module diff would push the stored diff past its size cap, while named key values
+            // and costs go stale as state changes and calls get indexed, so all are left out of it
+            attach_wasm_diff(&state.db, &node_address, &mut diff).await;
+            attach_named_key_values_diff(
+                &node_address,
+                &v1_db,
+                &v2_db,
+                key_resolution_limits,
+                &mut diff,
+            )
+            .await;
+            attach_gas_regressions(&state.db, &network, &user_id, &mut diff).await;
+            apply_diff_rules(&mut diff, &diff_rules);
             Json(ApiResponse {
                 success: true,
                 message: "Diff calculated successfully".to_string(),
@@ -349,149 +557,1213 @@ pub async fn get_contract_diff(
 }
 
 #[axum::debug_handler]
-pub async fn get_diff_analysis(
+pub async fn get_contract_changelog(
     state: State<Arc<AppState>>,
-    Path(_user_id): Path<Uuid>,
-    Json(payload): Json<ContractVersionDiff>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<ContractChangelogQuery>,
 ) -> impl IntoResponse {
-    let hf_token = state.config.huggingface_token.clone();
-    let content = format!(
-        "There are 2 smart contract deployment for same package on Casper Network. This is the diff between them:\n{:#?}\nwhere v1 contains metadata on previous version and v2 on new version, entry points and named keys show the changes from v1 to v2. Explain what it means for smart contract and how it affects development and security",
-        payload
-    );
-    let body = serde_json::json!({
-        "messages": [
-            {
-                "role": "user",
-                "content": content
-            }
-        ],
-        "model": "meta-llama/Llama-3.1-8B-Instruct:cerebras",
-        "stream": false
-    });
-    let client = reqwest::Client::new();
+    let package_hash = strip_hash_prefix(&package_hash);
 
-    let response = client
-        .post("https://router.huggingface.co/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", hf_token))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await;
-    if let Err(err) = &response {
-        return Json(ApiResponse {
-            success: false,
-            message: "Failed to get completions for diff".to_string(),
-            error: Some(format!("Failed to get completions for diff: {}", err)),
-            data: None::<String>,
-        })
-        .into_response();
-    }
-    let response = response.unwrap();
-    if response.status().is_success() {
-        let response_body = response.json::<serde_json::Value>().await;
-        if let Err(e) = response_body {
+    let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
+        Ok(Some(pkg)) => pkg.network,
+        Ok(None) => {
             return Json(ApiResponse {
                 success: false,
-                message: "Failed to parse response to JSON".to_string(),
-                error: Some(format!("Failed to parse response to JSON: {}", e)),
+                message: "Contract package not found".to_string(),
+                error: Some("Contract package not found".to_string()),
                 data: None::<String>,
             })
             .into_response();
         }
-        let response_body = response_body.unwrap();
-        if let Some(choice) = response_body.get("choices") {
-            if let Some(idx) = choice.get(0) {
-                if let Some(message) = idx.get("message") {
-                    if let Some(content) = message.get("content") {
-                        return Json(ApiResponse {
-                            success: true,
-                            message: "Successfully retrieved response".to_string(),
-                            error: None::<String>,
-                            data: Some(content.as_str().to_owned()),
-                        })
-                        .into_response();
-                    }
-                }
-            }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
         }
-        return Json(ApiResponse {
+    };
+    let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+        return unconfigured_network_response(&network);
+    };
+
+    let (versions, diff_rules) = match (
+        get_contract_versions(&state.db, &package_hash, &user_id).await,
+        get_diff_rules(&state.db, &user_id, Some(&package_hash)).await,
+    ) {
+        (Ok(versions), Ok(rules)) => (versions, rules),
+        (Err(e), _) | (_, Err(e)) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
+    match get_changelog(&versions, query.from, query.to, &diff_rules).await {
+        Ok(mut changelog) => {
+            attach_wasm_diff(&state.db, &node_address, &mut changelog.net).await;
+            // Cover the entry points of the module diff as well
+            apply_diff_rules(&mut changelog.net, &diff_rules);
+            Json(ApiResponse {
+                success: true,
+                message: "Changelog calculated successfully".to_string(),
+                error: None::<String>,
+                data: Some(changelog),
+            })
+            .into_response()
+        }
+        Err(e) => Json(ApiResponse {
             success: false,
-            message: "Failed to get data from response by parsing".to_string(),
-            error: Some("Failed to get data from response by parsing".to_string()),
+            message: "Failed to calculate changelog".to_string(),
+            error: Some(e),
             data: None::<String>,
         })
-        .into_response();
-    } else {
-        Json(ApiResponse {
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn compare_contract_versions(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<ContractCompareQuery>,
+) -> impl IntoResponse {
+    let mut sides = vec![];
+    for (package_hash, version) in [
+        (strip_hash_prefix(&package_hash), query.from_version),
+        (strip_hash_prefix(&query.to_package), query.to_version),
+    ] {
+        let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
+            Ok(Some(pkg)) => pkg.network,
+            Ok(None) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Contract package not found".to_string(),
+                    error: Some(format!("Contract package {} not found", package_hash)),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+            Err(e) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Database error".to_string(),
+                    error: Some(e.to_string()),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+        };
+        let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+            return unconfigured_network_response(&network);
+        };
+
+        match get_contract_version(&state.db, &package_hash, version, &user_id).await {
+            Ok(Some(v)) => sides.push((v, network, node_address)),
+            Ok(None) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: format!("Version {} not found", version),
+                    error: Some(format!(
+                        "Contract version {} of {} not found",
+                        version, package_hash
+                    )),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+            Err(e) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Database error".to_string(),
+                    error: Some(e.to_string()),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+        }
+    }
+
+    let (v1, from_network, from_node_address) = &sides[0];
+    let (v2, to_network, to_node_address) = &sides[1];
+    match get_cross_package_diff(
+        &state.db,
+        (v1, from_network.as_str(), from_node_address.as_str()),
+        (v2, to_network.as_str(), to_node_address.as_str()),
+    )
+    .await
+    {
+        Ok(diff) => Json(ApiResponse {
+            success: true,
+            message: "Contract versions compared successfully".to_string(),
+            error: None::<String>,
+            data: Some(diff),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
             success: false,
-            message: "Request failed".to_string(),
-            error: Some(format!(
-                "Request failed with status code: {}",
-                response.status()
-            )),
+            message: "Failed to compare contract versions".to_string(),
+            error: Some(e),
             data: None::<String>,
         })
-        .into_response()
+        .into_response(),
     }
 }
 
 #[axum::debug_handler]
-
-pub async fn register_contract(
+pub async fn link_contract_package(
     state: State<Arc<AppState>>,
-    Path(user_id): Path<Uuid>,
-    Json(payload): Json<RegisterContractRequest>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Json(payload): Json<LinkContractPackageRequest>,
 ) -> impl IntoResponse {
-    match resolve_network(&payload.network.to_string()) {
-        Some(network) => {
-            let package_hash_norm = normalize_hash(&payload.package_hash);
-            let node_address: String = if network == "testnet" {
-                state.config.testnet_node_address.clone()
-            } else {
-                state.config.mainnet_node_address.clone()
-            };
-            let contract_package_details =
-                get_contract_package_details(node_address.clone(), package_hash_norm.clone()).await;
-
-            match contract_package_details {
-                Ok(data) => {
-                    let mut package_hash: String = payload.package_hash.clone();
+    let testnet_package_hash = strip_hash_prefix(&package_hash);
+    let mainnet_package_hash = strip_hash_prefix(&payload.mainnet_package_hash);
 
-                    if let Some(stripped) = package_hash.strip_prefix("hash-") {
-                        package_hash = stripped.to_string();
-                    }
-                    tracing::info!("Getting metadata for contract package: {}", package_hash);
-                    let package_meta = get_contract_package_metadata(&network, &package_hash).await;
+    for (package_hash, expected_network) in [
+        (&testnet_package_hash, "testnet"),
+        (&mainnet_package_hash, "mainnet"),
+    ] {
+        match get_contract_package(&state.db, &user_id, package_hash).await {
+            Ok(Some(pkg)) if pkg.network == expected_network => {}
+            Ok(Some(pkg)) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Invalid package link".to_string(),
+                    error: Some(format!(
+                        "Contract package {} is on {}, expected {}",
+                        package_hash, pkg.network, expected_network
+                    )),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+            Ok(None) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Contract package not found".to_string(),
+                    error: Some(format!("Contract package {} not found", package_hash)),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+            Err(e) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Database error".to_string(),
+                    error: Some(e.to_string()),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+        }
+    }
 
-                    if let Err(e) = package_meta {
-                        return Json(ApiResponse {
-                            success: false,
-                            message: "Failed to register contract".to_string(),
-                            error: Some(format!("Failed to get contract package metadata: {}", e)),
-                            data: None::<String>,
-                        })
-                        .into_response();
-                    }
+    let link = ContractPackageLinkSchema {
+        user_id,
+        testnet_package_hash,
+        mainnet_package_hash,
+        created_at: Utc::now(),
+    };
+    if let Err(e) = insert_contract_package_link(&state.db, &link).await {
+        return Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response();
+    }
 
-                    let package_meta = package_meta.unwrap();
-                    let package_hash = package_hash.clone();
-                    let contract_name = payload.package_name.clone();
-                    let owner_id = package_meta.owner_public_key.clone();
-                    let lock_status = data.is_locked();
-                    let age = package_meta.timestamp.clone().parse::<DateTime<Utc>>();
+    let node_addresses = (
+        state.config.node_address("testnet").unwrap_or_default(),
+        state.config.node_address("mainnet").unwrap_or_default(),
+    );
+    match evaluate_promotion(&state.db, node_addresses, &link).await {
+        Ok(status) => Json(ApiResponse {
+            success: true,
+            message: "Contract packages linked successfully".to_string(),
+            error: None::<String>,
+            data: Some(status),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Failed to evaluate promotion status".to_string(),
+            error: Some(e),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
 
-                    if let Err(e) = age {
-                        return Json(ApiResponse {
-                            success: false,
-                            message: "Failed to register contract".to_string(),
-                            error: Some(format!("Failed to parse date: {}", e)),
-                            data: None::<String>,
-                        })
-                        .into_response();
-                    }
+#[axum::debug_handler]
+pub async fn unlink_contract_package(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    match delete_contract_package_link(&state.db, &user_id, &package_hash).await {
+        Ok(true) => Json(ApiResponse {
+            success: true,
+            message: "Contract packages unlinked successfully".to_string(),
+            error: None::<String>,
+            data: None::<String>,
+        })
+        .into_response(),
+        Ok(false) => Json(ApiResponse {
+            success: false,
+            message: "Contract package link not found".to_string(),
+            error: Some("Contract package link not found".to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_contract_promotion_status(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    let link = match get_contract_package_link(&state.db, &user_id, &package_hash).await {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Contract package link not found".to_string(),
+                error: Some("Contract package is not linked to a counterpart".to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
+    let node_addresses = (
+        state.config.node_address("testnet").unwrap_or_default(),
+        state.config.node_address("mainnet").unwrap_or_default(),
+    );
+    match evaluate_promotion(&state.db, node_addresses, &link).await {
+        Ok(status) => Json(ApiResponse {
+            success: true,
+            message: "Promotion status fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(status),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Failed to evaluate promotion status".to_string(),
+            error: Some(e),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_local_contract_diff(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Json(payload): Json<LocalDiffRequest>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    let wasm = match base64::engine::general_purpose::STANDARD.decode(payload.wasm.trim()) {
+        Ok(wasm) => wasm,
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Invalid Wasm upload".to_string(),
+                error: Some(format!("Wasm is not valid base64: {}", e)),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
+    let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
+        Ok(Some(pkg)) => pkg.network,
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Contract package not found".to_string(),
+                error: Some("Contract package not found".to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+    let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+        return unconfigured_network_response(&network);
+    };
+
+    let (versions, diff_rules) = match (
+        get_contract_versions(&state.db, &package_hash, &user_id).await,
+        get_diff_rules(&state.db, &user_id, Some(&package_hash)).await,
+    ) {
+        (Ok(versions), Ok(rules)) => (versions, rules),
+        (Err(e), _) | (_, Err(e)) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
+    // Versions come newest first, the upgrade replaces the latest enabled one
+    let Some(deployed) = versions
+        .iter()
+        .find(|v| !v.disabled)
+        .or_else(|| versions.first())
+    else {
+        return Json(ApiResponse {
+            success: false,
+            message: "No deployed version found".to_string(),
+            error: Some("Contract package has no versions to diff against".to_string()),
+            data: None::<String>,
+        })
+        .into_response();
+    };
+
+    match get_local_version_diff(
+        &state.db,
+        &node_address,
+        &user_id,
+        deployed,
+        &wasm,
+        &payload.schema,
+    )
+    .await
+    {
+        Ok(mut diff) => {
+            apply_diff_rules(&mut diff, &diff_rules);
+            Json(ApiResponse {
+                success: true,
+                message: "Local diff calculated successfully".to_string(),
+                error: None::<String>,
+                data: Some(diff),
+            })
+            .into_response()
+        }
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Failed to calculate local diff".to_string(),
+            error: Some(e),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_contract_wat_diff_page(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<ContractWatDiffQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
+        Ok(Some(pkg)) => pkg.network,
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Contract package not found".to_string(),
+                error: Some("Contract package not found".to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+    let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+        return unconfigured_network_response(&network);
+    };
+
+    let mut wasm_hashes = vec![];
+    for version in [query.v1, query.v2] {
+        match get_contract_version(&state.db, &package_hash, version, &user_id).await {
+            Ok(Some(v)) => wasm_hashes.push(v.contract_wasm_hash),
+            Ok(None) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: format!("Version {} not found", version),
+                    error: Some(format!("Contract version {} not found", version)),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+            Err(e) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Database error".to_string(),
+                    error: Some(e.to_string()),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+        }
+    }
+
+    match get_contract_wat_diff(
+        &state.db,
+        &node_address,
+        &wasm_hashes[0],
+        &wasm_hashes[1],
+        query.function.as_deref(),
+        query.page,
+        query.page_size,
+    )
+    .await
+    {
+        Ok(diff) => Json(ApiResponse {
+            success: true,
+            message: "WAT diff calculated successfully".to_string(),
+            error: None::<String>,
+            data: Some(diff),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Failed to calculate WAT diff".to_string(),
+            error: Some(e),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_contract_history(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<ContractHistoryQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
+        Ok(Some(pkg)) => pkg.network,
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Contract package not found".to_string(),
+                error: Some("Contract package not found".to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+    let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+        return unconfigured_network_response(&network);
+    };
+
+    // Sample the requested heights, or the whole chain from `from` (genesis by default) to `to` (latest by default)
+    let heights: Vec<Option<u64>> = match &query.heights {
+        Some(heights) => {
+            let parsed: Result<Vec<Option<u64>>, _> = heights
+                .split(',')
+                .map(|h| h.trim().parse::<u64>().map(Some))
+                .collect();
+            match parsed {
+                Ok(parsed) if !parsed.is_empty() => parsed,
+                _ => {
+                    return Json(ApiResponse {
+                        success: false,
+                        message: "Invalid block heights".to_string(),
+                        error: Some(format!("Unable to parse block heights: {}", heights)),
+                        data: None::<String>,
+                    })
+                    .into_response();
+                }
+            }
+        }
+        None => vec![Some(query.from.unwrap_or(0)), query.to],
+    };
+
+    match get_contract_package_history(
+        &node_address,
+        &package_hash,
+        &heights,
+        query.bisect.unwrap_or(true),
+    )
+    .await
+    {
+        Ok(history) => Json(ApiResponse {
+            success: true,
+            message: "Contract package history reconstructed successfully".to_string(),
+            error: None::<String>,
+            data: Some(history),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Failed to reconstruct contract package history".to_string(),
+            error: Some(e),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_contract_storage(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Json(query): Json<OdraStorageQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
+        Ok(Some(pkg)) => pkg.network,
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Contract package not found".to_string(),
+                error: Some("Contract package not found".to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+    let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+        return unconfigured_network_response(&network);
+    };
+
+    // Storage is read from the latest enabled version, so is its layout
+    let schema_fields = match get_contract_versions(&state.db, &package_hash, &user_id).await {
+        Ok(versions) => match versions.iter().find(|v| !v.disabled) {
+            Some(version) => get_version_schema(&state.db, &node_address, &user_id, version)
+                .await
+                .and_then(|schema| match schema {
+                    Some(schema) => get_schema_storage_fields(&schema.schema),
+                    None => Ok(vec![]),
+                }),
+            None => Ok(vec![]),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    // Fields laid out by hand are enough to read without the schema
+    let schema_fields = match schema_fields {
+        Err(e) if !query.fields.is_empty() => {
+            log::warn!(
+                "Failed to get the storage layout of {}: {}",
+                package_hash,
+                e
+            );
+            Ok(vec![])
+        }
+        other => other,
+    };
+    let fields = match schema_fields
+        .and_then(|fields| resolve_storage_fields(fields, &query.fields, &query.reads))
+    {
+        Ok(fields) if fields.is_empty() => {
+            return Json(ApiResponse {
+                success: false,
+                message: "No storage fields to read".to_string(),
+                error: Some(
+                    "The contract schema has no storage layout and no fields were given"
+                        .to_string(),
+                ),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Ok(fields) => fields,
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Failed to resolve storage fields".to_string(),
+                error: Some(e),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
+    match get_odra_storage_values(&node_address, &package_hash, &fields).await {
+        Ok(values) => Json(ApiResponse {
+            success: true,
+            message: "Contract storage read successfully".to_string(),
+            error: None::<String>,
+            data: Some(values),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Failed to read contract storage".to_string(),
+            error: Some(e),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn upload_contract_version_schema(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash, version)): Path<(Uuid, String, u32)>,
+    Json(schema): Json<OdraContractSchema>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    match get_contract_version(&state.db, &package_hash, version, &user_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Version not found".to_string(),
+                error: Some(format!("Contract version {} not found", version)),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    }
+
+    let schema = ContractVersionOdraSchema {
+        contract_package_hash: package_hash,
+        contract_version: version,
+        source: OdraSchemaSource::Upload,
+        schema,
+    };
+    match upsert_contract_schema(&state.db, &user_id, &schema).await {
+        Ok(_) => Json(ApiResponse {
+            success: true,
+            message: "Contract schema attached successfully".to_string(),
+            error: None::<String>,
+            data: Some(schema),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_contract_version_schema(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash, version)): Path<(Uuid, String, u32)>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    let (network, contract_version) = match (
+        get_contract_package(&state.db, &user_id, &package_hash).await,
+        get_contract_version(&state.db, &package_hash, version, &user_id).await,
+    ) {
+        (Ok(Some(pkg)), Ok(Some(v))) => (pkg.network, v),
+        (Ok(_), Ok(_)) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Version not found".to_string(),
+                error: Some(format!("Contract version {} not found", version)),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+    let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+        return unconfigured_network_response(&network);
+    };
+
+    match get_version_schema(&state.db, &node_address, &user_id, &contract_version).await {
+        Ok(Some(schema)) => Json(ApiResponse {
+            success: true,
+            message: "Contract schema fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(schema),
+        })
+        .into_response(),
+        Ok(None) => Json(ApiResponse {
+            success: false,
+            message: "Contract schema not found".to_string(),
+            error: Some(format!(
+                "No schema was uploaded for version {} and none is embedded in its Wasm",
+                version
+            )),
+            data: None::<String>,
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Failed to get contract schema".to_string(),
+            error: Some(e),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn verify_contract_version(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash, version)): Path<(Uuid, String, u32)>,
+    Json(payload): Json<VerifyBuildRequest>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    // Builds run code from the uploaded archive, they are never run outside the sandbox
+    if state.config.build_sandbox_command.is_none() {
+        return Json(ApiResponse {
+            success: false,
+            message: "Verification builds are disabled".to_string(),
+            error: Some("No BUILD_SANDBOX_COMMAND is configured on the server".to_string()),
+            data: None::<String>,
+        })
+        .into_response();
+    }
+
+    if let Some(invalid) = [
+        &payload.contract_name,
+        &payload.toolchain,
+        &payload.cargo_odra_version,
+    ]
+    .into_iter()
+    .chain(payload.features.iter())
+    .find(|v| !is_safe_build_argument(v))
+    {
+        return Json(ApiResponse {
+            success: false,
+            message: "Invalid build metadata".to_string(),
+            error: Some(format!("Unsupported build argument: {:?}", invalid)),
+            data: None::<String>,
+        })
+        .into_response();
+    }
+    if payload.commit_id.trim().is_empty() {
+        return Json(ApiResponse {
+            success: false,
+            message: "Invalid build metadata".to_string(),
+            error: Some("Commit id must not be empty".to_string()),
+            data: None::<String>,
+        })
+        .into_response();
+    }
+
+    // Checked before decoding, so an oversized archive is never held twice
+    if payload.source.trim().len() > MAX_ENCODED_SOURCE_ARCHIVE_SIZE {
+        return Json(ApiResponse {
+            success: false,
+            message: "Invalid source upload".to_string(),
+            error: Some(format!(
+                "Source archive exceeds {} bytes",
+                MAX_SOURCE_ARCHIVE_SIZE
+            )),
+            data: None::<String>,
+        })
+        .into_response();
+    }
+    let archive = match base64::engine::general_purpose::STANDARD.decode(payload.source.trim()) {
+        Ok(archive) => archive,
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Invalid source upload".to_string(),
+                error: Some(format!("Source archive is not valid base64: {}", e)),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+    if archive.len() > MAX_SOURCE_ARCHIVE_SIZE {
+        return Json(ApiResponse {
+            success: false,
+            message: "Invalid source upload".to_string(),
+            error: Some(format!(
+                "Source archive exceeds {} bytes",
+                MAX_SOURCE_ARCHIVE_SIZE
+            )),
+            data: None::<String>,
+        })
+        .into_response();
+    }
+
+    let contract_version =
+        match get_contract_version(&state.db, &package_hash, version, &user_id).await {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Version not found".to_string(),
+                    error: Some(format!("Contract version {} not found", version)),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+            Err(e) => {
+                return Json(ApiResponse {
+                    success: false,
+                    message: "Database error".to_string(),
+                    error: Some(e.to_string()),
+                    data: None::<String>,
+                })
+                .into_response();
+            }
+        };
+
+    let network = match get_contract_package(&state.db, &user_id, &package_hash).await {
+        Ok(Some(pkg)) => pkg.network,
+        Ok(None) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Contract package not found".to_string(),
+                error: Some("Contract package not found".to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Database error".to_string(),
+                error: Some(e.to_string()),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+    let Some(node_address) = state.config.node_address(&network).map(str::to_string) else {
+        return unconfigured_network_response(&network);
+    };
+
+    let Some(queued) = queue_build() else {
+        return Json(ApiResponse {
+            success: false,
+            message: "Verification queue is full".to_string(),
+            error: Some("Too many verification builds are queued, retry later".to_string()),
+            data: None::<String>,
+        })
+        .into_response();
+    };
+
+    let verification = ContractVerificationSchema {
+        id: Uuid::new_v4(),
+        contract_package_hash: package_hash,
+        contract_version: version,
+        user_id,
+        commit_id: payload.commit_id.trim().to_string(),
+        contract_name: payload.contract_name,
+        toolchain: payload.toolchain,
+        cargo_odra_version: payload.cargo_odra_version,
+        features: payload.features,
+        status: ContractVerificationStatus::Pending,
+        expected_wasm_hash: strip_hash_prefix(&contract_version.contract_wasm_hash),
+        built_wasm_hash: None,
+        build_log: None,
+        created_at: Utc::now(),
+        completed_at: None,
+    };
+    if let Err(e) = insert_contract_verification(&state.db, &verification).await {
+        return Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response();
+    }
+
+    tokio::spawn(run_contract_verification(
+        state.db.clone(),
+        state.config.clone(),
+        node_address,
+        verification.clone(),
+        archive,
+        queued,
+    ));
+
+    Json(ApiResponse {
+        success: true,
+        message: "Verification build queued".to_string(),
+        error: None::<String>,
+        data: Some(verification),
+    })
+    .into_response()
+}
+
+#[axum::debug_handler]
+pub async fn get_contract_version_verifications(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash, version)): Path<(Uuid, String, u32)>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+
+    match get_contract_verifications(&state.db, &package_hash, &user_id, Some(version)).await {
+        Ok(verifications) => Json(ApiResponse {
+            success: true,
+            message: "Verifications fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(verifications),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_diff_analysis(
+    state: State<Arc<AppState>>,
+    Path(_user_id): Path<Uuid>,
+    Json(payload): Json<ContractVersionDiff>,
+) -> impl IntoResponse {
+    let hf_token = state.config.huggingface_token.clone();
+    let content = format!(
+        "There are 2 smart contract deployment for same package on Casper Network. This is the diff between them:\n{:#?}\nwhere v1 contains metadata on previous version and v2 on new version, entry points and named keys show the changes from v1 to v2. Explain what it means for smart contract and how it affects development and security",
+        payload
+    );
+    let body = serde_json::json!({
+        "messages": [
+            {
+                "role": "user",
+                "content": content
+            }
+        ],
+        "model": "meta-llama/Llama-3.1-8B-Instruct:cerebras",
+        "stream": false
+    });
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post("https://router.huggingface.co/v1/chat/completions")
+        .header("Authorization", format!("Bearer {}", hf_token))
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await;
+    if let Err(err) = &response {
+        return Json(ApiResponse {
+            success: false,
+            message: "Failed to get completions for diff".to_string(),
+            error: Some(format!("Failed to get completions for diff: {}", err)),
+            data: None::<String>,
+        })
+        .into_response();
+    }
+    let response = response.unwrap();
+    if response.status().is_success() {
+        let response_body = response.json::<serde_json::Value>().await;
+        if let Err(e) = response_body {
+            return Json(ApiResponse {
+                success: false,
+                message: "Failed to parse response to JSON".to_string(),
+                error: Some(format!("Failed to parse response to JSON: {}", e)),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+        let response_body = response_body.unwrap();
+        if let Some(content) = response_body
+            .get("choices")
+            .and_then(|choice| choice.get(0))
+            .and_then(|idx| idx.get("message"))
+            .and_then(|message| message.get("content"))
+        {
+            return Json(ApiResponse {
+                success: true,
+                message: "Successfully retrieved response".to_string(),
+                error: None::<String>,
+                data: Some(content.as_str().to_owned()),
+            })
+            .into_response();
+        }
+        Json(ApiResponse {
+            success: false,
+            message: "Failed to get data from response by parsing".to_string(),
+            error: Some("Failed to get data from response by parsing".to_string()),
+            data: None::<String>,
+        })
+        .into_response()
+    } else {
+        Json(ApiResponse {
+            success: false,
+            message: "Request failed".to_string(),
+            error: Some(format!(
+                "Request failed with status code: {}",
+                response.status()
+            )),
+            data: None::<String>,
+        })
+        .into_response()
+    }
+}
+
+/// Schedule the rescan of a package the block indexer has no install for from the configured
+/// backfill height, returning the registration error telling whether the install is looked for
+async fn backfill_unindexed_package(state: &AppState, network: &str, package_hash: &str) -> String {
+    let not_indexed = format!(
+        "Contract package {package_hash} was installed before the block indexer start height"
+    );
+    let Some(height) = state.config.indexer_backfill_heights.get(network) else {
+        return format!(
+            "{not_indexed}, set {}_INDEXER_BACKFILL_HEIGHT to rescan earlier blocks for it",
+            network_env_prefix(network)
+        );
+    };
+    match schedule_package_backfill(&state.db, network, package_hash, *height).await {
+        Ok(()) => format!(
+            "{not_indexed}, blocks from {height} are rescanned for its install, register it again once it is found"
+        ),
+        Err(e) => format!("{not_indexed}, failed to schedule the rescan of earlier blocks: {e}"),
+    }
+}
+
+#[axum::debug_handler]
+
+pub async fn register_contract(
+    state: State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<RegisterContractRequest>,
+) -> impl IntoResponse {
+    match resolve_network(&state.config, &payload.network) {
+        Some((network, node_address)) => {
+            let package_hash_norm = normalize_hash(&payload.package_hash);
+            let contract_package_details =
+                get_contract_package_details(node_address.clone(), package_hash_norm.clone()).await;
+
+            match contract_package_details {
+                Ok(data) => {
+                    let package_hash = strip_hash_prefix(&payload.package_hash);
+                    tracing::info!("Getting metadata for contract package: {}", package_hash);
+                    let indexed =
+                        (state.config.indexer_mode == IndexerMode::Node).then_some(&state.db);
+                    // The block the package was installed in, rescanned for the transactions the
+                    // block indexer read before the package was tracked
+                    let package_meta = match indexed {
+                        Some(pool) => {
+                            match get_indexed_package_metadata(pool, &network, &package_hash).await
+                            {
+                                Ok(Some(write)) => Ok((
+                                    write.caller_public_key,
+                                    write.timestamp,
+                                    Some(write.block_height),
+                                )),
+                                Ok(None) => {
+                                    Err(backfill_unindexed_package(&state, &network, &package_hash)
+                                        .await)
+                                }
+                                Err(e) => Err(e),
+                            }
+                        }
+                        None => {
+                            match get_contract_package_metadata(&network, &package_hash).await {
+                                Ok(meta) => meta
+                                    .timestamp
+                                    .parse::<DateTime<Utc>>()
+                                    .map(|age| (meta.owner_public_key, age, None))
+                                    .map_err(|e| format!("Failed to parse date: {}", e)),
+                                Err(e) => {
+                                    Err(format!("Failed to get contract package metadata: {}", e))
+                                }
+                            }
+                        }
+                    };
 
-                    let age = age.unwrap();
+                    let (owner_id, age, install_height) = match package_meta {
+                        Ok(meta) => meta,
+                        Err(e) => {
+                            return Json(ApiResponse {
+                                success: false,
+                                message: "Failed to register contract".to_string(),
+                                error: Some(e),
+                                data: None::<String>,
+                            })
+                            .into_response();
+                        }
+                    };
+                    let package_hash = package_hash.clone();
+                    let contract_name = payload.package_name.clone();
+                    let lock_status = data.is_locked();
 
                     let contract_package = ContractPackageSchema::new(
                         package_hash.clone(),
@@ -507,7 +1779,8 @@ pub async fn register_contract(
                         &node_address,
                         &network,
                         user_id,
-                        data.versions(),
+                        &data,
+                        indexed,
                     )
                     .await
                     {
@@ -542,6 +1815,22 @@ pub async fn register_contract(
                                 .into_response();
                             }
 
+                            if let Some(height) = install_height
+                                && let Err(e) = schedule_package_backfill(
+                                    &state.db,
+                                    &network,
+                                    &package_hash,
+                                    height,
+                                )
+                                .await
+                            {
+                                tracing::error!(
+                                    "Failed to schedule the backfill of {}: {}",
+                                    package_hash,
+                                    e
+                                );
+                            }
+
                             let _ = write_contract_diff_versions_to_chain(
                                 &package_hash,
                                 versions_details,
@@ -561,15 +1850,13 @@ pub async fn register_contract(
                             .into_response()
                         }
 
-                        Err(e) => {
-                            return Json(ApiResponse {
-                                success: false,
-                                message: "Failed to register contract".to_string(),
-                                error: Some(format!("Database error: {}", e)),
-                                data: None::<String>,
-                            })
-                            .into_response();
-                        }
+                        Err(e) => Json(ApiResponse {
+                            success: false,
+                            message: "Failed to register contract".to_string(),
+                            error: Some(format!("Database error: {}", e)),
+                            data: None::<String>,
+                        })
+                        .into_response(),
                     }
                 }
 
@@ -586,7 +1873,10 @@ pub async fn register_contract(
         None => Json(ApiResponse {
             success: false,
             message: "Invalid network provided".to_string(),
-            error: Some("Network must be one of: mainnet, testnet, localnet".to_string()),
+            error: Some(format!(
+                "Network must be one of: {}",
+                configured_networks(&state.config)
+            )),
             data: None::<String>,
         })
         .into_response(),
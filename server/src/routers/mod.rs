@@ -1,2 +1,5 @@
+pub(crate) mod alert;
 pub(crate) mod contract;
 pub(crate) mod health;
+pub(crate) mod rules;
+pub(crate) mod transaction;
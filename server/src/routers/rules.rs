@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    config::AppState,
+    models::{
+        api::{
+            ApiResponse,
+            rules::{DiffRuleRequest, DiffRulesQuery},
+        },
+        schema::rules::{DiffRuleAction, DiffRuleSchema},
+    },
+    services::database::{
+        contract::get_contract_package,
+        rules::{delete_diff_rule, get_diff_rules, insert_diff_rule, update_diff_rule},
+    },
+    utils::strip_hash_prefix,
+};
+
+/// Validate a rule request and build the rule it describes
+async fn build_diff_rule(
+    state: &AppState,
+    user_id: Uuid,
+    rule_id: Uuid,
+    payload: DiffRuleRequest,
+) -> Result<DiffRuleSchema, String> {
+    if payload.pattern.trim().is_empty() {
+        return Err("Rule pattern must not be empty".to_string());
+    }
+    if payload.action == DiffRuleAction::Rename
+        && payload
+            .replacement
+            .as_deref()
+            .is_none_or(|r| r.trim().is_empty())
+    {
+        return Err("Rename rules require a replacement".to_string());
+    }
+
+    let package_hash = payload.package_hash.as_deref().map(strip_hash_prefix);
+    if let Some(package_hash) = &package_hash {
+        match get_contract_package(&state.db, &user_id, package_hash).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err("Contract package not found".to_string()),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    Ok(DiffRuleSchema {
+        id: rule_id,
+        user_id,
+        package_hash,
+        target: payload.target,
+        action: payload.action,
+        pattern: payload.pattern,
+        replacement: payload.replacement,
+        created_at: Utc::now(),
+    })
+}
+
+#[axum::debug_handler]
+pub async fn list_diff_rules(
+    state: State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<DiffRulesQuery>,
+) -> impl IntoResponse {
+    let package_hash = query.package_hash.as_deref().map(strip_hash_prefix);
+    match get_diff_rules(&state.db, &user_id, package_hash.as_deref()).await {
+        Ok(rules) => Json(ApiResponse {
+            success: true,
+            message: "Diff rules fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(rules),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn create_diff_rule(
+    state: State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<DiffRuleRequest>,
+) -> impl IntoResponse {
+    let rule = match build_diff_rule(&state, user_id, Uuid::new_v4(), payload).await {
+        Ok(rule) => rule,
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Invalid diff rule".to_string(),
+                error: Some(e),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
+    match insert_diff_rule(&state.db, &rule).await {
+        Ok(_) => Json(ApiResponse {
+            success: true,
+            message: "Diff rule created successfully".to_string(),
+            error: None::<String>,
+            data: Some(rule),
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn edit_diff_rule(
+    state: State<Arc<AppState>>,
+    Path((user_id, rule_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<DiffRuleRequest>,
+) -> impl IntoResponse {
+    let rule = match build_diff_rule(&state, user_id, rule_id, payload).await {
+        Ok(rule) => rule,
+        Err(e) => {
+            return Json(ApiResponse {
+                success: false,
+                message: "Invalid diff rule".to_string(),
+                error: Some(e),
+                data: None::<String>,
+            })
+            .into_response();
+        }
+    };
+
+    match update_diff_rule(&state.db, &rule).await {
+        Ok(Some(created_at)) => Json(ApiResponse {
+            success: true,
+            message: "Diff rule updated successfully".to_string(),
+            error: None::<String>,
+            data: Some(DiffRuleSchema { created_at, ..rule }),
+        })
+        .into_response(),
+        Ok(None) => Json(ApiResponse {
+            success: false,
+            message: "Diff rule not found".to_string(),
+            error: Some("Diff rule not found".to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn remove_diff_rule(
+    state: State<Arc<AppState>>,
+    Path((user_id, rule_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    match delete_diff_rule(&state.db, &user_id, &rule_id).await {
+        Ok(true) => Json(ApiResponse {
+            success: true,
+            message: "Diff rule deleted successfully".to_string(),
+            error: None::<String>,
+            data: None::<String>,
+        })
+        .into_response(),
+        Ok(false) => Json(ApiResponse {
+            success: false,
+            message: "Diff rule not found".to_string(),
+            error: Some("Diff rule not found".to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            message: "Database error".to_string(),
+            error: Some(e.to_string()),
+            data: None::<String>,
+        })
+        .into_response(),
+    }
+}
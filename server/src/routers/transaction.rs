@@ -0,0 +1,581 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    response::{IntoResponse, Response},
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    config::AppState,
+    models::{
+        api::{
+            ApiResponse,
+            analytics::{
+                AnalyticsBucket, AnalyticsGroup, AnalyticsQuery, AnomaliesQuery, CallSeries,
+                EntryPointUsageQuery, ImpactQuery,
+            },
+            transaction::{ContractErrorCodeRequest, TransactionFailuresQuery},
+        },
+        schema::{
+            contract::ContractVersionSchema,
+            transaction::{ContractErrorCodeSchema, TransactionOutcomeCount},
+        },
+    },
+    services::{
+        contract::{
+            failure::{ErrorCodeNames, get_failure_breakdown},
+            impact::get_impact_report,
+            usage::get_entry_point_usage,
+        },
+        database::{
+            analytics::{get_call_series, get_call_stats, get_top_callers},
+            anomaly::{get_transaction_anomalies, get_transaction_baselines},
+            contract::{get_contract_package, get_contract_version},
+            failure::{
+                get_contract_error_codes, get_transaction_outcome_counts,
+                replace_contract_error_codes,
+            },
+            rules::get_diff_rules,
+        },
+    },
+    utils::strip_hash_prefix,
+};
+
+const DEFAULT_TOP_CALLERS_LIMIT: u64 = 10;
+const MAX_TOP_CALLERS_LIMIT: u64 = 100;
+
+/// Buckets a call series may span, 90 days of hours
+const MAX_SERIES_BUCKETS: i64 = 90 * 24;
+
+const DEFAULT_IMPACT_LOOKBACK_DAYS: u32 = 30;
+const MAX_IMPACT_LOOKBACK_DAYS: u32 = 365;
+
+const DEFAULT_IDLE_DAYS: u32 = 30;
+
+const DEFAULT_ANOMALIES_LIMIT: u64 = 50;
+const MAX_ANOMALIES_LIMIT: u64 = 500;
+
+fn error_response(message: &str, error: String) -> Response {
+    Json(ApiResponse {
+        success: false,
+        message: message.to_string(),
+        error: Some(error),
+        data: None::<String>,
+    })
+    .into_response()
+}
+
+/// Network of a tracked package, or the response to return when it is not tracked
+async fn get_package_network(
+    state: &AppState,
+    user_id: &Uuid,
+    package_hash: &str,
+) -> Result<String, Response> {
+    match get_contract_package(&state.db, user_id, package_hash).await {
+        Ok(Some(pkg)) => Ok(pkg.network),
+        Ok(None) => Err(error_response(
+            "Contract package not found",
+            "Contract package not found".to_string(),
+        )),
+        Err(e) => Err(error_response("Database error", e.to_string())),
+    }
+}
+
+/// Stored version of a package, or the response to return when it does not exist
+async fn get_package_version(
+    state: &AppState,
+    user_id: &Uuid,
+    package_hash: &str,
+    version: u32,
+) -> Result<ContractVersionSchema, Response> {
+    match get_contract_version(&state.db, package_hash, version, user_id).await {
+        Ok(Some(v)) => Ok(v),
+        Ok(None) => Err(error_response(
+            "Contract version not found",
+            format!("Contract version {} not found", version),
+        )),
+        Err(e) => Err(error_response("Database error", e.to_string())),
+    }
+}
+
+/// Failure breakdown of the indexed transactions of a package, grouped by `group_of`
+async fn get_failures(
+    state: &AppState,
+    user_id: Uuid,
+    package_hash: &str,
+    query: &TransactionFailuresQuery,
+    group_of: impl Fn(&TransactionOutcomeCount) -> Option<String>,
+) -> Response {
+    let package_hash = strip_hash_prefix(package_hash);
+    let network = match get_package_network(state, &user_id, &package_hash).await {
+        Ok(network) => network,
+        Err(response) => return response,
+    };
+
+    let outcomes = match get_transaction_outcome_counts(
+        &state.db,
+        &network,
+        &package_hash,
+        &user_id,
+        query.from,
+        query.to,
+    )
+    .await
+    {
+        Ok(outcomes) => outcomes,
+        Err(e) => return error_response("Failed to fetch transactions", e.to_string()),
+    };
+    let versions: Vec<u32> = outcomes.iter().filter_map(|o| o.contract_version).collect();
+    let names = match ErrorCodeNames::load(&state.db, &package_hash, &user_id, &versions).await {
+        Ok(names) => names,
+        Err(e) => return error_response("Failed to load error codes", e),
+    };
+
+    Json(ApiResponse {
+        success: true,
+        message: "Failure breakdown fetched successfully".to_string(),
+        error: None::<String>,
+        data: Some(get_failure_breakdown(&outcomes, &names, group_of)),
+    })
+    .into_response()
+}
+
+#[axum::debug_handler]
+pub async fn get_entry_point_failures(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<TransactionFailuresQuery>,
+) -> impl IntoResponse {
+    get_failures(&state, user_id, &package_hash, &query, |outcome| {
+        outcome.entry_point_name.clone()
+    })
+    .await
+}
+
+#[axum::debug_handler]
+pub async fn get_version_failures(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<TransactionFailuresQuery>,
+) -> impl IntoResponse {
+    get_failures(&state, user_id, &package_hash, &query, |outcome| {
+        outcome.contract_version.map(|v| v.to_string())
+    })
+    .await
+}
+
+#[axum::debug_handler]
+pub async fn list_contract_error_codes(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    if let Err(response) = get_package_network(&state, &user_id, &package_hash).await {
+        return response;
+    }
+
+    match get_contract_error_codes(&state.db, &package_hash, &user_id).await {
+        Ok(error_codes) => Json(ApiResponse {
+            success: true,
+            message: "Error codes fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(error_codes),
+        })
+        .into_response(),
+        Err(e) => error_response("Database error", e.to_string()),
+    }
+}
+
+/// Replace the manual error code names of a package
+#[axum::debug_handler]
+pub async fn set_contract_error_codes(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Json(payload): Json<Vec<ContractErrorCodeRequest>>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    if let Err(response) = get_package_network(&state, &user_id, &package_hash).await {
+        return response;
+    }
+
+    let mut codes = HashSet::new();
+    for error_code in &payload {
+        // User errors revert with a 16-bit code
+        if error_code.code > u32::from(u16::MAX) {
+            return error_response(
+                "Invalid error codes",
+                format!("Error code {} is out of range", error_code.code),
+            );
+        }
+        if error_code.name.trim().is_empty() {
+            return error_response(
+                "Invalid error codes",
+                format!("Error code {} has no name", error_code.code),
+            );
+        }
+        if !codes.insert(error_code.code) {
+            return error_response(
+                "Invalid error codes",
+                format!("Error code {} is listed twice", error_code.code),
+            );
+        }
+    }
+
+    let error_codes: Vec<ContractErrorCodeSchema> = payload
+        .into_iter()
+        .map(|error_code| ContractErrorCodeSchema {
+            code: error_code.code,
+            name: error_code.name.trim().to_string(),
+            description: error_code.description,
+        })
+        .collect();
+    match replace_contract_error_codes(&state.db, &package_hash, &user_id, &error_codes).await {
+        Ok(()) => Json(ApiResponse {
+            success: true,
+            message: "Error codes saved successfully".to_string(),
+            error: None::<String>,
+            data: Some(error_codes),
+        })
+        .into_response(),
+        Err(e) => error_response("Database error", e.to_string()),
+    }
+}
+
+/// Call statistics of a package, grouped by `group`
+async fn get_stats(
+    state: &AppState,
+    user_id: Uuid,
+    package_hash: &str,
+    query: &AnalyticsQuery,
+    group: AnalyticsGroup,
+) -> Response {
+    let package_hash = strip_hash_prefix(package_hash);
+    let network = match get_package_network(state, &user_id, &package_hash).await {
+        Ok(network) => network,
+        Err(response) => return response,
+    };
+
+    match get_call_stats(
+        &state.db,
+        &network,
+        &package_hash,
+        &user_id,
+        group,
+        query.from,
+        query.to,
+    )
+    .await
+    {
+        Ok(stats) if group == AnalyticsGroup::None => Json(ApiResponse {
+            success: true,
+            message: "Call statistics fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(
+                stats
+                    .into_iter()
+                    .next()
+                    .map(|s| s.stats)
+                    .unwrap_or_default(),
+            ),
+        })
+        .into_response(),
+        Ok(stats) => Json(ApiResponse {
+            success: true,
+            message: "Call statistics fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(stats),
+        })
+        .into_response(),
+        Err(e) => error_response("Failed to compute call statistics", e.to_string()),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_package_call_stats(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
+    get_stats(&state, user_id, &package_hash, &query, AnalyticsGroup::None).await
+}
+
+#[axum::debug_handler]
+pub async fn get_entry_point_call_stats(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
+    get_stats(
+        &state,
+        user_id,
+        &package_hash,
+        &query,
+        AnalyticsGroup::EntryPoint,
+    )
+    .await
+}
+
+#[axum::debug_handler]
+pub async fn get_version_call_stats(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
+    get_stats(
+        &state,
+        user_id,
+        &package_hash,
+        &query,
+        AnalyticsGroup::Version,
+    )
+    .await
+}
+
+/// Calls of a package per hour or day, the last 48 hours or 30 days unless a range is given
+#[axum::debug_handler]
+pub async fn get_package_call_series(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    let network = match get_package_network(&state, &user_id, &package_hash).await {
+        Ok(network) => network,
+        Err(response) => return response,
+    };
+
+    let bucket = query.bucket.unwrap_or(AnalyticsBucket::Day);
+    let bucket_length = match bucket {
+        AnalyticsBucket::Hour => Duration::hours(1),
+        AnalyticsBucket::Day => Duration::days(1),
+    };
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or_else(|| {
+        to - match bucket {
+            AnalyticsBucket::Hour => Duration::hours(48),
+            AnalyticsBucket::Day => Duration::days(30),
+        }
+    });
+    if from >= to {
+        return error_response(
+            "Invalid time range",
+            "`from` must be before `to`".to_string(),
+        );
+    }
+    if (to - from).num_seconds() / bucket_length.num_seconds() > MAX_SERIES_BUCKETS {
+        return error_response(
+            "Invalid time range",
+            format!("Time range spans more than {} buckets", MAX_SERIES_BUCKETS),
+        );
+    }
+
+    match get_call_series(
+        &state.db,
+        &network,
+        &package_hash,
+        bucket,
+        query.entry_point.as_deref(),
+        from,
+        to,
+    )
+    .await
+    {
+        Ok(data) => Json(ApiResponse {
+            success: true,
+            message: "Call series fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(CallSeries {
+                bucket,
+                from,
+                to,
+                data,
+            }),
+        })
+        .into_response(),
+        Err(e) => error_response("Failed to compute call series", e.to_string()),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_package_top_callers(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    let network = match get_package_network(&state, &user_id, &package_hash).await {
+        Ok(network) => network,
+        Err(response) => return response,
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TOP_CALLERS_LIMIT)
+        .clamp(1, MAX_TOP_CALLERS_LIMIT);
+    match get_top_callers(
+        &state.db,
+        &network,
+        &package_hash,
+        query.from,
+        query.to,
+        limit,
+    )
+    .await
+    {
+        Ok(callers) => Json(ApiResponse {
+            success: true,
+            message: "Top callers fetched successfully".to_string(),
+            error: None::<String>,
+            data: Some(callers),
+        })
+        .into_response(),
+        Err(e) => error_response("Failed to fetch top callers", e.to_string()),
+    }
+}
+
+/// Callers of the entry points an upgrade removes or breaks, and how their calls fared after it
+#[axum::debug_handler]
+pub async fn get_upgrade_impact(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<ImpactQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    let network = match get_package_network(&state, &user_id, &package_hash).await {
+        Ok(network) => network,
+        Err(response) => return response,
+    };
+    let v1 = match get_package_version(&state, &user_id, &package_hash, query.v1).await {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let v2 = match get_package_version(&state, &user_id, &package_hash, query.v2).await {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+
+    let diff_rules = match get_diff_rules(&state.db, &user_id, Some(&package_hash)).await {
+        Ok(rules) => rules,
+        Err(e) => return error_response("Database error", e.to_string()),
+    };
+
+    let lookback_days = query
+        .lookback_days
+        .unwrap_or(DEFAULT_IMPACT_LOOKBACK_DAYS)
+        .clamp(1, MAX_IMPACT_LOOKBACK_DAYS);
+    match get_impact_report(
+        &state.db,
+        &network,
+        v1,
+        v2,
+        Duration::days(i64::from(lookback_days)),
+        &diff_rules,
+    )
+    .await
+    {
+        Ok(report) => Json(ApiResponse {
+            success: true,
+            message: "Impact report generated successfully".to_string(),
+            error: None::<String>,
+            data: Some(report),
+        })
+        .into_response(),
+        Err(e) => error_response("Failed to generate impact report", e),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn get_version_entry_point_usage(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash, version)): Path<(Uuid, String, u32)>,
+    Query(query): Query<EntryPointUsageQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    let package = match get_contract_package(&state.db, &user_id, &package_hash).await {
+        Ok(Some(pkg)) => pkg,
+        Ok(None) => {
+            return error_response(
+                "Contract package not found",
+                "Contract package not found".to_string(),
+            );
+        }
+        Err(e) => return error_response("Database error", e.to_string()),
+    };
+    let version = match get_package_version(&state, &user_id, &package_hash, version).await {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+
+    let idle_days = query.idle_days.unwrap_or(DEFAULT_IDLE_DAYS).max(1);
+    match get_entry_point_usage(&state.db, &package, version, idle_days).await {
+        Ok(report) => Json(ApiResponse {
+            success: true,
+            message: "Entry point usage retrieved successfully".to_string(),
+            error: None::<String>,
+            data: Some(report),
+        })
+        .into_response(),
+        Err(e) => error_response("Failed to get entry point usage", e),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn list_transaction_anomalies(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+    Query(query): Query<AnomaliesQuery>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    if let Err(response) = get_package_network(&state, &user_id, &package_hash).await {
+        return response;
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ANOMALIES_LIMIT)
+        .clamp(1, MAX_ANOMALIES_LIMIT);
+    match get_transaction_anomalies(
+        &state.db,
+        &package_hash,
+        &user_id,
+        query.metric,
+        query.from,
+        query.to,
+        limit,
+    )
+    .await
+    {
+        Ok(anomalies) => Json(ApiResponse {
+            success: true,
+            message: "Anomalies retrieved successfully".to_string(),
+            error: None::<String>,
+            data: Some(anomalies),
+        })
+        .into_response(),
+        Err(e) => error_response("Database error", e.to_string()),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn list_transaction_baselines(
+    state: State<Arc<AppState>>,
+    Path((user_id, package_hash)): Path<(Uuid, String)>,
+) -> impl IntoResponse {
+    let package_hash = strip_hash_prefix(&package_hash);
+    if let Err(response) = get_package_network(&state, &user_id, &package_hash).await {
+        return response;
+    }
+
+    match get_transaction_baselines(&state.db, &package_hash, &user_id).await {
+        Ok(baselines) => Json(ApiResponse {
+            success: true,
+            message: "Baselines retrieved successfully".to_string(),
+            error: None::<String>,
+            data: Some(baselines),
+        })
+        .into_response(),
+        Err(e) => error_response("Database error", e.to_string()),
+    }
+}
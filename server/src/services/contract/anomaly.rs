@@ -0,0 +1,510 @@
+use casper_types::EntryPointAccess;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        api::analytics::AnalyticsBucket,
+        schema::{
+            alert::AlertSchema,
+            anomaly::{AnomalyMetric, TransactionAnomalySchema, TransactionBaselineSchema},
+            contract::{ContractPackageSchema, ContractVersionSchema},
+        },
+    },
+    services::{
+        contract::{
+            failure::{ErrorCodeNames, get_failure_breakdown},
+            gas::{mann_whitney_greater, median, normal_sf},
+            usage::get_package_owners,
+        },
+        database::{
+            analytics::get_call_series,
+            anomaly::{get_cost_samples, get_new_caller_series, get_unknown_caller_calls},
+            contract::get_contract_versions,
+            failure::get_transaction_outcome_counts,
+        },
+    },
+};
+
+pub const TRANSACTION_ANOMALY_ALERT: &str = "TransactionAnomaly";
+
+/// Hours before the checked window the baselines are computed from
+const BASELINE_HOURS: i64 = 14 * 24;
+
+/// Hours of activity below which rates are not compared
+const MIN_BASELINE_HOURS: usize = 24;
+
+/// Significance level, low enough for hourly checks of every metric to rarely raise false alarms
+const ANOMALY_P_VALUE: f64 = 1e-4;
+
+/// Calls in the window below which the failure rate is not compared
+const MIN_WINDOW_CALLS: u64 = 10;
+
+/// Successful calls on either side below which costs are not compared
+const MIN_COST_SAMPLES: usize = 30;
+
+/// Latest successful calls on either side the cost comparison is based on
+const MAX_COST_SAMPLES: u64 = 1000;
+
+/// Median cost increase below which a significant difference is not reported
+const MIN_COST_INCREASE: f64 = 0.1;
+
+/// Calls of an admin entry point by an unknown caller within the window that raise an anomaly
+const MIN_ADMIN_CALLS: u64 = 5;
+
+/// Hours after an upgrade a failure spike is attributed to it
+const UPGRADE_HOURS: i64 = 24;
+
+/// Baselines of a package and the anomalies of the checked window
+#[derive(Debug, Default)]
+pub struct AnomalyDetection {
+    pub baselines: Vec<TransactionBaselineSchema>,
+    pub anomalies: Vec<TransactionAnomalySchema>,
+}
+
+/// Hour of transaction activity checked against the baselines of a package
+struct Window<'a> {
+    package: &'a ContractPackageSchema,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl Window<'_> {
+    fn baseline(&self, metric: AnomalyMetric, samples: &[f64]) -> TransactionBaselineSchema {
+        let (mean, stddev) = mean_stddev(samples);
+        TransactionBaselineSchema {
+            contract_package_hash: self.package.package_hash.clone(),
+            user_id: self.package.user_id,
+            metric,
+            mean,
+            stddev,
+            samples: samples.len() as u64,
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn anomaly(
+        &self,
+        metric: AnomalyMetric,
+        observed: f64,
+        expected: f64,
+        p_value: Option<f64>,
+        message: String,
+        evidence: serde_json::Value,
+    ) -> TransactionAnomalySchema {
+        TransactionAnomalySchema {
+            id: Uuid::new_v4(),
+            contract_package_hash: self.package.package_hash.clone(),
+            user_id: self.package.user_id,
+            metric,
+            window_start: self.start,
+            window_end: self.end,
+            observed,
+            expected,
+            p_value,
+            message,
+            evidence,
+            dedup_key: format!(
+                "anomaly:{}:{}:{}",
+                self.package.package_hash,
+                metric.as_str(),
+                self.start.timestamp()
+            ),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+fn mean_stddev(samples: &[f64]) -> (f64, f64) {
+    if samples.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples.iter().map(|s| (s - mean) * (s - mean)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+/// One-sided p-value of an hourly count against the counts of the baseline hours.
+///
+/// The variance is at least the mean, as for Poisson arrivals, so sparse but steady baselines do
+/// not turn every extra call into an anomaly.
+fn count_p_value(observed: f64, mean: f64, stddev: f64) -> f64 {
+    let variance = (stddev * stddev).max(mean).max(1.0);
+    normal_sf((observed - mean - 0.5) / variance.sqrt())
+}
+
+/// One-sided p-value of `failures` out of `calls` under the failure rate `rate`, normal
+/// approximation of the binomial test with continuity correction
+fn failure_p_value(failures: u64, calls: u64, rate: f64) -> f64 {
+    let n = calls as f64;
+    let variance = n * rate * (1.0 - rate);
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    normal_sf((failures as f64 - n * rate - 0.5) / variance.sqrt())
+}
+
+fn check_count(
+    window: &Window<'_>,
+    metric: AnomalyMetric,
+    what: &str,
+    baseline: &[f64],
+    observed: u64,
+    detection: &mut AnomalyDetection,
+) {
+    let baseline_schema = window.baseline(metric, baseline);
+    if baseline.len() >= MIN_BASELINE_HOURS {
+        let (mean, stddev) = (baseline_schema.mean, baseline_schema.stddev);
+        let p_value = count_p_value(observed as f64, mean, stddev);
+        if p_value < ANOMALY_P_VALUE {
+            detection.anomalies.push(window.anomaly(
+                metric,
+                observed as f64,
+                mean,
+                Some(p_value),
+                format!(
+                    "{} {} in the hour to {} against {:.1} per hour usually",
+                    observed, what, window.end, mean
+                ),
+                json!({
+                    "baseline_hours": baseline.len(),
+                    "baseline_stddev": stddev,
+                }),
+            ));
+        }
+    }
+    detection.baselines.push(baseline_schema);
+}
+
+/// Latest version installed shortly before or within the window
+fn recent_upgrade<'a>(
+    versions: &'a [ContractVersionSchema],
+    window: &Window<'_>,
+) -> Option<&'a ContractVersionSchema> {
+    versions
+        .iter()
+        .filter(|v| v.age >= window.start - Duration::hours(UPGRADE_HOURS) && v.age < window.end)
+        .max_by_key(|v| v.contract_version)
+}
+
+async fn check_failure_rate(
+    pool: &PgPool,
+    window: &Window<'_>,
+    versions: &[ContractVersionSchema],
+    (baseline_calls, baseline_failures): (u64, u64),
+    (calls, failures): (u64, u64),
+    detection: &mut AnomalyDetection,
+) -> Result<(), String> {
+    let package = window.package;
+    // Smoothed so a baseline without failures still allows for the occasional one
+    let rate = (baseline_failures as f64 + 1.0) / (baseline_calls as f64 + 2.0);
+    let mut baseline = window.baseline(AnomalyMetric::FailureRate, &[]);
+    baseline.mean = rate;
+    baseline.stddev = (rate * (1.0 - rate)).sqrt();
+    baseline.samples = baseline_calls;
+    detection.baselines.push(baseline);
+
+    if calls < MIN_WINDOW_CALLS || (failures as f64) / (calls as f64) <= rate {
+        return Ok(());
+    }
+    let p_value = failure_p_value(failures, calls, rate);
+    if p_value >= ANOMALY_P_VALUE {
+        return Ok(());
+    }
+
+    let outcomes = get_transaction_outcome_counts(
+        pool,
+        &package.network,
+        &package.package_hash,
+        &package.user_id,
+        Some(window.start),
+        Some(window.end),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let names = ErrorCodeNames::load(
+        pool,
+        &package.package_hash,
+        &package.user_id,
+        &outcomes
+            .iter()
+            .filter_map(|o| o.contract_version)
+            .collect::<Vec<_>>(),
+    )
+    .await?;
+    let entry_points = get_failure_breakdown(&outcomes, &names, |outcome| {
+        outcome.entry_point_name.clone()
+    });
+
+    let observed = failures as f64 / calls as f64;
+    let upgrade = recent_upgrade(versions, window);
+    let mut message = format!(
+        "{:.0}% of {} calls failed in the hour to {} against {:.1}% usually",
+        observed * 100.0,
+        calls,
+        window.end,
+        rate * 100.0
+    );
+    if let Some(version) = upgrade {
+        message.push_str(&format!(
+            ", after the upgrade to version {}",
+            version.contract_version
+        ));
+    }
+    detection.anomalies.push(window.anomaly(
+        AnomalyMetric::FailureRate,
+        observed,
+        rate,
+        Some(p_value),
+        message,
+        json!({
+            "calls": calls,
+            "failures": failures,
+            "baseline_calls": baseline_calls,
+            "baseline_failures": baseline_failures,
+            "upgrade": upgrade.map(|v| json!({
+                "contract_version": v.contract_version,
+                "age": v.age,
+            })),
+            "entry_points": entry_points,
+        }),
+    ));
+    Ok(())
+}
+
+async fn check_cost(
+    pool: &PgPool,
+    window: &Window<'_>,
+    baseline_from: DateTime<Utc>,
+    detection: &mut AnomalyDetection,
+) -> Result<(), String> {
+    let package = window.package;
+    let baseline_costs = get_cost_samples(
+        pool,
+        &package.network,
+        &package.package_hash,
+        baseline_from,
+        window.start,
+        MAX_COST_SAMPLES,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let window_costs = get_cost_samples(
+        pool,
+        &package.network,
+        &package.package_hash,
+        window.start,
+        window.end,
+        MAX_COST_SAMPLES,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    detection
+        .baselines
+        .push(window.baseline(AnomalyMetric::Cost, &baseline_costs));
+
+    if baseline_costs.len() < MIN_COST_SAMPLES || window_costs.len() < MIN_COST_SAMPLES {
+        return Ok(());
+    }
+    let (baseline_median, window_median) = (median(&baseline_costs), median(&window_costs));
+    if baseline_median <= 0.0 || window_median / baseline_median - 1.0 < MIN_COST_INCREASE {
+        return Ok(());
+    }
+    let p_value = mann_whitney_greater(&baseline_costs, &window_costs);
+    if p_value < ANOMALY_P_VALUE {
+        detection.anomalies.push(window.anomaly(
+            AnomalyMetric::Cost,
+            window_median,
+            baseline_median,
+            Some(p_value),
+            format!(
+                "Successful calls cost {:.0}% more in the hour to {} than usually",
+                (window_median / baseline_median - 1.0) * 100.0,
+                window.end
+            ),
+            json!({
+                "samples": window_costs.len(),
+                "baseline_samples": baseline_costs.len(),
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Flag callers unknown before the window calling an admin entry point, i.e. one restricted to
+/// groups or only ever called by the owners
+async fn check_admin_calls(
+    pool: &PgPool,
+    window: &Window<'_>,
+    versions: &[ContractVersionSchema],
+    detection: &mut AnomalyDetection,
+) -> Result<(), String> {
+    let package = window.package;
+    let owners = get_package_owners(pool, package).await?;
+    let calls = get_unknown_caller_calls(
+        pool,
+        &package.network,
+        &package.package_hash,
+        &package.user_id,
+        &owners,
+        window.start,
+        window.end,
+        MIN_ADMIN_CALLS,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for call in calls {
+        let restricted = call
+            .contract_version
+            .and_then(|version| versions.iter().find(|v| v.contract_version == version))
+            .and_then(|v| {
+                v.entry_points
+                    .iter()
+                    .find(|ep| ep.name() == call.entry_point_name)
+            })
+            .is_some_and(|ep| !matches!(ep.access(), EntryPointAccess::Public));
+        if !(restricted || call.owner_used && !call.others_used) {
+            continue;
+        }
+        let mut anomaly = window.anomaly(
+            AnomalyMetric::AdminCalls,
+            call.calls as f64,
+            0.0,
+            None,
+            format!(
+                "Unknown caller {} called admin entry point {} {} times in the hour to {}",
+                call.caller_public_key, call.entry_point_name, call.calls, window.end
+            ),
+            json!({
+                "caller_public_key": call.caller_public_key,
+                "entry_point_name": call.entry_point_name,
+                "contract_version": call.contract_version,
+                "calls": call.calls,
+                "failures": call.failures,
+                "restricted": restricted,
+            }),
+        );
+        anomaly.dedup_key = format!(
+            "{}:{}:{}",
+            anomaly.dedup_key, call.caller_public_key, call.entry_point_name
+        );
+        detection.anomalies.push(anomaly);
+    }
+    Ok(())
+}
+
+/// Compute the baselines of a package over the two weeks before the hour ending at `window_end`
+/// and check the hour against them.
+///
+/// Hours before the first indexed call are left out of the baselines, so newly tracked packages
+/// are only checked once they have a day of history.
+pub async fn detect_anomalies(
+    pool: &PgPool,
+    package: &ContractPackageSchema,
+    window_end: DateTime<Utc>,
+) -> Result<AnomalyDetection, String> {
+    let window = Window {
+        package,
+        start: window_end - Duration::hours(1),
+        end: window_end,
+    };
+    let baseline_from = window.start - Duration::hours(BASELINE_HOURS);
+
+    let series = get_call_series(
+        pool,
+        &package.network,
+        &package.package_hash,
+        AnalyticsBucket::Hour,
+        None,
+        baseline_from,
+        window.end,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some((current, history)) = series.split_last() else {
+        return Ok(AnomalyDetection::default());
+    };
+    let Some(first_active) = history.iter().position(|b| b.calls > 0) else {
+        return Ok(AnomalyDetection::default());
+    };
+    let history = &history[first_active..];
+    let active_from = history[0].bucket;
+
+    let new_callers = get_new_caller_series(
+        pool,
+        &package.network,
+        &package.package_hash,
+        active_from,
+        window.end,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let versions = get_contract_versions(pool, &package.package_hash, &package.user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut detection = AnomalyDetection::default();
+    let call_counts: Vec<f64> = history.iter().map(|b| b.calls as f64).collect();
+    check_count(
+        &window,
+        AnomalyMetric::CallRate,
+        "calls",
+        &call_counts,
+        current.calls,
+        &mut detection,
+    );
+    if let Some(((_, current_new_callers), history_new_callers)) = new_callers.split_last() {
+        let new_caller_counts: Vec<f64> = history_new_callers
+            .iter()
+            .map(|(_, count)| *count as f64)
+            .collect();
+        check_count(
+            &window,
+            AnomalyMetric::NewCallerRate,
+            "new callers",
+            &new_caller_counts,
+            *current_new_callers,
+            &mut detection,
+        );
+    }
+    check_failure_rate(
+        pool,
+        &window,
+        &versions,
+        (
+            history.iter().map(|b| b.calls).sum(),
+            history.iter().map(|b| b.failures).sum(),
+        ),
+        (current.calls, current.failures),
+        &mut detection,
+    )
+    .await?;
+    check_cost(pool, &window, active_from, &mut detection).await?;
+    check_admin_calls(pool, &window, &versions, &mut detection).await?;
+
+    Ok(detection)
+}
+
+pub fn get_anomaly_alert(anomaly: &TransactionAnomalySchema) -> AlertSchema {
+    AlertSchema::new(
+        anomaly.user_id,
+        anomaly.contract_package_hash.clone(),
+        TRANSACTION_ANOMALY_ALERT,
+        anomaly.message.clone(),
+        json!({
+            "anomaly_id": anomaly.id,
+            "metric": anomaly.metric,
+            "window_start": anomaly.window_start,
+            "window_end": anomaly.window_end,
+            "observed": anomaly.observed,
+            "expected": anomaly.expected,
+            "p_value": anomaly.p_value,
+            "evidence": anomaly.evidence,
+        }),
+        anomaly.dedup_key.clone(),
+    )
+}
@@ -0,0 +1,367 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde_json::{Value, json};
+
+use crate::{
+    models::{api::transaction::Transaction, schema::transaction::TransactionTarget},
+    utils::strip_hash_prefix,
+};
+
+/// Status cspr.live reports for executed deploys, failures carry an error message
+const PROCESSED_STATUS: &str = "processed";
+
+/// Block read from a node with the identifiers of the transactions it contains
+pub struct NodeBlock {
+    pub height: u64,
+    pub hash: String,
+    pub timestamp: DateTime<Utc>,
+    /// `{"Deploy": hash}` or `{"Version1": hash}` identifiers, as `info_get_transaction` expects them
+    pub transaction_hashes: Vec<Value>,
+}
+
+/// Executed transaction read from a node
+pub struct NodeTransaction {
+    pub transaction: Transaction,
+    pub timestamp: DateTime<Utc>,
+    /// Stored contract or package the transaction called, unset for calls by name and session code
+    pub target: Option<TransactionTarget>,
+    /// Contract packages written by the transaction, i.e. installed or upgraded
+    pub written_packages: Vec<String>,
+    /// Contracts written by the transaction, i.e. the versions it added
+    pub written_contracts: Vec<String>,
+    /// Keys read or written by the execution, the packages and contracts it called through other
+    /// contracts or session code included
+    pub touched_keys: Vec<String>,
+}
+
+/// Call a node JSON-RPC method.
+///
+/// Raw JSON keeps deploys and version 1 transactions, and execution results of both protocol
+/// versions, in one shape to extract from.
+async fn node_rpc(node_address: &str, method: &str, params: Value) -> Result<Value, String> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": rand::rng().random::<i64>(),
+        "method": method,
+        "params": params,
+    });
+    let response: Value = reqwest::Client::new()
+        .post(format!("{}/rpc", node_address.trim_end_matches('/')))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("{} request failed: {}", method, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid {} response: {}", method, e))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(format!("{} failed: {}", method, error));
+    }
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| format!("{} returned no result", method))
+}
+
+/// Single key and value of an externally tagged enum
+fn variant(value: &Value) -> Option<(&str, &Value)> {
+    value
+        .as_object()
+        .filter(|o| o.len() == 1)
+        .and_then(|o| o.iter().next())
+        .map(|(k, v)| (k.as_str(), v))
+}
+
+fn parse_timestamp(value: &Value) -> Result<DateTime<Utc>, String> {
+    let timestamp = value.as_str().ok_or("Missing timestamp")?;
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid timestamp {}: {}", timestamp, e))
+}
+
+/// Height of the latest block the node has
+pub async fn get_latest_block_height(node_address: &str) -> Result<u64, String> {
+    let result = node_rpc(node_address, "chain_get_block", json!({})).await?;
+    let (_, block) = variant(&result["block_with_signatures"]["block"]).ok_or("No latest block")?;
+    block["header"]["height"]
+        .as_u64()
+        .ok_or_else(|| "Latest block has no height".to_string())
+}
+
+pub async fn get_node_block(node_address: &str, height: u64) -> Result<NodeBlock, String> {
+    let result = node_rpc(
+        node_address,
+        "chain_get_block",
+        json!({ "block_identifier": { "Height": height } }),
+    )
+    .await?;
+    let (version, block) = variant(&result["block_with_signatures"]["block"])
+        .ok_or_else(|| format!("Node has no block at height {}", height))?;
+
+    let transaction_hashes = match version {
+        // Transactions are grouped by lane, each entry tagged with its transaction kind
+        "Version2" => block["body"]["transactions"]
+            .as_object()
+            .into_iter()
+            .flat_map(|lanes| lanes.values())
+            .filter_map(|lane| lane.as_array())
+            .flatten()
+            .cloned()
+            .collect(),
+        _ => ["deploy_hashes", "transfer_hashes"]
+            .iter()
+            .filter_map(|field| block["body"][field].as_array())
+            .flatten()
+            .map(|hash| json!({ "Deploy": hash }))
+            .collect(),
+    };
+
+    Ok(NodeBlock {
+        height,
+        hash: block["hash"].as_str().unwrap_or_default().to_string(),
+        timestamp: parse_timestamp(&block["header"]["timestamp"])?,
+        transaction_hashes,
+    })
+}
+
+fn named_arg<'a>(args: &'a Value, name: &str) -> Option<&'a Value> {
+    args.as_array()?
+        .iter()
+        .find(|arg| arg[0].as_str() == Some(name))
+        .map(|arg| &arg[1])
+}
+
+fn cl_value_text(value: &Value) -> Option<String> {
+    match &value["parsed"] {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Target, entry point, args and payment of a deploy
+fn parse_deploy_call(
+    deploy: &Value,
+) -> (
+    Option<TransactionTarget>,
+    Option<String>,
+    Option<Value>,
+    String,
+) {
+    let payment = variant(&deploy["payment"])
+        .and_then(|(_, p)| named_arg(&p["args"], "amount"))
+        .and_then(cl_value_text)
+        .unwrap_or_default();
+    let Some((kind, session)) = variant(&deploy["session"]) else {
+        return (None, None, None, payment);
+    };
+    let target = match kind {
+        "StoredContractByHash" => session["hash"]
+            .as_str()
+            .map(|h| TransactionTarget::Contract(strip_hash_prefix(h))),
+        "StoredVersionedContractByHash" => session["hash"]
+            .as_str()
+            .map(|h| TransactionTarget::ContractPackage(strip_hash_prefix(h))),
+        _ => None,
+    };
+    let entry_point = session["entry_point"].as_str().map(str::to_string);
+    (target, entry_point, Some(session["args"].clone()), payment)
+}
+
+/// Target, entry point, args and payment of a version 1 transaction
+fn parse_v1_call(
+    payload: &Value,
+) -> (
+    Option<TransactionTarget>,
+    Option<String>,
+    Option<Value>,
+    String,
+) {
+    let fields = &payload["fields"];
+    let payment = variant(&payload["pricing_mode"])
+        .and_then(|(_, mode)| match &mode["payment_amount"] {
+            Value::Number(n) => Some(n.to_string()),
+            Value::String(s) => Some(s.clone()),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let target = variant(&fields["target"]["Stored"]["id"]).and_then(|(kind, id)| match kind {
+        "ByHash" => id
+            .as_str()
+            .map(|h| TransactionTarget::Contract(strip_hash_prefix(h))),
+        "ByPackageHash" => id["addr"]
+            .as_str()
+            .map(|h| TransactionTarget::ContractPackage(strip_hash_prefix(h))),
+        _ => None,
+    });
+    let entry_point = match &fields["entry_point"] {
+        Value::String(name) => Some(name.clone()),
+        other => other["Custom"].as_str().map(str::to_string),
+    };
+    let args = match variant(&fields["args"]) {
+        Some(("Named", named)) => Some(named.clone()),
+        _ => None,
+    };
+    (target, entry_point, args, payment)
+}
+
+/// Parts of an execution result the indexer keeps
+struct ExecutionOutcome {
+    cost: String,
+    error_message: Option<String>,
+    /// Kind and key of every write
+    writes: Vec<(String, String)>,
+    /// Every key read or written
+    touched: Vec<String>,
+}
+
+/// Parse an execution result of either protocol version
+fn parse_execution_result(result: &Value) -> ExecutionOutcome {
+    let mut writes = vec![];
+    let mut touched = vec![];
+    let Some((version, result)) = variant(result) else {
+        return ExecutionOutcome {
+            cost: String::new(),
+            error_message: None,
+            writes,
+            touched,
+        };
+    };
+
+    let (result, effects) = match version {
+        "Version2" => (result, &result["effects"]),
+        _ => {
+            let (_, outcome) = variant(result).unwrap_or(("", result));
+            (outcome, &outcome["effect"]["transforms"])
+        }
+    };
+    for effect in effects.as_array().into_iter().flatten() {
+        let Some(key) = effect["key"].as_str() else {
+            continue;
+        };
+        // Reads are kept as `Identity` effects, so a contract called through another one shows up
+        // even when the call wrote nothing to it
+        let key = strip_hash_prefix(key);
+        if !touched.contains(&key) {
+            touched.push(key.clone());
+        }
+        // `{"Write": {"ContractPackage": ..}}` in 2.0 effects, `{"WriteContractPackage": ..}` or a
+        // bare string in 1.x transforms
+        let kind = match variant(&effect["kind"]) {
+            Some(("Write", written)) => variant(written).map(|(k, _)| k.to_string()),
+            _ => match &effect["transform"] {
+                Value::String(s) => s.strip_prefix("Write").map(str::to_string),
+                other => {
+                    variant(other).and_then(|(k, _)| k.strip_prefix("Write").map(str::to_string))
+                }
+            },
+        };
+        if let Some(kind) = kind {
+            writes.push((kind, key));
+        }
+    }
+
+    let cost = match &result["cost"] {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    };
+    let error_message = result["error_message"].as_str().map(str::to_string);
+    ExecutionOutcome {
+        cost,
+        error_message,
+        writes,
+        touched,
+    }
+}
+
+/// Read a transaction and its execution result from a node, `None` while it is not executed
+pub async fn get_node_transaction(
+    node_address: &str,
+    transaction_hash: &Value,
+) -> Result<Option<NodeTransaction>, String> {
+    let result = node_rpc(
+        node_address,
+        "info_get_transaction",
+        json!({ "transaction_hash": transaction_hash, "finalized_approvals": false }),
+    )
+    .await?;
+
+    let execution_info = &result["execution_info"];
+    if execution_info["execution_result"].is_null() {
+        return Ok(None);
+    }
+    let (kind, transaction) =
+        variant(&result["transaction"]).ok_or("Node returned no transaction")?;
+
+    let (deploy_hash, caller, timestamp, (target, entry_point, args, payment)) = match kind {
+        "Deploy" => (
+            &transaction["hash"],
+            transaction["header"]["account"].clone(),
+            parse_timestamp(&transaction["header"]["timestamp"])?,
+            parse_deploy_call(transaction),
+        ),
+        _ => {
+            let payload = &transaction["payload"];
+            let initiator = variant(&payload["initiator_addr"])
+                .map(|(_, addr)| addr.clone())
+                .unwrap_or_default();
+            (
+                &transaction["hash"],
+                initiator,
+                parse_timestamp(&payload["timestamp"])?,
+                parse_v1_call(payload),
+            )
+        }
+    };
+    let ExecutionOutcome {
+        cost,
+        error_message,
+        writes,
+        touched: touched_keys,
+    } = parse_execution_result(&execution_info["execution_result"]);
+
+    let written = |kinds: &[&str]| -> Vec<String> {
+        writes
+            .iter()
+            .filter(|(kind, _)| kinds.contains(&kind.as_str()))
+            .map(|(_, key)| key.clone())
+            .collect()
+    };
+    let contract_hash = match &target {
+        Some(TransactionTarget::Contract(hash)) => Some(hash.clone()),
+        _ => None,
+    };
+    let contract_package_hash = match &target {
+        Some(TransactionTarget::ContractPackage(hash)) => Some(hash.clone()),
+        _ => None,
+    };
+
+    Ok(Some(NodeTransaction {
+        transaction: Transaction {
+            deploy_hash: deploy_hash.as_str().unwrap_or_default().to_string(),
+            block_hash: execution_info["block_hash"].as_str().map(str::to_string),
+            block_height: execution_info["block_height"].as_u64(),
+            caller_public_key: caller.as_str().unwrap_or_default().to_string(),
+            execution_type_id: None,
+            contract_package_hash,
+            contract_hash,
+            entry_point_id: None,
+            entry_point_name: entry_point,
+            args,
+            payment_amount: payment,
+            cost,
+            status: PROCESSED_STATUS.to_string(),
+            timestamp: timestamp.to_rfc3339(),
+            error_message,
+            call: None,
+            failure: None,
+        },
+        timestamp,
+        target,
+        written_packages: written(&["ContractPackage", "Package"]),
+        written_contracts: written(&["Contract", "AddressableEntity"]),
+        touched_keys,
+    }))
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use casper_types::{CLType, CLValue, Parameter};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::{
+    models::{
+        api::transaction::{DecodedArg, DecodedCall, Transaction},
+        schema::contract::ContractVersionSchema,
+    },
+    services::contract::transaction::fetch_contract_entry_points,
+    utils::strip_hash_prefix,
+};
+
+/// Entry point names of contracts fetched from cspr.live, keyed by contract hash and entry point id
+#[derive(Default)]
+pub struct EntryPointNames(HashMap<String, HashMap<u64, String>>);
+
+impl EntryPointNames {
+    /// Name of the entry point a transaction called, fetching the entry points of its contract once
+    pub async fn resolve(&mut self, network: &str, transaction: &Transaction) -> Option<String> {
+        if let Some(name) = &transaction.entry_point_name {
+            return Some(name.clone());
+        }
+        let id = transaction.entry_point_id?;
+        let contract_hash = strip_hash_prefix(transaction.contract_hash.as_deref()?);
+        if !self.0.contains_key(&contract_hash) {
+            let names = match fetch_contract_entry_points(network, &contract_hash).await {
+                Ok(entry_points) => entry_points
+                    .into_iter()
+                    .map(|ep| (ep.id, ep.name))
+                    .collect(),
+                Err(e) => {
+                    log::warn!("Failed to resolve entry points of {}: {}", contract_hash, e);
+                    HashMap::new()
+                }
+            };
+            self.0.insert(contract_hash.clone(), names);
+        }
+        self.0.get(&contract_hash)?.get(&id).cloned()
+    }
+}
+
+/// Version a transaction called: the one with its contract hash, otherwise the latest version
+/// installed before it for calls through the package
+pub fn find_called_version<'a>(
+    versions: &'a [ContractVersionSchema],
+    transaction: &Transaction,
+) -> Option<&'a ContractVersionSchema> {
+    if let Some(contract_hash) = &transaction.contract_hash {
+        let contract_hash = strip_hash_prefix(contract_hash);
+        return versions
+            .iter()
+            .find(|v| strip_hash_prefix(&v.contract_hash) == contract_hash);
+    }
+    let timestamp = DateTime::parse_from_rfc3339(&transaction.timestamp)
+        .ok()?
+        .with_timezone(&Utc);
+    versions
+        .iter()
+        .filter(|v| v.age <= timestamp)
+        .max_by_key(|v| v.contract_version)
+}
+
+/// Named arguments of a transaction, cspr.live lists them as an object and nodes as name and value pairs
+fn named_args(args: &Value) -> Vec<(String, &Value)> {
+    match args {
+        Value::Object(args) => args.iter().map(|(name, v)| (name.clone(), v)).collect(),
+        Value::Array(args) => args
+            .iter()
+            .filter_map(|arg| Some((arg[0].as_str()?.to_string(), &arg[1])))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Decode an argument with the declared type of its parameter.
+///
+/// Serialized bytes are decoded with the declared type when the caller sent them, otherwise the
+/// value parsed by the caller's type is kept and only checked against the declaration.
+fn decode_arg(name: String, raw: &Value, parameter: Option<&Parameter>) -> DecodedArg {
+    let passed_type: Option<CLType> = serde_json::from_value(raw["cl_type"].clone()).ok();
+    let declared_type = parameter.map(|p| p.cl_type().clone());
+
+    let decoded = declared_type.as_ref().and_then(|ty| {
+        let bytes = decode_hex(raw["bytes"].as_str()?)?;
+        let value = serde_json::to_value(CLValue::from_components(ty.clone(), bytes)).ok()?;
+        // `parsed` is null when the bytes do not decode as the declared type
+        value
+            .get("parsed")
+            .filter(|parsed| !parsed.is_null())
+            .cloned()
+    });
+    let matches_signature =
+        declared_type.is_some() && (decoded.is_some() || passed_type == declared_type);
+
+    DecodedArg {
+        name,
+        cl_type: declared_type.or(passed_type),
+        value: decoded.unwrap_or_else(|| raw["parsed"].clone()),
+        matches_signature,
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Decode the arguments of a transaction against the entry point of the version it called
+pub fn decode_call(
+    versions: &[ContractVersionSchema],
+    transaction: &Transaction,
+    entry_point_name: &str,
+) -> Option<DecodedCall> {
+    let version = find_called_version(versions, transaction)?;
+    let entry_point = version
+        .entry_points
+        .iter()
+        .find(|ep| ep.name() == entry_point_name)?;
+
+    let raw_args = transaction
+        .args
+        .as_ref()
+        .map(named_args)
+        .unwrap_or_default();
+    let args: Vec<DecodedArg> = raw_args
+        .into_iter()
+        .map(|(name, raw)| {
+            let parameter = entry_point.args().iter().find(|p| p.name() == name);
+            decode_arg(name, raw, parameter)
+        })
+        .collect();
+    let missing_args = entry_point
+        .args()
+        .iter()
+        .filter(|p| !args.iter().any(|a| a.name == p.name()))
+        .map(|p| p.name().to_string())
+        .collect();
+
+    let display = format!(
+        "{}({})",
+        entry_point_name,
+        args.iter()
+            .map(|a| format!("{}={}", a.name, display_value(&a.value)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    Some(DecodedCall {
+        contract_version: version.contract_version,
+        entry_point_name: entry_point_name.to_string(),
+        args,
+        missing_args,
+        display,
+    })
+}
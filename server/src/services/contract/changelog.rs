@@ -0,0 +1,233 @@
+use crate::{
+    models::schema::{
+        contract::{
+            ContractChangelog, ContractEntryPointDiff, ContractNamedKeysDiff, ContractVersionSchema,
+        },
+        rules::DiffRuleSchema,
+    },
+    services::contract::diff::get_contract_version_diff,
+};
+
+/// Names that were added by one step, removed by a later one and exist at neither end of the range
+fn get_transient_names(
+    added: &[(usize, String)],
+    removed: &[(usize, String)],
+    exists_at_ends: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let mut transient: Vec<String> = added
+        .iter()
+        .filter(|(added_at, name)| {
+            !exists_at_ends(name)
+                && removed
+                    .iter()
+                    .any(|(removed_at, removed_name)| removed_at > added_at && removed_name == name)
+        })
+        .map(|(_, name)| name.clone())
+        .collect();
+    transient.sort();
+    transient.dedup();
+    transient
+}
+
+/// Build the changelog of a contract package between two versions.
+///
+/// `versions` are all known versions of the package. Every version within `from..=to` is stepped
+/// through, disabled ones included, and the net diff compares the range ends directly so entries
+/// that only existed in between do not show up in it. `rules` apply to every step and the net diff.
+pub async fn get_contract_changelog(
+    versions: &[ContractVersionSchema],
+    from: u32,
+    to: u32,
+    rules: &[DiffRuleSchema],
+) -> Result<ContractChangelog, String> {
+    if from >= to {
+        return Err(format!(
+            "Version range start {} must be lower than its end {}",
+            from, to
+        ));
+    }
+
+    let mut range: Vec<&ContractVersionSchema> = versions
+        .iter()
+        .filter(|v| v.contract_version >= from && v.contract_version <= to)
+        .collect();
+    range.sort_by_key(|v| v.contract_version);
+
+    let first = range
+        .first()
+        .filter(|v| v.contract_version == from)
+        .ok_or_else(|| format!("Contract version {} not found", from))?;
+    let last = range
+        .last()
+        .filter(|v| v.contract_version == to)
+        .ok_or_else(|| format!("Contract version {} not found", to))?;
+
+    let mut steps = vec![];
+    let mut added_entry_points = vec![];
+    let mut removed_entry_points = vec![];
+    let mut added_named_keys = vec![];
+    let mut removed_named_keys = vec![];
+    for (step, pair) in range.windows(2).enumerate() {
+        let diff = get_contract_version_diff(pair[0].clone(), pair[1].clone(), rules).await?;
+        for entry_point in &diff.entry_points {
+            match entry_point {
+                ContractEntryPointDiff::Added(ep) => {
+                    added_entry_points.push((step, ep.name().to_string()))
+                }
+                ContractEntryPointDiff::Removed(ep) => {
+                    removed_entry_points.push((step, ep.name().to_string()))
+                }
+                ContractEntryPointDiff::Modified { .. } => {}
+            }
+        }
+        for named_key in &diff.named_keys {
+            match named_key {
+                ContractNamedKeysDiff::Added { key, .. } => {
+                    added_named_keys.push((step, key.clone()))
+                }
+                ContractNamedKeysDiff::Removed { key, .. } => {
+                    removed_named_keys.push((step, key.clone()))
+                }
+                ContractNamedKeysDiff::Modified { .. } => {}
+            }
+        }
+        steps.push(diff);
+    }
+
+    let net = get_contract_version_diff((*first).clone(), (*last).clone(), rules).await?;
+
+    Ok(ContractChangelog {
+        contract_package_hash: first.contract_package_hash.clone(),
+        from_version: from,
+        to_version: to,
+        versions: range.iter().map(|v| v.contract_version).collect(),
+        steps,
+        net,
+        transient_entry_points: get_transient_names(
+            &added_entry_points,
+            &removed_entry_points,
+            |name| {
+                [first, last]
+                    .iter()
+                    .any(|v| v.entry_points.iter().any(|ep| ep.name() == name))
+            },
+        ),
+        transient_named_keys: get_transient_names(&added_named_keys, &removed_named_keys, |name| {
+            [first, last].iter().any(|v| v.named_keys.contains(name))
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{
+        CLType, EntryPointAccess, EntryPointType, Key, NamedKeys, contracts::EntryPoint,
+    };
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn version(
+        contract_version: u32,
+        entry_points: &[&str],
+        named_keys: &[&str],
+    ) -> ContractVersionSchema {
+        let mut keys = NamedKeys::new();
+        for name in named_keys {
+            keys.insert(name.to_string(), Key::Hash([1; 32]));
+        }
+        ContractVersionSchema {
+            protocol_major_version: 2,
+            contract_version,
+            contract_package_hash: "package".to_string(),
+            contract_hash: format!("contract-{}", contract_version),
+            contract_wasm_hash: format!("wasm-{}", contract_version),
+            user_id: Uuid::nil(),
+            protocol_version: "2.0.0".to_string(),
+            entry_points: entry_points
+                .iter()
+                .map(|name| {
+                    EntryPoint::new(
+                        *name,
+                        vec![],
+                        CLType::Unit,
+                        EntryPointAccess::Public,
+                        EntryPointType::Called,
+                    )
+                })
+                .collect(),
+            named_keys: keys,
+            disabled: false,
+            age: Utc::now(),
+            deploy_hash: None,
+            block_height: None,
+            deployer_public_key: None,
+            upgrade_cost: None,
+        }
+    }
+
+    fn versions() -> Vec<ContractVersionSchema> {
+        vec![
+            version(3, &["transfer", "burn"], &["balances"]),
+            version(1, &["transfer", "pause"], &["balances"]),
+            version(2, &["transfer", "mint", "burn"], &["balances", "tmp"]),
+        ]
+    }
+
+    #[tokio::test]
+    async fn steps_through_every_version_of_the_range() {
+        let changelog = get_contract_changelog(&versions(), 1, 3, &[])
+            .await
+            .unwrap();
+        assert_eq!(changelog.versions, vec![1, 2, 3]);
+        assert_eq!(changelog.steps.len(), 2);
+        assert_eq!(changelog.transient_entry_points, vec!["mint".to_string()]);
+        assert_eq!(changelog.transient_named_keys, vec!["tmp".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn net_diff_leaves_out_transient_entries() {
+        let changelog = get_contract_changelog(&versions(), 1, 3, &[])
+            .await
+            .unwrap();
+        let mut names: Vec<String> = changelog
+            .net
+            .entry_points
+            .iter()
+            .map(|d| match d {
+                ContractEntryPointDiff::Added(ep) => format!("+{}", ep.name()),
+                ContractEntryPointDiff::Removed(ep) => format!("-{}", ep.name()),
+                ContractEntryPointDiff::Modified { to, .. } => format!("~{}", to.name()),
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["+burn".to_string(), "-pause".to_string()]);
+        assert!(changelog.net.named_keys.is_empty());
+    }
+
+    #[test]
+    fn transient_names_are_removed_after_they_were_added() {
+        let added = vec![(1, "a".to_string()), (0, "b".to_string())];
+        let removed = vec![(0, "a".to_string()), (1, "b".to_string())];
+        assert_eq!(
+            get_transient_names(&added, &removed, |_| false),
+            vec!["b".to_string()]
+        );
+        assert!(get_transient_names(&added, &removed, |name| name == "b").is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_and_unknown_ranges() {
+        assert!(
+            get_contract_changelog(&versions(), 2, 2, &[])
+                .await
+                .is_err()
+        );
+        assert!(
+            get_contract_changelog(&versions(), 1, 4, &[])
+                .await
+                .is_err()
+        );
+    }
+}
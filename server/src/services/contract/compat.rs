@@ -0,0 +1,422 @@
+use casper_types::{CLType, EntryPointAccess};
+
+use crate::models::schema::contract::{
+    ContractChangeClassification, ContractChangeCompatibility, ContractEntryPointChange,
+    ContractEntryPointDiff, ContractErrorDiff, ContractEventDiff, ContractNamedKeysDiff,
+    ContractUpgradeAssessment, ContractVersionDiff, SemverBump,
+};
+
+/// Position of an integer type in its widening chain, types in different chains are not comparable
+fn integer_rank(cl_type: &CLType) -> Option<(u8, u8)> {
+    match cl_type {
+        CLType::U8 => Some((0, 0)),
+        CLType::U32 => Some((0, 1)),
+        CLType::U64 => Some((0, 2)),
+        CLType::U128 => Some((0, 3)),
+        CLType::U256 => Some((0, 4)),
+        CLType::U512 => Some((0, 5)),
+        CLType::I32 => Some((1, 0)),
+        CLType::I64 => Some((1, 1)),
+        _ => None,
+    }
+}
+
+/// Whether every value of `from` is representable in `to`
+fn is_widening(from: &CLType, to: &CLType) -> bool {
+    if let CLType::Option(inner) = to
+        && (inner.as_ref() == from || is_widening(from, inner))
+    {
+        return true;
+    }
+    match (integer_rank(from), integer_rank(to)) {
+        (Some((from_chain, from_rank)), Some((to_chain, to_rank))) => {
+            from_chain == to_chain && from_rank < to_rank
+        }
+        _ => false,
+    }
+}
+
+fn classify_entry_point_change(
+    change: &ContractEntryPointChange,
+) -> (String, ContractChangeCompatibility, String) {
+    use ContractChangeCompatibility::*;
+
+    match change {
+        ContractEntryPointChange::ParameterAdded { name, cl_type } => match cl_type {
+            CLType::Option(_) => (
+                format!("Parameter {} added", name),
+                PotentiallyBreaking,
+                "Callers built against the old signature do not pass the new argument".to_string(),
+            ),
+            _ => (
+                format!("Parameter {} added", name),
+                Breaking,
+                "New required argument is missing from existing calls".to_string(),
+            ),
+        },
+        ContractEntryPointChange::ParameterRemoved { name, .. } => (
+            format!("Parameter {} removed", name),
+            PotentiallyBreaking,
+            "Argument is ignored, callers relying on its effect get different behavior".to_string(),
+        ),
+        ContractEntryPointChange::ParameterRenamed { from, to, .. } => (
+            format!("Parameter {} renamed to {}", from, to),
+            Breaking,
+            "Arguments are passed by name, existing calls miss the renamed argument".to_string(),
+        ),
+        ContractEntryPointChange::ParameterTypeChanged { name, from, to } => {
+            let reason = if is_widening(to, from) {
+                "Argument type was narrowed, existing values may no longer fit"
+            } else {
+                "Argument type changed, existing calls fail to decode it"
+            };
+            (
+                format!("Parameter {} type changed", name),
+                Breaking,
+                reason.to_string(),
+            )
+        }
+        ContractEntryPointChange::ReturnTypeChanged { from, to } => {
+            if is_widening(from, to) {
+                (
+                    "Return type widened".to_string(),
+                    PotentiallyBreaking,
+                    "Callers decoding the old return type have to handle the wider type"
+                        .to_string(),
+                )
+            } else {
+                (
+                    "Return type changed".to_string(),
+                    Breaking,
+                    "Callers decoding the old return type fail".to_string(),
+                )
+            }
+        }
+        ContractEntryPointChange::AccessChanged { from, to } => match (from, to) {
+            (_, EntryPointAccess::Public) => (
+                "Access opened to public".to_string(),
+                Compatible,
+                "All existing callers keep access".to_string(),
+            ),
+            (EntryPointAccess::Public, _) => (
+                "Access restricted".to_string(),
+                Breaking,
+                "Callers outside the allowed groups lose access".to_string(),
+            ),
+            _ => (
+                "Access groups changed".to_string(),
+                PotentiallyBreaking,
+                "Callers may not hold the newly required group".to_string(),
+            ),
+        },
+        ContractEntryPointChange::EntryPointTypeChanged { from, to } => (
+            format!("Entry point type changed from {:?} to {:?}", from, to),
+            Breaking,
+            "Entry point now runs in a different context".to_string(),
+        ),
+    }
+}
+
+/// Classify every interface change of a version diff and derive the overall upgrade severity.
+///
+/// Logic changes behind an unchanged interface are compatible and only warrant a patch bump.
+pub fn assess_upgrade(diff: &ContractVersionDiff) -> ContractUpgradeAssessment {
+    use ContractChangeCompatibility::*;
+
+    let mut changes = vec![];
+    let mut push = |subject: &str,
+                    change: String,
+                    compatibility: ContractChangeCompatibility,
+                    reason: String| {
+        changes.push(ContractChangeClassification {
+            subject: subject.to_string(),
+            change,
+            compatibility,
+            reason,
+        })
+    };
+    let mut has_additions = false;
+
+    for entry_point in &diff.entry_points {
+        match entry_point {
+            ContractEntryPointDiff::Added(ep) => {
+                has_additions = true;
+                push(
+                    ep.name(),
+                    "Entry point added".to_string(),
+                    Compatible,
+                    "New functionality, existing calls are unaffected".to_string(),
+                );
+            }
+            ContractEntryPointDiff::Removed(ep) => push(
+                ep.name(),
+                "Entry point removed".to_string(),
+                Breaking,
+                "Existing calls to the entry point fail".to_string(),
+            ),
+            ContractEntryPointDiff::Modified {
+                to,
+                changes: entry_point_changes,
+                ..
+            } => {
+                if entry_point_changes.is_empty() {
+                    push(
+                        to.name(),
+                        "Entry point modified".to_string(),
+                        PotentiallyBreaking,
+                        "Entry point changed without a recognized breakdown".to_string(),
+                    );
+                }
+                for change in entry_point_changes {
+                    let (change, compatibility, reason) = classify_entry_point_change(change);
+                    push(to.name(), change, compatibility, reason);
+                }
+            }
+        }
+    }
+
+    for named_key in &diff.named_keys {
+        match named_key {
+            ContractNamedKeysDiff::Added { key, .. } => {
+                has_additions = true;
+                push(
+                    key,
+                    "Named key added".to_string(),
+                    Compatible,
+                    "New state, existing readers are unaffected".to_string(),
+                );
+            }
+            ContractNamedKeysDiff::Removed { key, .. } => push(
+                key,
+                "Named key removed".to_string(),
+                PotentiallyBreaking,
+                "Off-chain readers of the key lose access to its value".to_string(),
+            ),
+            ContractNamedKeysDiff::Modified { key, .. } => push(
+                key,
+                "Named key moved".to_string(),
+                PotentiallyBreaking,
+                "Readers holding the old key read stale state".to_string(),
+            ),
+        }
+    }
+
+    for event in &diff.events {
+        match event {
+            ContractEventDiff::Added { name, .. } => {
+                has_additions = true;
+                push(
+                    name,
+                    "Event added".to_string(),
+                    Compatible,
+                    "New event, existing indexers skip it".to_string(),
+                );
+            }
+            ContractEventDiff::Removed { name, .. } => push(
+                name,
+                "Event removed".to_string(),
+                PotentiallyBreaking,
+                "Indexers waiting for the event no longer receive it".to_string(),
+            ),
+            ContractEventDiff::Modified { name, .. } => push(
+                name,
+                "Event fields changed".to_string(),
+                PotentiallyBreaking,
+                "Indexers decoding the old event layout fail".to_string(),
+            ),
+        }
+    }
+
+    for error in &diff.errors {
+        match error {
+            ContractErrorDiff::Added { name, .. } => {
+                has_additions = true;
+                push(
+                    name,
+                    "Error added".to_string(),
+                    Compatible,
+                    "New failure code, existing codes keep their meaning".to_string(),
+                );
+            }
+            ContractErrorDiff::Removed { name, .. } => push(
+                name,
+                "Error removed".to_string(),
+                PotentiallyBreaking,
+                "Callers handling the error code never see it again".to_string(),
+            ),
+            ContractErrorDiff::Renumbered { name, from, to } => push(
+                name,
+                format!("Error renumbered from {} to {}", from, to),
+                Breaking,
+                "Callers matching on the error code misinterpret failures".to_string(),
+            ),
+            ContractErrorDiff::Renamed {
+                discriminant,
+                from,
+                to,
+            } => push(
+                to,
+                format!("Error {} renamed from {}", discriminant, from),
+                Compatible,
+                "Error code keeps its value".to_string(),
+            ),
+        }
+    }
+
+    let severity = changes
+        .iter()
+        .map(|c| c.compatibility)
+        .max()
+        .unwrap_or(Compatible);
+    let semver_bump = match severity {
+        Breaking => SemverBump::Major,
+        PotentiallyBreaking => SemverBump::Minor,
+        Compatible if has_additions => SemverBump::Minor,
+        Compatible => SemverBump::Patch,
+    };
+
+    ContractUpgradeAssessment {
+        severity,
+        semver_bump,
+        changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{EntryPointType, Parameter, contracts::EntryPoint};
+
+    use super::*;
+
+    fn diff() -> ContractVersionDiff {
+        let meta = serde_json::json!({
+            "contract_hash": "hash-a",
+            "timestamp": "2026-01-01T00:00:00Z",
+            "contract_version": 1,
+            "is_disabled": false,
+            "wasm_hash": "hash-b"
+        });
+        serde_json::from_value(serde_json::json!({
+            "v1": meta,
+            "v2": meta,
+            "contract_package_hash": "hash-c",
+            "entry_points": [],
+            "named_keys": []
+        }))
+        .unwrap()
+    }
+
+    fn entry_point(args: Vec<Parameter>) -> EntryPoint {
+        EntryPoint::new(
+            "transfer",
+            args,
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Called,
+        )
+    }
+
+    fn modified(change: ContractEntryPointChange) -> ContractEntryPointDiff {
+        ContractEntryPointDiff::Modified {
+            from: entry_point(vec![]),
+            to: entry_point(vec![]),
+            changes: vec![change],
+        }
+    }
+
+    #[test]
+    fn widens_integers_within_their_chain() {
+        assert!(is_widening(&CLType::U32, &CLType::U64));
+        assert!(is_widening(&CLType::U64, &CLType::U512));
+        assert!(is_widening(&CLType::I32, &CLType::I64));
+        assert!(!is_widening(&CLType::U64, &CLType::U32));
+        assert!(!is_widening(&CLType::U32, &CLType::I64));
+        assert!(!is_widening(&CLType::U64, &CLType::U64));
+    }
+
+    #[test]
+    fn widens_into_options() {
+        assert!(is_widening(
+            &CLType::Key,
+            &CLType::Option(Box::new(CLType::Key))
+        ));
+        assert!(is_widening(
+            &CLType::U32,
+            &CLType::Option(Box::new(CLType::U64))
+        ));
+        assert!(!is_widening(
+            &CLType::Option(Box::new(CLType::Key)),
+            &CLType::Key
+        ));
+    }
+
+    #[test]
+    fn logic_only_changes_are_a_patch() {
+        let assessment = assess_upgrade(&diff());
+        assert_eq!(assessment.severity, ContractChangeCompatibility::Compatible);
+        assert_eq!(assessment.semver_bump, SemverBump::Patch);
+    }
+
+    #[test]
+    fn additions_are_a_minor_bump() {
+        let mut diff = diff();
+        diff.entry_points
+            .push(ContractEntryPointDiff::Added(entry_point(vec![])));
+        diff.errors.push(ContractErrorDiff::Added {
+            name: "Paused".to_string(),
+            discriminant: 3,
+        });
+        let assessment = assess_upgrade(&diff);
+        assert_eq!(assessment.severity, ContractChangeCompatibility::Compatible);
+        assert_eq!(assessment.semver_bump, SemverBump::Minor);
+    }
+
+    #[test]
+    fn potentially_breaking_changes_are_a_minor_bump() {
+        let mut diff = diff();
+        diff.entry_points
+            .push(modified(ContractEntryPointChange::ParameterAdded {
+                name: "memo".to_string(),
+                cl_type: CLType::Option(Box::new(CLType::String)),
+            }));
+        let assessment = assess_upgrade(&diff);
+        assert_eq!(
+            assessment.severity,
+            ContractChangeCompatibility::PotentiallyBreaking
+        );
+        assert_eq!(assessment.semver_bump, SemverBump::Minor);
+    }
+
+    #[test]
+    fn breaking_changes_are_a_major_bump() {
+        let mut diff = diff();
+        diff.entry_points
+            .push(ContractEntryPointDiff::Added(entry_point(vec![])));
+        diff.entry_points
+            .push(modified(ContractEntryPointChange::ParameterTypeChanged {
+                name: "amount".to_string(),
+                from: CLType::U512,
+                to: CLType::U64,
+            }));
+        let assessment = assess_upgrade(&diff);
+        assert_eq!(assessment.severity, ContractChangeCompatibility::Breaking);
+        assert_eq!(assessment.semver_bump, SemverBump::Major);
+        assert_eq!(
+            assessment.changes[1].reason,
+            "Argument type was narrowed, existing values may no longer fit"
+        );
+    }
+
+    #[test]
+    fn widened_return_types_are_potentially_breaking() {
+        let (_, compatibility, _) =
+            classify_entry_point_change(&ContractEntryPointChange::ReturnTypeChanged {
+                from: CLType::U64,
+                to: CLType::U256,
+            });
+        assert_eq!(
+            compatibility,
+            ContractChangeCompatibility::PotentiallyBreaking
+        );
+    }
+}
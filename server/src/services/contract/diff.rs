@@ -1,16 +1,27 @@
 use casper_client::JsonRpcId;
 use casper_client::rpcs::DictionaryItemIdentifier;
-use casper_types::{Digest, contracts::EntryPoints};
+use casper_types::{
+    Digest,
+    contracts::{EntryPoint, EntryPoints},
+};
 use rand::Rng;
+use sqlx::PgPool;
 
 use crate::{
-    models::schema::contract::{
-        ContractEntryPointDiff, ContractNamedKeysDiff, ContractVersionDiff,
-        ContractVersionDiffMeta, ContractVersionSchema,
+    models::schema::{
+        contract::{
+            ContractCrossPackageDiff, ContractEntryPointChange, ContractEntryPointDiff,
+            ContractNamedKeysDiff, ContractVersionDiff, ContractVersionDiffMeta,
+            ContractVersionSchema,
+        },
+        rules::DiffRuleSchema,
     },
     services::contract::{
+        compat::assess_upgrade,
         get_state_root_hash,
         package::{get_contract_package_details, get_contract_version_details},
+        rules::apply_diff_rules,
+        wasm::get_contract_wasm_diff,
     },
 };
 
@@ -56,7 +67,7 @@ pub async fn fetch_contract_diff_from_chain(
         .versions()
         .iter()
         .rev()
-        .find(|(key, _)| obs_package.is_version_enabled((*key).clone()))
+        .find(|(key, _)| obs_package.is_version_enabled(**key))
         .map(|(_, hash)| hash)
         .ok_or("No enabled contract version found for observability package")?;
 
@@ -105,10 +116,97 @@ pub async fn fetch_contract_diff_from_chain(
     }
 }
 
-/// Get the diff for v1 to v2 transition, with v1 being the older version and v2 being newer version
+/// Break down the difference between two versions of an entry point into individual changes.
+///
+/// A parameter is considered renamed when it keeps its position and type while its old name is
+/// gone and its new name did not exist before.
+pub fn get_entry_point_changes(
+    from: &EntryPoint,
+    to: &EntryPoint,
+) -> Vec<ContractEntryPointChange> {
+    let mut changes = vec![];
+    let from_args = from.args();
+    let to_args = to.args();
+    let mut renamed_from = vec![];
+    let mut renamed_to = vec![];
+
+    for (position, from_arg) in from_args.iter().enumerate() {
+        if to_args.iter().any(|a| a.name() == from_arg.name()) {
+            continue;
+        }
+        if let Some(to_arg) = to_args.get(position)
+            && to_arg.cl_type() == from_arg.cl_type()
+            && !from_args.iter().any(|a| a.name() == to_arg.name())
+        {
+            changes.push(ContractEntryPointChange::ParameterRenamed {
+                from: from_arg.name().to_string(),
+                to: to_arg.name().to_string(),
+                cl_type: to_arg.cl_type().clone(),
+            });
+            renamed_from.push(from_arg.name());
+            renamed_to.push(to_arg.name());
+        }
+    }
+
+    for from_arg in from_args {
+        if renamed_from.contains(&from_arg.name()) {
+            continue;
+        }
+        match to_args.iter().find(|a| a.name() == from_arg.name()) {
+            None => changes.push(ContractEntryPointChange::ParameterRemoved {
+                name: from_arg.name().to_string(),
+                cl_type: from_arg.cl_type().clone(),
+            }),
+            Some(to_arg) if to_arg.cl_type() != from_arg.cl_type() => {
+                changes.push(ContractEntryPointChange::ParameterTypeChanged {
+                    name: from_arg.name().to_string(),
+                    from: from_arg.cl_type().clone(),
+                    to: to_arg.cl_type().clone(),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    for to_arg in to_args {
+        if !renamed_to.contains(&to_arg.name())
+            && !from_args.iter().any(|a| a.name() == to_arg.name())
+        {
+            changes.push(ContractEntryPointChange::ParameterAdded {
+                name: to_arg.name().to_string(),
+                cl_type: to_arg.cl_type().clone(),
+            });
+        }
+    }
+
+    if from.ret() != to.ret() {
+        changes.push(ContractEntryPointChange::ReturnTypeChanged {
+            from: from.ret().clone(),
+            to: to.ret().clone(),
+        });
+    }
+    if from.access() != to.access() {
+        changes.push(ContractEntryPointChange::AccessChanged {
+            from: from.access().clone(),
+            to: to.access().clone(),
+        });
+    }
+    if from.entry_point_type() != to.entry_point_type() {
+        changes.push(ContractEntryPointChange::EntryPointTypeChanged {
+            from: from.entry_point_type(),
+            to: to.entry_point_type(),
+        });
+    }
+    changes
+}
+
+/// Get the diff for v1 to v2 transition, with v1 being the older version and v2 being newer version.
+///
+/// `rules` are applied before the diff is returned, pass none to get the complete diff.
 pub async fn get_contract_version_diff(
     v1: ContractVersionSchema,
     v2: ContractVersionSchema,
+    rules: &[DiffRuleSchema],
 ) -> Result<ContractVersionDiff, String> {
     // Contract validation
     let v1_package_hash = &v1.contract_package_hash;
@@ -129,6 +227,16 @@ pub async fn get_contract_version_diff(
         ));
     }
 
+    let mut diff = compute_version_diff(&v1, &v2);
+    apply_diff_rules(&mut diff, rules);
+    Ok(diff)
+}
+
+/// Compute the diff between two contract versions without checking how they relate to each other
+pub fn compute_version_diff(
+    v1: &ContractVersionSchema,
+    v2: &ContractVersionSchema,
+) -> ContractVersionDiff {
     // Compute diff in v1 and v2
     let v1_entry_points_vec = &v1.entry_points;
     let mut v1_entry_points: EntryPoints = EntryPoints::new();
@@ -150,6 +258,7 @@ pub async fn get_contract_version_diff(
                 entry_point_diffs.push(ContractEntryPointDiff::Modified {
                     from: v1_ep.clone(),
                     to: v2_ep.clone(),
+                    changes: get_entry_point_changes(v1_ep, v2_ep),
                 });
             }
             _ => {}
@@ -173,13 +282,13 @@ pub async fn get_contract_version_diff(
         match v2.named_keys.get(&key.to_string()) {
             None => named_keys_diff.push(ContractNamedKeysDiff::Removed {
                 key: key.to_string(),
-                value: v1_val.clone(),
+                value: *v1_val,
             }),
             Some(v2_val) if v1_val != v2_val => {
                 named_keys_diff.push(ContractNamedKeysDiff::Modified {
                     key: key.to_string(),
-                    from: v1_val.clone(),
-                    to: v2_val.clone(),
+                    from: *v1_val,
+                    to: *v2_val,
                 });
             }
             _ => {}
@@ -191,7 +300,7 @@ pub async fn get_contract_version_diff(
             let v2_val = v2.named_keys.get(key).expect("key from keys()");
             named_keys_diff.push(ContractNamedKeysDiff::Added {
                 key: key.to_string(),
-                value: v2_val.clone(),
+                value: *v2_val,
             });
         }
     }
@@ -201,6 +310,10 @@ pub async fn get_contract_version_diff(
         timestamp: v1.age,
         contract_version: v1.contract_version,
         is_disabled: v1.disabled,
+        deploy_hash: v1.deploy_hash.clone(),
+        block_height: v1.block_height,
+        deployer_public_key: v1.deployer_public_key.clone(),
+        upgrade_cost: v1.upgrade_cost.clone(),
     };
 
     let v2_diff_meta = ContractVersionDiffMeta {
@@ -209,14 +322,182 @@ pub async fn get_contract_version_diff(
         timestamp: v2.age,
         contract_version: v2.contract_version,
         is_disabled: v2.disabled,
+        deploy_hash: v2.deploy_hash.clone(),
+        block_height: v2.block_height,
+        deployer_public_key: v2.deployer_public_key.clone(),
+        upgrade_cost: v2.upgrade_cost.clone(),
     };
 
-    let contract_version_diff = ContractVersionDiff {
+    let mut contract_version_diff = ContractVersionDiff {
         v1: v1_diff_meta,
         v2: v2_diff_meta,
-        contract_package_hash: v1_package_hash.to_string(),
+        contract_package_hash: v1.contract_package_hash.clone(),
         entry_points: entry_point_diffs,
         named_keys: named_keys_diff,
+        wasm: None,
+        named_key_values: vec![],
+        assessment: None,
+        applied_rules: vec![],
+        collapsed: vec![],
+        events: vec![],
+        errors: vec![],
+        gas_regressions: vec![],
     };
-    Ok(contract_version_diff)
+    contract_version_diff.assessment = Some(assess_upgrade(&contract_version_diff));
+    contract_version_diff
+}
+
+/// Attach the Wasm module diff to a version diff, leaving it unset when the Wasm cannot be retrieved
+pub async fn attach_wasm_diff(pool: &PgPool, node_address: &str, diff: &mut ContractVersionDiff) {
+    if diff.wasm.is_some() {
+        return;
+    }
+    match get_contract_wasm_diff(
+        pool,
+        (node_address, &diff.v1.wasm_hash),
+        (node_address, &diff.v2.wasm_hash),
+    )
+    .await
+    {
+        Ok(wasm_diff) => diff.wasm = Some(wasm_diff),
+        Err(e) => log::warn!(
+            "Failed to diff wasm {} -> {}: {}",
+            diff.v1.wasm_hash,
+            diff.v2.wasm_hash,
+            e
+        ),
+    }
+}
+
+/// Compare contract versions of any two tracked packages, e.g. a testnet deployment and its mainnet counterpart.
+///
+/// Each Wasm module is fetched from the network of its own package.
+pub async fn get_cross_package_diff(
+    pool: &PgPool,
+    from: (&ContractVersionSchema, &str, &str),
+    to: (&ContractVersionSchema, &str, &str),
+) -> Result<ContractCrossPackageDiff, String> {
+    let (v1, from_network, from_node_address) = from;
+    let (v2, to_network, to_node_address) = to;
+
+    let mut diff = compute_version_diff(v1, v2);
+
+    match get_contract_wasm_diff(
+        pool,
+        (from_node_address, &diff.v1.wasm_hash),
+        (to_node_address, &diff.v2.wasm_hash),
+    )
+    .await
+    {
+        Ok(wasm_diff) => diff.wasm = Some(wasm_diff),
+        Err(e) => log::warn!(
+            "Failed to diff wasm {} -> {}: {}",
+            v1.contract_wasm_hash,
+            v2.contract_wasm_hash,
+            e
+        ),
+    }
+
+    // Wasm hashes are addresses assigned at install, so the same code only matches by its bytes
+    let wasm_hash_equal = diff.wasm.as_ref().map(|wasm| wasm.is_identical);
+
+    Ok(ContractCrossPackageDiff {
+        from_package_hash: v1.contract_package_hash.clone(),
+        from_network: from_network.to_string(),
+        to_package_hash: v2.contract_package_hash.clone(),
+        to_network: to_network.to_string(),
+        wasm_hash_equal,
+        diff,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{CLType, EntryPointAccess, EntryPointType, Parameter};
+
+    use super::*;
+
+    fn entry_point(args: &[(&str, CLType)], ret: CLType) -> EntryPoint {
+        EntryPoint::new(
+            "transfer",
+            args.iter()
+                .map(|(name, cl_type)| Parameter::new(*name, cl_type.clone()))
+                .collect(),
+            ret,
+            EntryPointAccess::Public,
+            EntryPointType::Called,
+        )
+    }
+
+    #[test]
+    fn renames_parameters_kept_in_place() {
+        let from = entry_point(
+            &[("recipient", CLType::Key), ("amount", CLType::U256)],
+            CLType::Unit,
+        );
+        let to = entry_point(
+            &[("to", CLType::Key), ("amount", CLType::U256)],
+            CLType::Unit,
+        );
+        assert_eq!(
+            get_entry_point_changes(&from, &to),
+            vec![ContractEntryPointChange::ParameterRenamed {
+                from: "recipient".to_string(),
+                to: "to".to_string(),
+                cl_type: CLType::Key,
+            }]
+        );
+    }
+
+    #[test]
+    fn replaces_parameters_whose_type_changes_with_their_name() {
+        let from = entry_point(&[("recipient", CLType::Key)], CLType::Unit);
+        let to = entry_point(&[("to", CLType::PublicKey)], CLType::Unit);
+        assert_eq!(
+            get_entry_point_changes(&from, &to),
+            vec![
+                ContractEntryPointChange::ParameterRemoved {
+                    name: "recipient".to_string(),
+                    cl_type: CLType::Key,
+                },
+                ContractEntryPointChange::ParameterAdded {
+                    name: "to".to_string(),
+                    cl_type: CLType::PublicKey,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_type_and_return_changes() {
+        let from = entry_point(&[("amount", CLType::U64)], CLType::Unit);
+        let to = entry_point(
+            &[("amount", CLType::U256), ("memo", CLType::String)],
+            CLType::Bool,
+        );
+        assert_eq!(
+            get_entry_point_changes(&from, &to),
+            vec![
+                ContractEntryPointChange::ParameterTypeChanged {
+                    name: "amount".to_string(),
+                    from: CLType::U64,
+                    to: CLType::U256,
+                },
+                ContractEntryPointChange::ParameterAdded {
+                    name: "memo".to_string(),
+                    cl_type: CLType::String,
+                },
+                ContractEntryPointChange::ReturnTypeChanged {
+                    from: CLType::Unit,
+                    to: CLType::Bool,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_nothing_for_unchanged_entry_points() {
+        let from = entry_point(&[("amount", CLType::U256)], CLType::Unit);
+        assert!(get_entry_point_changes(&from, &from).is_empty());
+    }
 }
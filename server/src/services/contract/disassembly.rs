@@ -0,0 +1,168 @@
+use similar::TextDiff;
+use sqlx::PgPool;
+
+use crate::{
+    models::schema::wasm::ContractWatDiff,
+    services::{
+        contract::wasm::{get_contract_wasm, parse_wasm_module},
+        database::wasm::{
+            get_wasm_disassembly, get_wat_diff_page, insert_wasm_disassembly, insert_wat_diff,
+        },
+    },
+    utils::strip_hash_prefix,
+};
+
+const DEFAULT_WAT_DIFF_PAGE_SIZE: u32 = 500;
+const MAX_WAT_DIFF_PAGE_SIZE: u32 = 5000;
+const WAT_DIFF_CONTEXT_LINES: usize = 3;
+
+/// Get WAT disassembly of contract wasm, disassembling and caching on first use
+pub async fn get_contract_wat(
+    pool: &PgPool,
+    node_address: &str,
+    wasm_hash: &str,
+) -> Result<String, String> {
+    let wasm_hash = strip_hash_prefix(wasm_hash);
+    if let Some(wat) = get_wasm_disassembly(pool, &wasm_hash)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(wat);
+    }
+
+    let bytes = get_contract_wasm(pool, node_address, &wasm_hash).await?;
+    let wat = wasmprinter::print_bytes(&bytes)
+        .map_err(|e| format!("Failed to disassemble wasm {}: {}", wasm_hash, e))?;
+    if let Err(e) = insert_wasm_disassembly(pool, &wasm_hash, &wat).await {
+        log::warn!("Failed to cache disassembly of {}: {}", wasm_hash, e);
+    }
+    Ok(wat)
+}
+
+/// Extract the `(func ...)` block of the function at `index` from module level WAT
+fn extract_wat_function(wat: &str, index: u32) -> Option<String> {
+    let marker = format!("(;{};)", index);
+    let mut lines = wat
+        .lines()
+        .skip_while(|line| !(line.starts_with("  (func ") && line.contains(&marker)));
+    let first = lines.next()?;
+    let mut function = vec![first];
+    // Module fields are indented once, anything deeper still belongs to the function
+    function.extend(lines.take_while(|line| !line.starts_with("  (") && *line != ")"));
+    Some(function.join("\n"))
+}
+
+/// Find the function index behind an export or debug name
+fn find_function_index(bytes: &[u8], function: &str) -> Result<Option<u32>, String> {
+    let module = parse_wasm_module(bytes)?;
+    Ok(module
+        .functions
+        .iter()
+        .find(|f| {
+            f.export_names.iter().any(|n| n == function) || f.name.as_deref() == Some(function)
+        })
+        .map(|f| f.index))
+}
+
+/// Compute the unified diff lines between the WAT disassembly of two contract wasms
+async fn compute_wat_diff(
+    pool: &PgPool,
+    node_address: &str,
+    from_wasm_hash: &str,
+    to_wasm_hash: &str,
+    function: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let from_wat = get_contract_wat(pool, node_address, from_wasm_hash).await?;
+    let to_wat = get_contract_wat(pool, node_address, to_wasm_hash).await?;
+
+    let (from_text, to_text) = match function {
+        Some(function) => {
+            let from_bytes = get_contract_wasm(pool, node_address, from_wasm_hash).await?;
+            let to_bytes = get_contract_wasm(pool, node_address, to_wasm_hash).await?;
+            // A function missing on one side diffs against an empty text, showing it as added or removed
+            let from_text = find_function_index(&from_bytes, function)?
+                .and_then(|index| extract_wat_function(&from_wat, index))
+                .unwrap_or_default();
+            let to_text = find_function_index(&to_bytes, function)?
+                .and_then(|index| extract_wat_function(&to_wat, index))
+                .unwrap_or_default();
+            if from_text.is_empty() && to_text.is_empty() {
+                return Err(format!("Function {} not found in either version", function));
+            }
+            (from_text, to_text)
+        }
+        None => (from_wat, to_wat),
+    };
+
+    let unified = TextDiff::from_lines(&from_text, &to_text)
+        .unified_diff()
+        .context_radius(WAT_DIFF_CONTEXT_LINES)
+        .header(
+            &strip_hash_prefix(from_wasm_hash),
+            &strip_hash_prefix(to_wasm_hash),
+        )
+        .to_string();
+    Ok(unified.lines().map(|line| line.to_string()).collect())
+}
+
+/// Get a page of the unified diff between the WAT disassembly of two contract wasms.
+///
+/// The diff is computed once per pair of wasms and function, later pages are read from the cache.
+pub async fn get_contract_wat_diff(
+    pool: &PgPool,
+    node_address: &str,
+    from_wasm_hash: &str,
+    to_wasm_hash: &str,
+    function: Option<&str>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> Result<ContractWatDiff, String> {
+    let from_label = strip_hash_prefix(from_wasm_hash);
+    let to_label = strip_hash_prefix(to_wasm_hash);
+    let page_size = page_size
+        .unwrap_or(DEFAULT_WAT_DIFF_PAGE_SIZE)
+        .clamp(1, MAX_WAT_DIFF_PAGE_SIZE);
+    let page = page.unwrap_or(1).max(1);
+    let offset = (page - 1).saturating_mul(page_size);
+    let cache_key = function.unwrap_or_default();
+
+    let cached = get_wat_diff_page(pool, &from_label, &to_label, cache_key, offset, page_size)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (total_lines, lines) = match cached {
+        Some(page) => page,
+        None => {
+            let diff_lines =
+                compute_wat_diff(pool, node_address, from_wasm_hash, to_wasm_hash, function)
+                    .await?;
+            if let Err(e) =
+                insert_wat_diff(pool, &from_label, &to_label, cache_key, &diff_lines).await
+            {
+                log::warn!(
+                    "Failed to cache WAT diff of {} to {}: {}",
+                    from_label,
+                    to_label,
+                    e
+                );
+            }
+            let total_lines = diff_lines.len() as u32;
+            let lines = diff_lines
+                .into_iter()
+                .skip(offset as usize)
+                .take(page_size as usize)
+                .collect();
+            (total_lines, lines)
+        }
+    };
+
+    Ok(ContractWatDiff {
+        from_wasm_hash: from_label,
+        to_wasm_hash: to_label,
+        function: function.map(|f| f.to_string()),
+        page,
+        page_size,
+        total_lines,
+        total_pages: total_lines.div_ceil(page_size),
+        lines,
+    })
+}
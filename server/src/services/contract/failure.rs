@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        api::transaction::{FailureBreakdown, FailureCount},
+        schema::transaction::{
+            TransactionFailure, TransactionFailureKind, TransactionOutcomeCount,
+        },
+    },
+    services::database::{failure::get_contract_error_codes, schema::get_contract_schema},
+};
+
+/// Digits following `prefix` in a lowercased error message
+fn code_after(message: &str, prefix: &str) -> Option<u32> {
+    let rest = &message[message.find(prefix)? + prefix.len()..];
+    let digits: String = rest
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+/// Parse an execution error message, as reported by nodes and cspr.live, into its category
+pub fn classify_failure(message: &str) -> (TransactionFailureKind, Option<u32>) {
+    let message = message.to_lowercase();
+    if let Some(code) = code_after(&message, "user error:") {
+        return (TransactionFailureKind::UserError, Some(code));
+    }
+    // Host errors are rendered as `ApiError::<Variant> [<code>]`
+    let code = code_after(&message, "[");
+    let kind = if message.contains("out of gas") || message.contains("gaslimit") {
+        TransactionFailureKind::OutOfGas
+    } else if [
+        "missingkey",
+        "missing key",
+        "key not found",
+        "valuenotfound",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+    {
+        TransactionFailureKind::MissingKey
+    } else if [
+        "missingargument",
+        "invalidargument",
+        "missing argument",
+        "invalid argument",
+        "cltype",
+        "deserializ",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+    {
+        TransactionFailureKind::InvalidArgs
+    } else {
+        TransactionFailureKind::Other
+    };
+    (kind, code.filter(|_| kind != TransactionFailureKind::Other))
+}
+
+/// Names of the user error codes of a package: its manual error codes, then the errors of the Odra
+/// schema of the called version
+#[derive(Default)]
+pub struct ErrorCodeNames {
+    manual: HashMap<u32, String>,
+    schemas: HashMap<u32, HashMap<u32, String>>,
+}
+
+impl ErrorCodeNames {
+    pub async fn load(
+        pool: &PgPool,
+        contract_package_hash: &str,
+        user_id: &Uuid,
+        versions: &[u32],
+    ) -> Result<Self, String> {
+        let manual = get_contract_error_codes(pool, contract_package_hash, user_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|error_code| (error_code.code, error_code.name))
+            .collect();
+
+        let mut schemas = HashMap::new();
+        for &version in versions {
+            if schemas.contains_key(&version) {
+                continue;
+            }
+            let errors = get_contract_schema(pool, contract_package_hash, version, user_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|schema| {
+                    schema
+                        .schema
+                        .errors
+                        .into_iter()
+                        .map(|error| (u32::from(error.discriminant), error.name))
+                        .collect()
+                })
+                .unwrap_or_default();
+            schemas.insert(version, errors);
+        }
+        Ok(Self { manual, schemas })
+    }
+
+    pub fn name(&self, version: Option<u32>, code: u32) -> Option<String> {
+        self.manual
+            .get(&code)
+            .or_else(|| self.schemas.get(&version?)?.get(&code))
+            .cloned()
+    }
+}
+
+/// Structured failure of a transaction, `None` when it succeeded
+pub fn describe_failure(
+    error_message: Option<&str>,
+    version: Option<u32>,
+    names: &ErrorCodeNames,
+) -> Option<TransactionFailure> {
+    let message = error_message.filter(|m| !m.is_empty())?;
+    let (kind, code) = classify_failure(message);
+    let error_name = match (kind, code) {
+        (TransactionFailureKind::UserError, Some(code)) => names.name(version, code),
+        _ => None,
+    };
+    Some(TransactionFailure {
+        kind,
+        code,
+        error_name,
+        message: message.to_string(),
+    })
+}
+
+/// Group transaction outcomes by the key `group_of` gives, e.g. the entry point or the version, the
+/// most called groups first
+pub fn get_failure_breakdown(
+    outcomes: &[TransactionOutcomeCount],
+    names: &ErrorCodeNames,
+    group_of: impl Fn(&TransactionOutcomeCount) -> Option<String>,
+) -> Vec<FailureBreakdown> {
+    let mut groups: HashMap<Option<String>, (u64, HashMap<_, u64>)> = HashMap::new();
+    for outcome in outcomes {
+        let (calls, failures) = groups.entry(group_of(outcome)).or_default();
+        *calls += outcome.count;
+        if let Some(failure) = describe_failure(
+            outcome.error_message.as_deref(),
+            outcome.contract_version,
+            names,
+        ) {
+            *failures
+                .entry((failure.kind, failure.code, failure.error_name))
+                .or_default() += outcome.count;
+        }
+    }
+
+    let mut breakdowns: Vec<FailureBreakdown> = groups
+        .into_iter()
+        .map(|(group, (calls, failures))| {
+            let mut breakdown: Vec<FailureCount> = failures
+                .into_iter()
+                .map(|((kind, code, error_name), count)| FailureCount {
+                    kind,
+                    code,
+                    error_name,
+                    count,
+                })
+                .collect();
+            breakdown.sort_by(|a, b| b.count.cmp(&a.count).then(a.code.cmp(&b.code)));
+            let failures: u64 = breakdown.iter().map(|f| f.count).sum();
+            FailureBreakdown {
+                group,
+                calls,
+                failures,
+                failure_rate: if calls == 0 {
+                    0.0
+                } else {
+                    failures as f64 / calls as f64
+                },
+                breakdown,
+            }
+        })
+        .collect();
+    breakdowns.sort_by(|a, b| b.calls.cmp(&a.calls).then(a.group.cmp(&b.group)));
+    breakdowns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_user_errors_with_their_code() {
+        assert_eq!(
+            classify_failure("User error: 1"),
+            (TransactionFailureKind::UserError, Some(1))
+        );
+        assert_eq!(
+            classify_failure("ApiError::User(64658) [64658] User error: 64658"),
+            (TransactionFailureKind::UserError, Some(64658))
+        );
+    }
+
+    #[test]
+    fn classifies_host_errors_by_variant() {
+        assert_eq!(
+            classify_failure("ApiError::MissingKey [2]"),
+            (TransactionFailureKind::MissingKey, Some(2))
+        );
+        assert_eq!(
+            classify_failure("ApiError::MissingArgument [1]"),
+            (TransactionFailureKind::InvalidArgs, Some(1))
+        );
+        assert_eq!(
+            classify_failure("Out of gas error"),
+            (TransactionFailureKind::OutOfGas, None)
+        );
+    }
+
+    #[test]
+    fn drops_codes_of_unknown_errors() {
+        assert_eq!(
+            classify_failure("ApiError::Unhandled [31]"),
+            (TransactionFailureKind::Other, None)
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::schema::{
+        alert::AlertSchema,
+        contract::{ContractVersionDiff, GasRegression},
+    },
+    services::database::analytics::get_entry_point_costs,
+};
+
+pub const GAS_REGRESSION_ALERT: &str = "GasRegression";
+
+/// Calls per version and entry point below which costs are not compared
+const MIN_GAS_SAMPLES: usize = 30;
+
+/// Latest calls per version and entry point the comparison is based on
+const MAX_GAS_SAMPLES: u64 = 1000;
+
+/// Significance level of the Mann-Whitney U test
+const GAS_REGRESSION_P_VALUE: f64 = 0.01;
+
+/// Median increase below which a significant difference is not reported
+const MIN_GAS_INCREASE: f64 = 0.05;
+
+/// Complementary error function, Numerical Recipes' Chebyshev approximation with a relative error
+/// below 1.2e-7
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Probability that a standard normal variable exceeds `z`
+pub fn normal_sf(z: f64) -> f64 {
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+pub fn median(samples: &[f64]) -> f64 {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// One-sided Mann-Whitney U test of `to` tending to be larger than `from`.
+///
+/// Uses the normal approximation with tie and continuity correction, sound for the sample sizes
+/// compared.
+pub fn mann_whitney_greater(from: &[f64], to: &[f64]) -> f64 {
+    let (n1, n2) = (from.len() as f64, to.len() as f64);
+    let mut pooled: Vec<(f64, bool)> = from
+        .iter()
+        .map(|c| (*c, false))
+        .chain(to.iter().map(|c| (*c, true)))
+        .collect();
+    pooled.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut to_rank_sum = 0.0;
+    let mut tie_correction = 0.0;
+    let mut i = 0;
+    while i < pooled.len() {
+        let mut j = i;
+        while j < pooled.len() && pooled[j].0 == pooled[i].0 {
+            j += 1;
+        }
+        // Tied values share the average of their ranks, which start at 1
+        let rank = (i + j + 1) as f64 / 2.0;
+        let ties = (j - i) as f64;
+        to_rank_sum += rank * pooled[i..j].iter().filter(|(_, is_to)| *is_to).count() as f64;
+        tie_correction += ties * ties * ties - ties;
+        i = j;
+    }
+
+    let n = n1 + n2;
+    let u = to_rank_sum - n2 * (n2 + 1.0) / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    normal_sf((u - n1 * n2 / 2.0 - 0.5) / variance.sqrt())
+}
+
+/// Compare the costs of the entry points called on both versions
+pub fn get_gas_regressions(
+    from: &HashMap<String, Vec<f64>>,
+    to: &HashMap<String, Vec<f64>>,
+) -> Vec<GasRegression> {
+    let mut regressions: Vec<GasRegression> = from
+        .iter()
+        .filter_map(|(entry_point_name, from_costs)| {
+            let to_costs = to.get(entry_point_name)?;
+            if from_costs.len() < MIN_GAS_SAMPLES || to_costs.len() < MIN_GAS_SAMPLES {
+                return None;
+            }
+            let (from_median, to_median) = (median(from_costs), median(to_costs));
+            if from_median <= 0.0 {
+                return None;
+            }
+            let increase = to_median / from_median - 1.0;
+            if increase < MIN_GAS_INCREASE {
+                return None;
+            }
+            let p_value = mann_whitney_greater(from_costs, to_costs);
+            (p_value < GAS_REGRESSION_P_VALUE).then(|| GasRegression {
+                entry_point_name: entry_point_name.clone(),
+                from_samples: from_costs.len() as u64,
+                to_samples: to_costs.len() as u64,
+                from_median,
+                to_median,
+                increase,
+                p_value,
+            })
+        })
+        .collect();
+    regressions.sort_by(|a, b| b.increase.total_cmp(&a.increase));
+    regressions
+}
+
+/// Find the entry points that got more expensive from version `v1` to `v2` of a package
+pub async fn find_gas_regressions(
+    pool: &PgPool,
+    network: &str,
+    package_hash: &str,
+    user_id: &Uuid,
+    v1: u32,
+    v2: u32,
+) -> Result<Vec<GasRegression>, String> {
+    let costs = get_entry_point_costs(
+        pool,
+        network,
+        package_hash,
+        user_id,
+        &[v1, v2],
+        MAX_GAS_SAMPLES,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut from: HashMap<String, Vec<f64>> = HashMap::new();
+    let mut to: HashMap<String, Vec<f64>> = HashMap::new();
+    for (version, entry_point_name, cost) in costs {
+        let samples = if version == v1 { &mut from } else { &mut to };
+        samples.entry(entry_point_name).or_default().push(cost);
+    }
+    Ok(get_gas_regressions(&from, &to))
+}
+
+/// Attach gas regressions to a version diff, leaving them empty when the costs cannot be read
+pub async fn attach_gas_regressions(
+    pool: &PgPool,
+    network: &str,
+    user_id: &Uuid,
+    diff: &mut ContractVersionDiff,
+) {
+    match find_gas_regressions(
+        pool,
+        network,
+        &diff.contract_package_hash,
+        user_id,
+        diff.v1.contract_version,
+        diff.v2.contract_version,
+    )
+    .await
+    {
+        Ok(regressions) => diff.gas_regressions = regressions,
+        Err(e) => log::warn!(
+            "Failed to compare costs of {} v{} -> v{}: {}",
+            diff.contract_package_hash,
+            diff.v1.contract_version,
+            diff.v2.contract_version,
+            e
+        ),
+    }
+}
+
+pub fn get_gas_regression_alerts(
+    user_id: Uuid,
+    package_hash: &str,
+    v1: u32,
+    v2: u32,
+    regressions: &[GasRegression],
+) -> Vec<AlertSchema> {
+    regressions
+        .iter()
+        .map(|regression| {
+            AlertSchema::new(
+                user_id,
+                package_hash.to_string(),
+                GAS_REGRESSION_ALERT,
+                format!(
+                    "Entry point {} costs {:.0}% more on version {} than on version {}",
+                    regression.entry_point_name,
+                    regression.increase * 100.0,
+                    v2,
+                    v1
+                ),
+                serde_json::json!({
+                    "from_version": v1,
+                    "to_version": v2,
+                    "regression": regression,
+                }),
+                format!(
+                    "gas-regression:{}:{}:{}:{}",
+                    package_hash, regression.entry_point_name, v1, v2
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_of_odd_and_even_samples() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert_eq!(median(&[7.0]), 7.0);
+    }
+
+    #[test]
+    fn mann_whitney_textbook_example() {
+        // U = 17 for the larger group against 3 for the smaller one, z = 1.592 after continuity
+        // correction
+        let from = [20.0, 11.0, 17.0, 12.0];
+        let to = [19.0, 22.0, 16.0, 29.0, 24.0];
+        assert!((mann_whitney_greater(&from, &to) - 0.05567).abs() < 1e-4);
+        assert!(mann_whitney_greater(&to, &from) > 0.9);
+    }
+
+    #[test]
+    fn mann_whitney_identical_samples_are_not_greater() {
+        assert_eq!(mann_whitney_greater(&[5.0; 4], &[5.0; 6]), 1.0);
+    }
+}
@@ -0,0 +1,226 @@
+use casper_client::{JsonRpcId, rpcs::common::BlockIdentifier};
+use casper_types::contracts::ContractPackage;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+
+use crate::{
+    models::schema::contract::{
+        ContractPackageHistory, ContractPackageHistoryEvent, ContractPackageHistoryEventKind,
+        ContractPackageSnapshot, ContractPackageSnapshotVersion,
+    },
+    services::contract::{digest_to_hex, package::query_contract_package_at_state_root},
+    utils::strip_hash_prefix,
+};
+
+/// Upper bound on global state queries made while bisecting a single history request
+const MAX_HISTORY_PROBES: usize = 512;
+
+struct BlockInfo {
+    height: u64,
+    hash: String,
+    timestamp: DateTime<Utc>,
+    state_root_hash: String,
+}
+
+/// Get block height, hash, timestamp and state root hash, falling back to the latest block when no height is given
+async fn get_block_info(
+    node_address: &str,
+    block_height: Option<u64>,
+) -> Result<BlockInfo, String> {
+    let rpc_id = JsonRpcId::from(rand::rng().random::<i64>());
+
+    let response = casper_client::get_block(
+        rpc_id,
+        node_address,
+        casper_client::Verbosity::Low,
+        block_height.map(BlockIdentifier::Height),
+    )
+    .await
+    .map_err(|e| format!("Failed to get block: {}", e))?;
+
+    let block = response
+        .result
+        .block_with_signatures
+        .ok_or("Node did not return a block for the requested height")?
+        .block;
+
+    let timestamp = DateTime::<Utc>::from_timestamp_millis(block.timestamp().millis() as i64)
+        .ok_or("Block timestamp is out of range")?;
+
+    Ok(BlockInfo {
+        height: block.height(),
+        hash: digest_to_hex(block.hash().inner())?,
+        timestamp,
+        state_root_hash: digest_to_hex(block.state_root_hash())?,
+    })
+}
+
+fn snapshot_versions(package: &ContractPackage) -> Vec<ContractPackageSnapshotVersion> {
+    package
+        .versions()
+        .iter()
+        .map(|(key, hash)| {
+            let contract_hash = format!("hash-{}", strip_hash_prefix(&hash.to_formatted_string()));
+            ContractPackageSnapshotVersion {
+                protocol_major_version: key.protocol_version_major(),
+                contract_version: key.contract_version(),
+                contract_hash,
+                disabled: !package.is_version_enabled(*key),
+            }
+        })
+        .collect()
+}
+
+/// Get the state of a contract package at the given block height
+pub async fn get_contract_package_snapshot(
+    node_address: &str,
+    package_hash: &str,
+    block_height: Option<u64>,
+) -> Result<ContractPackageSnapshot, String> {
+    let raw_package_hash = strip_hash_prefix(package_hash);
+    let package_hash = format!("hash-{}", raw_package_hash);
+    let block = get_block_info(node_address, block_height).await?;
+
+    let package =
+        query_contract_package_at_state_root(node_address, &package_hash, &block.state_root_hash)
+            .await?;
+
+    // Packages do not exist in global state before the block that installed them
+    let (exists, lock_status, versions) = match package {
+        Some(package) => (true, package.is_locked(), snapshot_versions(&package)),
+        None => (false, false, vec![]),
+    };
+
+    Ok(ContractPackageSnapshot {
+        block_height: block.height,
+        block_hash: block.hash,
+        timestamp: block.timestamp,
+        exists,
+        lock_status,
+        versions,
+    })
+}
+
+fn is_same_state(a: &ContractPackageSnapshot, b: &ContractPackageSnapshot) -> bool {
+    a.exists == b.exists && a.lock_status == b.lock_status && a.versions == b.versions
+}
+
+/// Events explaining the transition from `from` to `to`, attributed to the block of `to`
+fn get_state_changes(
+    from: &ContractPackageSnapshot,
+    to: &ContractPackageSnapshot,
+) -> Vec<ContractPackageHistoryEvent> {
+    let mut events = vec![];
+    let mut push = |event: ContractPackageHistoryEventKind| {
+        events.push(ContractPackageHistoryEvent {
+            block_height: to.block_height,
+            block_hash: to.block_hash.clone(),
+            timestamp: to.timestamp,
+            event,
+        })
+    };
+
+    if !from.exists && to.exists {
+        push(ContractPackageHistoryEventKind::Created);
+    }
+
+    for version in &to.versions {
+        let previous = from.versions.iter().find(|v| {
+            v.protocol_major_version == version.protocol_major_version
+                && v.contract_version == version.contract_version
+        });
+        match previous {
+            None => {
+                push(ContractPackageHistoryEventKind::VersionAdded {
+                    protocol_major_version: version.protocol_major_version,
+                    contract_version: version.contract_version,
+                    contract_hash: version.contract_hash.clone(),
+                });
+                if version.disabled {
+                    push(ContractPackageHistoryEventKind::VersionDisabled {
+                        protocol_major_version: version.protocol_major_version,
+                        contract_version: version.contract_version,
+                    });
+                }
+            }
+            Some(previous) if !previous.disabled && version.disabled => {
+                push(ContractPackageHistoryEventKind::VersionDisabled {
+                    protocol_major_version: version.protocol_major_version,
+                    contract_version: version.contract_version,
+                });
+            }
+            Some(previous) if previous.disabled && !version.disabled => {
+                push(ContractPackageHistoryEventKind::VersionEnabled {
+                    protocol_major_version: version.protocol_major_version,
+                    contract_version: version.contract_version,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if (!from.exists || !from.lock_status) && to.lock_status {
+        push(ContractPackageHistoryEventKind::Locked);
+    }
+
+    events
+}
+
+/// Reconstruct the history of a contract package across the given block heights.
+///
+/// The package is sampled at each of `heights`. When `bisect` is set, every pair of adjacent samples whose
+/// state differs is bisected down to the exact block in which the change happened. A change that is reverted
+/// between two samples with identical state (e.g. a version disabled and re-enabled) cannot be observed, so
+/// denser samples give a more complete timeline.
+///
+/// Changes are resolved in block order. When the query budget runs out the history found so far is
+/// returned with `continue_from_height`, the height to resume from, up to which it is complete.
+pub async fn get_contract_package_history(
+    node_address: &str,
+    package_hash: &str,
+    heights: &[Option<u64>],
+    bisect: bool,
+) -> Result<ContractPackageHistory, String> {
+    let mut snapshots = vec![];
+    for height in heights {
+        snapshots.push(get_contract_package_snapshot(node_address, package_hash, *height).await?);
+    }
+    snapshots.sort_by_key(|s| s.block_height);
+    snapshots.dedup_by_key(|s| s.block_height);
+
+    let mut events = vec![];
+    let mut probes = snapshots.len();
+    let mut continue_from_height = None;
+    'pairs: for pair in snapshots.windows(2) {
+        let mut pending = vec![(pair[0].clone(), pair[1].clone())];
+        while let Some((lo, hi)) = pending.pop() {
+            if is_same_state(&lo, &hi) {
+                continue;
+            }
+            if !bisect || hi.block_height - lo.block_height <= 1 {
+                events.extend(get_state_changes(&lo, &hi));
+                continue;
+            }
+            if probes >= MAX_HISTORY_PROBES {
+                continue_from_height = Some(lo.block_height);
+                break 'pairs;
+            }
+            probes += 1;
+
+            let mid = lo.block_height + (hi.block_height - lo.block_height) / 2;
+            let mid_snapshot =
+                get_contract_package_snapshot(node_address, package_hash, Some(mid)).await?;
+            // Push upper half first so the lower half is resolved first and events stay ordered
+            pending.push((mid_snapshot.clone(), hi));
+            pending.push((lo, mid_snapshot));
+        }
+    }
+    events.sort_by_key(|e| e.block_height);
+
+    Ok(ContractPackageHistory {
+        contract_package_hash: strip_hash_prefix(package_hash),
+        snapshots,
+        events,
+        continue_from_height,
+    })
+}
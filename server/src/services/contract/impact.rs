@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use chrono::Duration;
+use sqlx::PgPool;
+
+use crate::{
+    models::{
+        api::analytics::{AffectedEntryPoint, ImpactReport},
+        schema::{
+            contract::{
+                ContractChangeCompatibility, ContractEntryPointDiff, ContractVersionDiff,
+                ContractVersionSchema,
+            },
+            rules::DiffRuleSchema,
+        },
+    },
+    services::{
+        contract::{compat::assess_upgrade, diff::get_contract_version_diff},
+        database::analytics::{get_contract_callers, get_entry_point_callers},
+    },
+};
+
+/// Entry points of a diff that are removed or carry a breaking change
+pub fn get_affected_entry_points(diff: &ContractVersionDiff) -> Vec<AffectedEntryPoint> {
+    let assessment = diff
+        .assessment
+        .clone()
+        .unwrap_or_else(|| assess_upgrade(diff));
+
+    diff.entry_points
+        .iter()
+        .filter_map(|entry_point| match entry_point {
+            ContractEntryPointDiff::Removed(ep) => Some(AffectedEntryPoint {
+                name: ep.name().to_string(),
+                changes: vec!["Entry point removed".to_string()],
+            }),
+            ContractEntryPointDiff::Modified { to, .. } => {
+                let changes: Vec<String> = assessment
+                    .changes
+                    .iter()
+                    .filter(|c| {
+                        c.subject == to.name()
+                            && c.compatibility == ContractChangeCompatibility::Breaking
+                    })
+                    .map(|c| c.change.clone())
+                    .collect();
+                (!changes.is_empty()).then(|| AffectedEntryPoint {
+                    name: to.name().to_string(),
+                    changes,
+                })
+            }
+            ContractEntryPointDiff::Added(_) => None,
+        })
+        .collect()
+}
+
+/// Report the callers of the entry points an upgrade from `v1` to `v2` breaks.
+///
+/// Callers are the accounts initiating the transactions to the affected entry points. The contracts
+/// and session code the block indexer found calling into the package are reported apart, without
+/// the entry point they called.
+pub async fn get_impact_report(
+    pool: &PgPool,
+    network: &str,
+    v1: ContractVersionSchema,
+    v2: ContractVersionSchema,
+    lookback: Duration,
+    rules: &[DiffRuleSchema],
+) -> Result<ImpactReport, String> {
+    let upgraded_at = v2.age;
+    let lookback_from = upgraded_at - lookback;
+    // Renamed entry points are paired by the rules rather than reported as removed
+    let diff = get_contract_version_diff(v1, v2, rules).await?;
+    let affected_entry_points = get_affected_entry_points(&diff);
+
+    let names: Vec<String> = affected_entry_points
+        .iter()
+        .map(|ep| ep.name.clone())
+        .collect();
+    let callers = if names.is_empty() {
+        vec![]
+    } else {
+        get_entry_point_callers(
+            pool,
+            network,
+            &diff.contract_package_hash,
+            &names,
+            upgraded_at,
+            lookback_from,
+        )
+        .await
+        .map_err(|e| e.to_string())?
+    };
+
+    let package_contract_callers = if names.is_empty() {
+        vec![]
+    } else {
+        get_contract_callers(
+            pool,
+            network,
+            &diff.contract_package_hash,
+            upgraded_at,
+            lookback_from,
+        )
+        .await
+        .map_err(|e| e.to_string())?
+    };
+
+    let impacted_accounts = callers
+        .iter()
+        .map(|c| &c.caller_public_key)
+        .collect::<HashSet<_>>()
+        .len() as u64;
+    let failing_accounts = callers
+        .iter()
+        .filter(|c| c.failures_after_upgrade > 0)
+        .map(|c| &c.caller_public_key)
+        .collect::<HashSet<_>>()
+        .len() as u64;
+    Ok(ImpactReport {
+        contract_package_hash: diff.contract_package_hash,
+        v1: diff.v1.contract_version,
+        v2: diff.v2.contract_version,
+        upgraded_at,
+        lookback_from,
+        affected_entry_points,
+        callers,
+        impacted_accounts,
+        failing_accounts,
+        package_contract_callers,
+    })
+}
@@ -1,6 +1,11 @@
 use crate::constants::headers::CONTRACT_PACKAGE_TESTNET_API_INCLUDES_PARAMS;
 use crate::constants::network::{MAINNET_API_ENDPOINT, TESTNET_API_ENDPOINT};
+use crate::models::api::transaction::Transaction;
 use crate::models::schema::contract::{APIMetaResponse, ContractPackageMeta, ContractVersionMeta};
+use crate::models::schema::transaction::ContractPackageWrite;
+use crate::services::database::indexer::get_contract_package_writes;
+use crate::utils::strip_hash_prefix;
+use sqlx::PgPool;
 
 /// Fetch contract package metadata from cspr.live
 pub async fn get_contract_package_metadata(
@@ -14,7 +19,7 @@ pub async fn get_contract_package_metadata(
     };
 
     // cspr.live expects RAW hash (no `hash-`)
-    let raw_hash = hash.strip_prefix("hash-").unwrap_or(hash);
+    let raw_hash = strip_hash_prefix(hash);
 
     if raw_hash.len() != 64 || !raw_hash.chars().all(|c| c.is_ascii_hexdigit()) {
         return Err(format!("Invalid contract package hash: {}", raw_hash));
@@ -57,7 +62,7 @@ pub async fn get_contract_version_metadata(
 
     // cspr.live expects RAW hash (no `hash-`)
 
-    let raw_hash = hash.strip_prefix("hash-").unwrap_or(hash);
+    let raw_hash = strip_hash_prefix(hash);
 
     let url = format!("{endpoint}/contracts/{raw_hash}");
 
@@ -81,3 +86,59 @@ pub async fn get_contract_version_metadata(
         Err(format!("Error while fetching contract {raw_hash}"))
     }
 }
+
+/// Fetch a single deploy from cspr.live, used to attribute contract upgrades to their deployer
+pub async fn get_deploy_metadata(network: &str, deploy_hash: &str) -> Result<Transaction, String> {
+    let endpoint: &str = if network == "mainnet" {
+        MAINNET_API_ENDPOINT
+    } else {
+        TESTNET_API_ENDPOINT
+    };
+
+    let raw_hash = strip_hash_prefix(deploy_hash);
+    let url = format!("{endpoint}/deploys/{raw_hash}");
+
+    let client = reqwest::Client::new();
+    let resp = client.get(&url).send().await.map_err(|e| e.to_string())?;
+    if resp.status().is_success() {
+        let json: APIMetaResponse<Transaction> = resp.json().await.map_err(|e| e.to_string())?;
+        Ok(json.data)
+    } else {
+        if reqwest::StatusCode::NOT_FOUND == resp.status() {
+            return Err(format!("Deploy {raw_hash} not found"));
+        }
+        Err(format!("Error while fetching deploy {raw_hash}"))
+    }
+}
+
+/// Get the transaction that installed a contract package, as read by the block indexer, `None` when
+/// the indexer has not read it, i.e. the package was installed before the start height
+pub async fn get_indexed_package_metadata(
+    pool: &PgPool,
+    network: &str,
+    package_hash: &str,
+) -> Result<Option<ContractPackageWrite>, String> {
+    let raw_hash = strip_hash_prefix(package_hash);
+    get_contract_package_writes(pool, network, &raw_hash)
+        .await
+        .map(|writes| writes.into_iter().next())
+        .map_err(|e| e.to_string())
+}
+
+/// Get the transaction that added a contract to its package, as read by the block indexer
+pub async fn get_indexed_version_metadata(
+    pool: &PgPool,
+    network: &str,
+    package_hash: &str,
+    contract_hash: &str,
+) -> Result<ContractPackageWrite, String> {
+    let raw_hash = strip_hash_prefix(contract_hash);
+    get_contract_package_writes(pool, network, &strip_hash_prefix(package_hash))
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|write| write.contract_hashes.contains(&raw_hash))
+        .ok_or_else(|| {
+            format!("Contract {raw_hash} was installed before the block indexer start height")
+        })
+}
@@ -1,19 +1,58 @@
-use casper_client::JsonRpcId;
+use casper_client::{JsonRpcId, rpcs::common::BlockIdentifier};
+use casper_types::Digest;
 use rand::Rng;
 
+pub(crate) mod anomaly;
+pub(crate) mod block;
+pub(crate) mod call;
+pub(crate) mod changelog;
+pub(crate) mod compat;
 pub(crate) mod diff;
+pub(crate) mod disassembly;
+pub(crate) mod failure;
+pub(crate) mod gas;
+pub(crate) mod history;
+pub(crate) mod impact;
 pub(crate) mod metadata;
+pub(crate) mod named_keys;
 pub(crate) mod package;
+pub(crate) mod predeploy;
+pub(crate) mod promotion;
+pub(crate) mod rules;
+pub(crate) mod schema;
+pub(crate) mod storage;
 pub(crate) mod transaction;
+pub(crate) mod usage;
+pub(crate) mod verification;
+pub(crate) mod wasm;
+
+/// JSON-RPC error code nodes answer global state queries of absent entries with
+const QUERY_FAILED_ERROR_CODE: i64 = -32003;
+
+/// Whether a node error reports that the queried global state entry does not exist
+pub fn is_value_not_found(error: &casper_client::Error) -> bool {
+    matches!(
+        error,
+        casper_client::Error::ResponseIsRpcError { error, .. } if error.code == QUERY_FAILED_ERROR_CODE
+    )
+}
 
 pub async fn get_state_root_hash(node_address: &str) -> Result<String, String> {
+    get_state_root_hash_at_height(node_address, None).await
+}
+
+/// Get state root hash at the given block height, falling back to the latest block when no height is given
+pub async fn get_state_root_hash_at_height(
+    node_address: &str,
+    block_height: Option<u64>,
+) -> Result<String, String> {
     let rpc_id = JsonRpcId::from(rand::rng().random::<i64>());
 
     let state_root = casper_client::get_state_root_hash(
         rpc_id,
         node_address,
         casper_client::Verbosity::Low,
-        None,
+        block_height.map(BlockIdentifier::Height),
     )
     .await
     .map_err(|e| format!("Failed to get state root digest: {:?}", e.to_string()))?;
@@ -23,9 +62,12 @@ pub async fn get_state_root_hash(node_address: &str) -> Result<String, String> {
         return Err("Failed to obtain state root digest".to_string());
     }
 
-    let state_root_hash_raw = state_root_hash.unwrap();
+    digest_to_hex(&state_root_hash.unwrap())
+}
 
-    let hash_str = format!("{:?}", state_root_hash_raw);
+/// Render a digest as its raw 64-character hex string
+pub fn digest_to_hex(digest: &Digest) -> Result<String, String> {
+    let hash_str = format!("{:?}", digest);
     let hex_chars: String = hash_str.chars().filter(|c| c.is_ascii_hexdigit()).collect();
 
     if hex_chars.len() == 64 {
@@ -0,0 +1,240 @@
+use std::{future::Future, pin::Pin};
+
+use casper_types::{CLType, Key, NamedKeys, StoredValue};
+
+use crate::{
+    models::schema::contract::{
+        ContractNamedKeyValueDiff, ContractVersionDiff, ContractVersionSchema, ResolvedKeyValue,
+    },
+    services::contract::{get_state_root_hash, get_state_root_hash_at_height},
+};
+
+/// Limits applied while resolving named key values
+#[derive(Debug, Clone, Copy)]
+pub struct KeyResolutionLimits {
+    /// How many `Key` values to follow after the named key itself
+    pub depth: usize,
+    /// Maximum length of a rendered value in bytes
+    pub size_cap: usize,
+}
+
+/// Render a stored value as a JSON value with its type information
+fn render_stored_value(stored_value: &StoredValue) -> (String, Option<String>, serde_json::Value) {
+    match stored_value {
+        StoredValue::CLValue(cl_value) => {
+            // CLValue JSON carries `cl_type`, `bytes` and `parsed`, only the parsed form is readable
+            let value = serde_json::to_value(cl_value)
+                .ok()
+                .and_then(|v| v.get("parsed").cloned())
+                .unwrap_or(serde_json::Value::Null);
+            (
+                "CLValue".to_string(),
+                Some(format!("{:?}", cl_value.cl_type())),
+                value,
+            )
+        }
+        other => (
+            other.type_name(),
+            None,
+            serde_json::to_value(other).unwrap_or(serde_json::Value::Null),
+        ),
+    }
+}
+
+/// Cut a rendered value down to the size cap, keeping it valid JSON
+fn cap_value(value: serde_json::Value, size_cap: usize) -> (serde_json::Value, bool) {
+    let rendered = value.to_string();
+    if rendered.len() <= size_cap {
+        return (value, false);
+    }
+    let mut end = size_cap;
+    while !rendered.is_char_boundary(end) {
+        end -= 1;
+    }
+    (serde_json::Value::String(rendered[..end].to_string()), true)
+}
+
+/// Resolve the value stored behind a key at the given state root, following nested keys up to `depth`
+fn resolve_key_value<'a>(
+    node_address: &'a str,
+    state_root_hash: &'a str,
+    key: Key,
+    depth: usize,
+    size_cap: usize,
+) -> Pin<Box<dyn Future<Output = ResolvedKeyValue> + Send + 'a>> {
+    Box::pin(async move {
+        let key_str = key.to_formatted_string();
+        let response = casper_client::cli::query_global_state(
+            "",
+            node_address,
+            0,
+            "",
+            state_root_hash,
+            &key_str,
+            "",
+        )
+        .await;
+
+        let stored_value = match response {
+            Ok(response) => response.result.stored_value,
+            Err(e) => {
+                return ResolvedKeyValue {
+                    key: key_str,
+                    stored_value_type: "Unresolved".to_string(),
+                    cl_type: None,
+                    value: serde_json::Value::String(e.to_string()),
+                    truncated: false,
+                    resolved: None,
+                };
+            }
+        };
+
+        let (stored_value_type, cl_type, value) = render_stored_value(&stored_value);
+        let (value, truncated) = cap_value(value, size_cap);
+
+        let nested_key = match &stored_value {
+            StoredValue::CLValue(cl_value) if *cl_value.cl_type() == CLType::Key && depth > 0 => {
+                cl_value.clone().into_t::<Key>().ok()
+            }
+            _ => None,
+        };
+        let resolved = match nested_key {
+            Some(nested_key) => Some(Box::new(
+                resolve_key_value(
+                    node_address,
+                    state_root_hash,
+                    nested_key,
+                    depth - 1,
+                    size_cap,
+                )
+                .await,
+            )),
+            None => None,
+        };
+
+        ResolvedKeyValue {
+            key: key_str,
+            stored_value_type,
+            cl_type,
+            value,
+            truncated,
+            resolved,
+        }
+    })
+}
+
+/// Values are compared without the key they live under, a moved value is not a changed value
+pub fn is_same_value(a: &ResolvedKeyValue, b: &ResolvedKeyValue) -> bool {
+    let nested_same = match (&a.resolved, &b.resolved) {
+        (Some(a), Some(b)) => is_same_value(a, b),
+        (None, None) => true,
+        _ => false,
+    };
+    a.stored_value_type == b.stored_value_type
+        && a.cl_type == b.cl_type
+        && a.value == b.value
+        && nested_same
+}
+
+async fn resolve_named_keys(
+    node_address: &str,
+    state_root_hash: &str,
+    named_keys: &NamedKeys,
+    limits: KeyResolutionLimits,
+) -> Vec<(String, ResolvedKeyValue)> {
+    let mut values = vec![];
+    for (name, key) in named_keys.iter() {
+        let value = resolve_key_value(
+            node_address,
+            state_root_hash,
+            *key,
+            limits.depth,
+            limits.size_cap,
+        )
+        .await;
+        values.push((name.clone(), value));
+    }
+    values
+}
+
+/// Get value-level diff of the named keys of two contract versions.
+///
+/// Values of `v2` are read from the current global state. Values of `v1` are read from the state
+/// right before the block that upgraded to `v2` when its block height is known, so values kept
+/// under the same URef across the upgrade can still be compared. Without a known height both
+/// sides are read from the current state.
+pub async fn get_named_key_values_diff(
+    node_address: &str,
+    v1: &ContractVersionSchema,
+    v2: &ContractVersionSchema,
+    limits: KeyResolutionLimits,
+) -> Result<Vec<ContractNamedKeyValueDiff>, String> {
+    let current_state_root_hash = get_state_root_hash(node_address).await?;
+    let v1_state_root_hash = match v2.block_height {
+        Some(height) if height > 0 => {
+            get_state_root_hash_at_height(node_address, Some(height - 1)).await?
+        }
+        _ => current_state_root_hash.clone(),
+    };
+
+    let v1_values =
+        resolve_named_keys(node_address, &v1_state_root_hash, &v1.named_keys, limits).await;
+    let v2_values = resolve_named_keys(
+        node_address,
+        &current_state_root_hash,
+        &v2.named_keys,
+        limits,
+    )
+    .await;
+
+    let mut diffs = vec![];
+    for (name, v1_value) in &v1_values {
+        match v2_values.iter().find(|(n, _)| n == name) {
+            None => diffs.push(ContractNamedKeyValueDiff::Removed {
+                key: name.clone(),
+                value: v1_value.clone(),
+            }),
+            Some((_, v2_value)) if !is_same_value(v1_value, v2_value) => {
+                diffs.push(ContractNamedKeyValueDiff::Modified {
+                    key: name.clone(),
+                    from: v1_value.clone(),
+                    to: v2_value.clone(),
+                })
+            }
+            _ => {}
+        }
+    }
+    for (name, v2_value) in &v2_values {
+        if !v1_values.iter().any(|(n, _)| n == name) {
+            diffs.push(ContractNamedKeyValueDiff::Added {
+                key: name.clone(),
+                value: v2_value.clone(),
+            });
+        }
+    }
+    Ok(diffs)
+}
+
+/// Attach the named key value diff to a version diff, leaving it empty when values cannot be resolved.
+///
+/// Values move with every state change, so they are always resolved again rather than taken from
+/// a stored diff.
+pub async fn attach_named_key_values_diff(
+    node_address: &str,
+    v1: &ContractVersionSchema,
+    v2: &ContractVersionSchema,
+    limits: KeyResolutionLimits,
+    diff: &mut ContractVersionDiff,
+) {
+    diff.named_key_values = vec![];
+    match get_named_key_values_diff(node_address, v1, v2, limits).await {
+        Ok(values) => diff.named_key_values = values,
+        Err(e) => log::warn!(
+            "Failed to resolve named key values for {} v{} -> v{}: {}",
+            v1.contract_package_hash,
+            v1.contract_version,
+            v2.contract_version,
+            e
+        ),
+    }
+}
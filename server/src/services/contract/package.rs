@@ -1,12 +1,17 @@
-use casper_types::{
-    Contract,
-    contracts::{ContractPackage, ContractVersions},
-};
+use casper_client::cli::CliError;
+use casper_types::{Contract, contracts::ContractPackage};
+use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
     models::schema::contract::ContractVersionSchema,
-    services::contract::{get_state_root_hash, metadata::get_contract_version_metadata},
+    services::contract::{
+        get_state_root_hash, is_value_not_found,
+        metadata::{
+            get_contract_version_metadata, get_deploy_metadata, get_indexed_version_metadata,
+        },
+    },
+    utils::strip_hash_prefix,
 };
 
 /// Get contract package by obtaining state root hash and querying global state based
@@ -14,7 +19,7 @@ pub async fn get_contract_package_details(
     node_address: String,
     package_hash: String,
 ) -> Result<ContractPackage, String> {
-    let raw_package_hash = package_hash.strip_prefix("hash-").unwrap_or(&package_hash);
+    let raw_package_hash = strip_hash_prefix(&package_hash);
 
     if raw_package_hash.len() != 64 || !raw_package_hash.chars().all(|c| c.is_ascii_hexdigit()) {
         return Err(format!(
@@ -26,6 +31,26 @@ pub async fn get_contract_package_details(
     let package_hash = format!("hash-{}", raw_package_hash);
     let state_root_hash = get_state_root_hash(&node_address).await?;
 
+    get_contract_package_at_state_root(&node_address, &package_hash, &state_root_hash).await
+}
+
+/// Query contract package from global state as of the given state root hash
+pub async fn get_contract_package_at_state_root(
+    node_address: &str,
+    package_hash: &str,
+    state_root_hash: &str,
+) -> Result<ContractPackage, String> {
+    query_contract_package_at_state_root(node_address, package_hash, state_root_hash)
+        .await?
+        .ok_or_else(|| format!("Contract package {} not found", package_hash))
+}
+
+/// Like [`get_contract_package_at_state_root`], `None` when the package does not exist in that state
+pub async fn query_contract_package_at_state_root(
+    node_address: &str,
+    package_hash: &str,
+    state_root_hash: &str,
+) -> Result<Option<ContractPackage>, String> {
     if state_root_hash.len() != 64 || !state_root_hash.chars().all(|c| c.is_ascii_hexdigit()) {
         return Err(format!(
             "Invalid state root hash returned by node: {}",
@@ -33,41 +58,48 @@ pub async fn get_contract_package_details(
         ));
     }
 
-    let package_response = casper_client::cli::query_global_state(
+    let package_response = match casper_client::cli::query_global_state(
         "",
-        &node_address,
+        node_address,
         0,
         "",
-        &state_root_hash,
-        &package_hash,
+        state_root_hash,
+        package_hash,
         "",
     )
     .await
-    .map_err(|e| e.to_string())?;
+    {
+        Ok(response) => response,
+        Err(CliError::Core(e)) if is_value_not_found(&e) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
     let package_details = package_response.result.stored_value.as_contract_package();
     if let Some(package) = package_details {
-        return Ok(package.to_owned());
+        return Ok(Some(package.to_owned()));
     }
     Err("The provided hash does not correspond to contract package".to_string())
 }
 
+/// Get the details of every version of a package.
+///
+/// Version metadata comes from cspr.live, or from the block indexer when its pool is given.
 pub async fn get_contract_versions_details(
     node_address: &str,
     network: &str,
     user_id: Uuid,
-    versions: &ContractVersions,
+    package: &ContractPackage,
+    indexed: Option<&PgPool>,
 ) -> Result<Vec<ContractVersionSchema>, String> {
     let state_root_hash = get_state_root_hash(node_address).await?;
     let mut contract_versions_data: Vec<ContractVersionSchema> = vec![];
-    for (cv_key, cv_value) in versions {
+    for (cv_key, cv_value) in package.versions() {
         let protocol_major_version = cv_key.protocol_version_major();
         let contract_version = cv_key.contract_version();
-        let mut contract_hash = cv_value.to_formatted_string();
-        let mut contract_hash_value: String = "".to_string();
-        if let Some(stripped) = contract_hash.clone().strip_prefix("contract-") {
-            contract_hash = format!("hash-{}", stripped.to_owned());
-            contract_hash_value = format!("hash-{}", stripped.to_owned());
-        }
+        let contract_hash = format!(
+            "hash-{}",
+            strip_hash_prefix(&cv_value.to_formatted_string())
+        );
+        let contract_hash_value = contract_hash.clone();
         let contract_version_response = casper_client::cli::query_global_state(
             "",
             node_address,
@@ -81,15 +113,59 @@ pub async fn get_contract_versions_details(
         .map_err(|e| e.to_string())?;
         let contract_version_details = contract_version_response.result.stored_value.as_contract();
         if let Some(contract) = contract_version_details {
-            let contract_version_meta =
-                get_contract_version_metadata(network, &contract_hash_value).await?;
             let contract_package_hash = contract.contract_package_hash().to_string();
             let contract_wasm_hash = contract.contract_wasm_hash().to_string();
             let contract_protocol_version = contract.protocol_version().to_string();
             let entry_points = contract.entry_points().clone().take_entry_points();
             let named_keys = contract.named_keys().clone();
-            let disabled = contract_version_meta.is_disabled;
-            let age = contract_version_meta.timestamp;
+            let (disabled, age, deploy_hash, block_height, deploy_meta) = match indexed {
+                Some(pool) => {
+                    let write = get_indexed_version_metadata(
+                        pool,
+                        network,
+                        &contract_package_hash,
+                        &contract_hash_value,
+                    )
+                    .await?;
+                    (
+                        !package.is_version_enabled(*cv_key),
+                        write.timestamp,
+                        Some(write.deploy_hash),
+                        Some(write.block_height),
+                        Some((write.caller_public_key, write.cost, None)),
+                    )
+                }
+                None => {
+                    let contract_version_meta =
+                        get_contract_version_metadata(network, &contract_hash_value).await?;
+                    let deploy_hash = contract_version_meta.deploy_hash.clone();
+                    // Provenance is best effort, a missing deploy must not block registration
+                    let deploy_meta = match &deploy_hash {
+                        Some(hash) => match get_deploy_metadata(network, hash).await {
+                            Ok(meta) => {
+                                Some((meta.caller_public_key, meta.cost, meta.block_height))
+                            }
+                            Err(e) => {
+                                log::warn!(
+                                    "Failed to fetch deploy {} for {}: {}",
+                                    hash,
+                                    contract_hash_value,
+                                    e
+                                );
+                                None
+                            }
+                        },
+                        None => None,
+                    };
+                    (
+                        contract_version_meta.is_disabled,
+                        contract_version_meta.timestamp,
+                        deploy_hash,
+                        contract_version_meta.block_height,
+                        deploy_meta,
+                    )
+                }
+            };
             let contract_version_data = ContractVersionSchema {
                 contract_version,
                 protocol_major_version,
@@ -102,6 +178,10 @@ pub async fn get_contract_versions_details(
                 disabled,
                 user_id,
                 age,
+                deploy_hash,
+                block_height: block_height.or(deploy_meta.as_ref().and_then(|d| d.2)),
+                deployer_public_key: deploy_meta.as_ref().map(|d| d.0.clone()),
+                upgrade_cost: deploy_meta.map(|d| d.1),
             };
             contract_versions_data.push(contract_version_data);
         } else {
@@ -134,3 +214,26 @@ pub async fn get_contract_version_details(
     }
     Err("The provided hash does not correspond to contract version".to_string())
 }
+
+/// Get the latest enabled contract version of a contract package
+pub async fn get_latest_enabled_contract(
+    node_address: &str,
+    package_hash: &str,
+) -> Result<Contract, String> {
+    let package =
+        get_contract_package_details(node_address.to_string(), package_hash.to_string()).await?;
+
+    let contract_hash = package
+        .versions()
+        .iter()
+        .rev()
+        .find(|(key, _)| package.is_version_enabled(**key))
+        .map(|(_, hash)| hash)
+        .ok_or("No enabled contract version found for contract package")?;
+
+    let contract_hash = format!(
+        "hash-{}",
+        strip_hash_prefix(&contract_hash.to_formatted_string())
+    );
+    get_contract_version_details(node_address.to_string(), contract_hash).await
+}
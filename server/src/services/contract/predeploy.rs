@@ -0,0 +1,215 @@
+use casper_types::{
+    CLType, EntryPointAccess, EntryPointType, Group, Parameter, contracts::EntryPoint,
+};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::schema::{
+        contract::{ContractVersionDiff, ContractVersionSchema},
+        odra::OdraContractSchema,
+    },
+    services::contract::{
+        diff::compute_version_diff,
+        schema::{get_version_schema, set_schema_diff},
+        wasm::{get_contract_wasm, get_wasm_module_diff, parse_wasm_module},
+    },
+};
+
+/// Placeholder contract hash of a version that only exists locally
+pub const LOCAL_CONTRACT_HASH: &str = "local";
+
+/// Convert a named CL type of the contract schema to the CL type the installed entry point carries.
+///
+/// Custom types are serialized to bytes by Odra and appear as `Any` on chain.
+pub fn named_cl_type_to_cl_type(ty: &serde_json::Value) -> Result<CLType, String> {
+    let nested = |value: &serde_json::Value| named_cl_type_to_cl_type(value).map(Box::new);
+
+    if let Some(name) = ty.as_str() {
+        return match name {
+            "Bool" => Ok(CLType::Bool),
+            "I32" => Ok(CLType::I32),
+            "I64" => Ok(CLType::I64),
+            "U8" => Ok(CLType::U8),
+            "U32" => Ok(CLType::U32),
+            "U64" => Ok(CLType::U64),
+            "U128" => Ok(CLType::U128),
+            "U256" => Ok(CLType::U256),
+            "U512" => Ok(CLType::U512),
+            "Unit" => Ok(CLType::Unit),
+            "String" => Ok(CLType::String),
+            "Key" => Ok(CLType::Key),
+            "URef" => Ok(CLType::URef),
+            "PublicKey" => Ok(CLType::PublicKey),
+            other => Err(format!("Unknown schema type: {}", other)),
+        };
+    }
+
+    let (name, inner) = ty
+        .as_object()
+        .filter(|o| o.len() == 1)
+        .and_then(|o| o.iter().next())
+        .ok_or_else(|| format!("Invalid schema type: {}", ty))?;
+    let items = |count: usize| -> Result<Vec<Box<CLType>>, String> {
+        let items = inner
+            .as_array()
+            .filter(|a| a.len() == count)
+            .ok_or_else(|| format!("{} expects {} types", name, count))?;
+        items.iter().map(nested).collect()
+    };
+
+    match name.as_str() {
+        "Option" => Ok(CLType::Option(nested(inner)?)),
+        "List" => Ok(CLType::List(nested(inner)?)),
+        "ByteArray" => Ok(CLType::ByteArray(
+            inner.as_u64().ok_or("ByteArray expects a length")? as u32,
+        )),
+        "Result" => Ok(CLType::Result {
+            ok: nested(&inner["ok"])?,
+            err: nested(&inner["err"])?,
+        }),
+        "Map" => Ok(CLType::Map {
+            key: nested(&inner["key"])?,
+            value: nested(&inner["value"])?,
+        }),
+        "Tuple1" => {
+            let [a] = <[Box<CLType>; 1]>::try_from(items(1)?).map_err(|_| "Invalid Tuple1")?;
+            Ok(CLType::Tuple1([a]))
+        }
+        "Tuple2" => {
+            let [a, b] = <[Box<CLType>; 2]>::try_from(items(2)?).map_err(|_| "Invalid Tuple2")?;
+            Ok(CLType::Tuple2([a, b]))
+        }
+        "Tuple3" => {
+            let [a, b, c] =
+                <[Box<CLType>; 3]>::try_from(items(3)?).map_err(|_| "Invalid Tuple3")?;
+            Ok(CLType::Tuple3([a, b, c]))
+        }
+        "Custom" => Ok(CLType::Any),
+        other => Err(format!("Unknown schema type: {}", other)),
+    }
+}
+
+fn schema_access(access: &serde_json::Value) -> Result<EntryPointAccess, String> {
+    if access.as_str() == Some("public") {
+        return Ok(EntryPointAccess::Public);
+    }
+    let groups = access
+        .get("groups")
+        .and_then(|g| g.as_array())
+        .ok_or_else(|| format!("Invalid entry point access: {}", access))?;
+    Ok(EntryPointAccess::Groups(
+        groups
+            .iter()
+            .filter_map(|g| g.as_str())
+            .map(Group::new)
+            .collect(),
+    ))
+}
+
+/// Build the entry points a contract installed from the schema would expose
+pub fn get_schema_entry_points(schema: &OdraContractSchema) -> Result<Vec<EntryPoint>, String> {
+    schema
+        .entry_points
+        .iter()
+        .map(|ep| {
+            let args = ep
+                .arguments
+                .iter()
+                .map(|arg| {
+                    let cl_type = named_cl_type_to_cl_type(&arg.ty)?;
+                    // Optional arguments are read as `Option`s by the contract
+                    let cl_type = match cl_type {
+                        CLType::Option(_) => cl_type,
+                        _ if arg.optional => CLType::Option(Box::new(cl_type)),
+                        _ => cl_type,
+                    };
+                    Ok(Parameter::new(&arg.name, cl_type))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let entry_point_type = if ep.is_contract_context {
+                EntryPointType::Called
+            } else {
+                EntryPointType::Caller
+            };
+            Ok(EntryPoint::new(
+                ep.name.clone(),
+                args,
+                named_cl_type_to_cl_type(&ep.return_ty)?,
+                schema_access(&ep.access)?,
+                entry_point_type,
+            ))
+        })
+        .collect()
+}
+
+/// Diff a locally built contract against the deployed version it would upgrade.
+///
+/// The local version takes over the named keys of the deployed one, as they are only known once the
+/// upgrade is installed, so the diff covers entry points and Wasm, plus events and errors when the
+/// deployed version has a schema too.
+pub async fn get_local_version_diff(
+    pool: &PgPool,
+    node_address: &str,
+    user_id: &Uuid,
+    deployed: &ContractVersionSchema,
+    wasm: &[u8],
+    schema: &OdraContractSchema,
+) -> Result<ContractVersionDiff, String> {
+    let local_module = parse_wasm_module(wasm)?;
+    let deployed_module =
+        match get_contract_wasm(pool, node_address, &deployed.contract_wasm_hash).await {
+            Ok(deployed_wasm) => Some(parse_wasm_module(&deployed_wasm)?),
+            Err(e) => {
+                log::warn!(
+                    "Failed to fetch deployed wasm {}: {}",
+                    deployed.contract_wasm_hash,
+                    e
+                );
+                None
+            }
+        };
+    // The wasm hash of an installed version is an address rather than a hash of its bytes, an
+    // unchanged rebuild keeps the deployed one so the diff does not report a Wasm change
+    let local_wasm_hash = match &deployed_module {
+        Some(deployed_module) if deployed_module.module_hash == local_module.module_hash => {
+            deployed.contract_wasm_hash.clone()
+        }
+        _ => local_module.module_hash.clone(),
+    };
+
+    let local = ContractVersionSchema {
+        contract_version: deployed.contract_version + 1,
+        contract_hash: LOCAL_CONTRACT_HASH.to_string(),
+        contract_wasm_hash: local_wasm_hash.clone(),
+        entry_points: get_schema_entry_points(schema)?,
+        disabled: false,
+        age: Utc::now(),
+        deploy_hash: None,
+        block_height: None,
+        deployer_public_key: None,
+        upgrade_cost: None,
+        ..deployed.clone()
+    };
+    let mut diff = compute_version_diff(deployed, &local);
+    diff.wasm = deployed_module.map(|deployed_module| {
+        get_wasm_module_diff(
+            &deployed.contract_wasm_hash,
+            &deployed_module,
+            &local_wasm_hash,
+            &local_module,
+        )
+    });
+
+    match get_version_schema(pool, node_address, user_id, deployed).await {
+        Ok(Some(deployed_schema)) => set_schema_diff(&mut diff, &deployed_schema.schema, schema),
+        Ok(None) => {}
+        Err(e) => log::warn!(
+            "Failed to get schema of deployed version {}: {}",
+            deployed.contract_version,
+            e
+        ),
+    }
+    Ok(diff)
+}
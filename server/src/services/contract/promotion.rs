@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::schema::{
+        alert::AlertSchema,
+        contract::ContractVersionSchema,
+        promotion::{ContractPackageLinkSchema, PromotionMatch, PromotionStatus, VersionPromotion},
+    },
+    services::{
+        contract::wasm::get_contract_wasm_module_hash, database::contract::get_contract_versions,
+    },
+    utils::strip_hash_prefix,
+};
+
+pub const PROMOTION_DRIFT_ALERT: &str = "PromotionDrift";
+
+fn has_same_entry_points(a: &ContractVersionSchema, b: &ContractVersionSchema) -> bool {
+    a.entry_points.len() == b.entry_points.len()
+        && a.entry_points
+            .iter()
+            .all(|ep| b.entry_points.iter().any(|other| other == ep))
+}
+
+/// Match mainnet versions of a linked package pair to the testnet versions they were promoted from.
+///
+/// Contract wasm hashes are addresses assigned at install, so versions are paired by the hashes of
+/// their module bytes, mapped from the normalized wasm hash in `module_hashes`. A module match is
+/// preferred over an entry point match, and among equal matches the latest testnet version wins.
+pub fn get_promotion_status(
+    link: &ContractPackageLinkSchema,
+    testnet_versions: &[ContractVersionSchema],
+    mainnet_versions: &[ContractVersionSchema],
+    module_hashes: &HashMap<String, String>,
+) -> PromotionStatus {
+    let module_hash =
+        |v: &ContractVersionSchema| module_hashes.get(&strip_hash_prefix(&v.contract_wasm_hash));
+    let mut testnet_versions: Vec<&ContractVersionSchema> = testnet_versions.iter().collect();
+    testnet_versions.sort_by_key(|v| std::cmp::Reverse(v.contract_version));
+    let mut mainnet_versions: Vec<&ContractVersionSchema> = mainnet_versions.iter().collect();
+    mainnet_versions.sort_by_key(|v| v.contract_version);
+
+    let mut promotions = vec![];
+    let mut untested_mainnet_versions = vec![];
+    for mainnet in &mainnet_versions {
+        let mainnet_module_hash = module_hash(mainnet);
+        let by_wasm = testnet_versions
+            .iter()
+            .find(|t| mainnet_module_hash.is_some() && module_hash(t) == mainnet_module_hash)
+            .map(|t| (t, PromotionMatch::WasmHash));
+        let matched = by_wasm.or_else(|| {
+            testnet_versions
+                .iter()
+                .find(|t| has_same_entry_points(t, mainnet))
+                .map(|t| (t, PromotionMatch::EntryPoints))
+        });
+        match matched {
+            Some((testnet, matched_by)) => promotions.push(VersionPromotion {
+                testnet_version: testnet.contract_version,
+                mainnet_version: mainnet.contract_version,
+                matched_by,
+            }),
+            None => untested_mainnet_versions.push(mainnet.contract_version),
+        }
+    }
+
+    let mut unpromoted_testnet_versions: Vec<u32> = testnet_versions
+        .iter()
+        .map(|t| t.contract_version)
+        .filter(|v| !promotions.iter().any(|p| p.testnet_version == *v))
+        .collect();
+    unpromoted_testnet_versions.sort();
+
+    let in_sync = untested_mainnet_versions.is_empty()
+        && promotions
+            .iter()
+            .all(|p| p.matched_by == PromotionMatch::WasmHash);
+
+    PromotionStatus {
+        testnet_package_hash: link.testnet_package_hash.clone(),
+        mainnet_package_hash: link.mainnet_package_hash.clone(),
+        promotions,
+        unpromoted_testnet_versions,
+        untested_mainnet_versions,
+        in_sync,
+    }
+}
+
+/// Alerts for mainnet versions that drifted from what was tested, raised against the mainnet package
+pub fn get_promotion_drift_alerts(user_id: Uuid, status: &PromotionStatus) -> Vec<AlertSchema> {
+    let mut alerts = vec![];
+    for version in &status.untested_mainnet_versions {
+        alerts.push(AlertSchema::new(
+            user_id,
+            status.mainnet_package_hash.clone(),
+            PROMOTION_DRIFT_ALERT,
+            format!(
+                "Mainnet version {} has no tested counterpart on testnet package {}",
+                version, status.testnet_package_hash
+            ),
+            serde_json::json!({
+                "mainnet_version": version,
+                "testnet_package_hash": status.testnet_package_hash,
+            }),
+            format!(
+                "promotion-untested:{}:{}",
+                status.mainnet_package_hash, version
+            ),
+        ));
+    }
+    for promotion in &status.promotions {
+        if promotion.matched_by != PromotionMatch::EntryPoints {
+            continue;
+        }
+        alerts.push(AlertSchema::new(
+            user_id,
+            status.mainnet_package_hash.clone(),
+            PROMOTION_DRIFT_ALERT,
+            format!(
+                "Mainnet version {} exposes the same entry points as testnet version {} but was built from different Wasm",
+                promotion.mainnet_version, promotion.testnet_version
+            ),
+            serde_json::json!({
+                "mainnet_version": promotion.mainnet_version,
+                "testnet_version": promotion.testnet_version,
+                "testnet_package_hash": status.testnet_package_hash,
+            }),
+            format!(
+                "promotion-wasm-mismatch:{}:{}",
+                status.mainnet_package_hash, promotion.mainnet_version
+            ),
+        ));
+    }
+    alerts
+}
+
+/// Evaluate the promotion status of a linked package pair from the versions known for both sides,
+/// fetching the Wasm of each version from the node of its network
+pub async fn evaluate_promotion(
+    pool: &PgPool,
+    (testnet_node_address, mainnet_node_address): (&str, &str),
+    link: &ContractPackageLinkSchema,
+) -> Result<PromotionStatus, String> {
+    let testnet_versions = get_contract_versions(pool, &link.testnet_package_hash, &link.user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mainnet_versions = get_contract_versions(pool, &link.mainnet_package_hash, &link.user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // A module that cannot be fetched fails the evaluation rather than pairing its version by entry
+    // points, which would report drift that is not there
+    let mut module_hashes = HashMap::new();
+    for (versions, node_address) in [
+        (&testnet_versions, testnet_node_address),
+        (&mainnet_versions, mainnet_node_address),
+    ] {
+        for version in versions {
+            let wasm_hash = strip_hash_prefix(&version.contract_wasm_hash);
+            if module_hashes.contains_key(&wasm_hash) {
+                continue;
+            }
+            let module_hash = get_contract_wasm_module_hash(pool, node_address, &wasm_hash)
+                .await
+                .map_err(|e| format!("Failed to fetch wasm {}: {}", wasm_hash, e))?;
+            module_hashes.insert(wasm_hash, module_hash);
+        }
+    }
+
+    Ok(get_promotion_status(
+        link,
+        &testnet_versions,
+        &mainnet_versions,
+        &module_hashes,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{
+        CLType, EntryPointAccess, EntryPointType, NamedKeys, contracts::EntryPoint,
+    };
+    use chrono::Utc;
+
+    use super::*;
+
+    fn version(
+        contract_version: u32,
+        wasm_hash: &str,
+        entry_points: &[&str],
+    ) -> ContractVersionSchema {
+        ContractVersionSchema {
+            protocol_major_version: 2,
+            contract_version,
+            contract_package_hash: "package".to_string(),
+            contract_hash: format!("contract-{}", wasm_hash),
+            contract_wasm_hash: format!("contract-wasm-{}", wasm_hash),
+            user_id: Uuid::nil(),
+            protocol_version: "2.0.0".to_string(),
+            entry_points: entry_points
+                .iter()
+                .map(|name| {
+                    EntryPoint::new(
+                        name.to_string(),
+                        vec![],
+                        CLType::Unit,
+                        EntryPointAccess::Public,
+                        EntryPointType::Called,
+                    )
+                })
+                .collect(),
+            named_keys: NamedKeys::new(),
+            disabled: false,
+            age: Utc::now(),
+            deploy_hash: None,
+            block_height: None,
+            deployer_public_key: None,
+            upgrade_cost: None,
+        }
+    }
+
+    fn link() -> ContractPackageLinkSchema {
+        ContractPackageLinkSchema {
+            user_id: Uuid::nil(),
+            testnet_package_hash: "testnet".to_string(),
+            mainnet_package_hash: "mainnet".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn module_hashes(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(wasm_hash, module_hash)| (wasm_hash.to_string(), module_hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn pairs_versions_by_module_bytes_across_wasm_addresses() {
+        let testnet = [version(1, "t1", &["transfer"])];
+        let mainnet = [version(1, "m1", &["transfer"])];
+        let hashes = module_hashes(&[("t1", "module-a"), ("m1", "module-a")]);
+
+        let status = get_promotion_status(&link(), &testnet, &mainnet, &hashes);
+        assert_eq!(status.promotions.len(), 1);
+        assert_eq!(status.promotions[0].matched_by, PromotionMatch::WasmHash);
+        assert!(status.in_sync);
+        assert!(get_promotion_drift_alerts(Uuid::nil(), &status).is_empty());
+    }
+
+    #[test]
+    fn prefers_the_latest_testnet_version_with_the_same_module() {
+        let testnet = [
+            version(1, "t1", &["transfer"]),
+            version(2, "t2", &["transfer"]),
+        ];
+        let mainnet = [version(1, "m1", &["transfer"])];
+        let hashes = module_hashes(&[("t1", "module-a"), ("t2", "module-a"), ("m1", "module-a")]);
+
+        let status = get_promotion_status(&link(), &testnet, &mainnet, &hashes);
+        assert_eq!(status.promotions[0].testnet_version, 2);
+        assert_eq!(status.unpromoted_testnet_versions, vec![1]);
+    }
+
+    #[test]
+    fn flags_different_modules_behind_the_same_entry_points() {
+        let testnet = [version(1, "t1", &["transfer"])];
+        let mainnet = [version(1, "m1", &["transfer"])];
+        let hashes = module_hashes(&[("t1", "module-a"), ("m1", "module-b")]);
+
+        let status = get_promotion_status(&link(), &testnet, &mainnet, &hashes);
+        assert_eq!(status.promotions[0].matched_by, PromotionMatch::EntryPoints);
+        assert!(!status.in_sync);
+        let alerts = get_promotion_drift_alerts(Uuid::nil(), &status);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].dedup_key, "promotion-wasm-mismatch:mainnet:1");
+    }
+
+    #[test]
+    fn flags_untested_mainnet_versions() {
+        let testnet = [version(1, "t1", &["transfer"])];
+        let mainnet = [version(1, "m1", &["transfer", "mint"])];
+        let hashes = module_hashes(&[("t1", "module-a"), ("m1", "module-b")]);
+
+        let status = get_promotion_status(&link(), &testnet, &mainnet, &hashes);
+        assert!(status.promotions.is_empty());
+        assert_eq!(status.untested_mainnet_versions, vec![1]);
+        let alerts = get_promotion_drift_alerts(Uuid::nil(), &status);
+        assert_eq!(alerts[0].dedup_key, "promotion-untested:mainnet:1");
+    }
+}
@@ -0,0 +1,476 @@
+use casper_types::contracts::EntryPoint;
+
+use crate::{
+    models::schema::{
+        contract::{
+            ContractEntryPointDiff, ContractNamedKeyValueDiff, ContractNamedKeysDiff,
+            ContractVersionDiff,
+        },
+        rules::{AppliedDiffRule, CollapsedDiff, DiffRuleAction, DiffRuleSchema, DiffRuleTarget},
+    },
+    services::contract::{
+        compat::assess_upgrade, diff::get_entry_point_changes, named_keys::is_same_value,
+    },
+};
+
+/// Match `text` against a glob pattern where `*` matches any run of characters and `?` a single one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` swallow one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Name a renamed entry should be paired with, substituting the text matched by a single `*`
+fn get_renamed(rule: &DiffRuleSchema, name: &str) -> Option<String> {
+    let replacement = rule.replacement.as_deref()?;
+    if !glob_match(&rule.pattern, name) {
+        return None;
+    }
+    match rule.pattern.split_once('*') {
+        Some((prefix, suffix)) if !suffix.contains('*') && replacement.contains('*') => {
+            let captured = name
+                .get(prefix.len()..name.len().saturating_sub(suffix.len()))
+                .unwrap_or_default();
+            Some(replacement.replacen('*', captured, 1))
+        }
+        _ => Some(replacement.to_string()),
+    }
+}
+
+fn entry_point_diff_name(diff: &ContractEntryPointDiff) -> &str {
+    match diff {
+        ContractEntryPointDiff::Added(ep) | ContractEntryPointDiff::Removed(ep) => ep.name(),
+        ContractEntryPointDiff::Modified { to, .. } => to.name(),
+    }
+}
+
+fn named_key_diff_name(diff: &ContractNamedKeysDiff) -> &str {
+    match diff {
+        ContractNamedKeysDiff::Added { key, .. }
+        | ContractNamedKeysDiff::Removed { key, .. }
+        | ContractNamedKeysDiff::Modified { key, .. } => key,
+    }
+}
+
+fn named_key_value_diff_name(diff: &ContractNamedKeyValueDiff) -> &str {
+    match diff {
+        ContractNamedKeyValueDiff::Added { key, .. }
+        | ContractNamedKeyValueDiff::Removed { key, .. }
+        | ContractNamedKeyValueDiff::Modified { key, .. } => key,
+    }
+}
+
+fn rename_entry_points(diff: &mut ContractVersionDiff, rule: &DiffRuleSchema) -> u32 {
+    let mut matched = 0;
+    let removed: Vec<(EntryPoint, String)> = diff
+        .entry_points
+        .iter()
+        .filter_map(|d| match d {
+            ContractEntryPointDiff::Removed(ep) => {
+                get_renamed(rule, ep.name()).map(|renamed| (ep.clone(), renamed))
+            }
+            _ => None,
+        })
+        .collect();
+
+    for (from, renamed) in removed {
+        let to = diff.entry_points.iter().find_map(|d| match d {
+            ContractEntryPointDiff::Added(ep) if ep.name() == renamed => Some(ep.clone()),
+            _ => None,
+        });
+        let Some(to) = to else {
+            continue;
+        };
+        matched += 1;
+        diff.entry_points.retain(|d| match d {
+            ContractEntryPointDiff::Removed(ep) => ep.name() != from.name(),
+            ContractEntryPointDiff::Added(ep) => ep.name() != to.name(),
+            _ => true,
+        });
+        let changes = get_entry_point_changes(&from, &to);
+        if !changes.is_empty() {
+            diff.entry_points
+                .push(ContractEntryPointDiff::Modified { from, to, changes });
+        }
+    }
+    matched
+}
+
+fn rename_named_keys(diff: &mut ContractVersionDiff, rule: &DiffRuleSchema) -> u32 {
+    let mut matched = 0;
+    let removed: Vec<(String, String)> = diff
+        .named_keys
+        .iter()
+        .filter_map(|d| match d {
+            ContractNamedKeysDiff::Removed { key, .. } => {
+                get_renamed(rule, key).map(|renamed| (key.clone(), renamed))
+            }
+            _ => None,
+        })
+        .collect();
+
+    for (from_key, to_key) in removed {
+        let pair = (
+            diff.named_keys.iter().find_map(|d| match d {
+                ContractNamedKeysDiff::Removed { key, value } if *key == from_key => Some(*value),
+                _ => None,
+            }),
+            diff.named_keys.iter().find_map(|d| match d {
+                ContractNamedKeysDiff::Added { key, value } if *key == to_key => Some(*value),
+                _ => None,
+            }),
+        );
+        let (Some(from), Some(to)) = pair else {
+            continue;
+        };
+        matched += 1;
+        diff.named_keys.retain(|d| match d {
+            ContractNamedKeysDiff::Removed { key, .. } => *key != from_key,
+            ContractNamedKeysDiff::Added { key, .. } => *key != to_key,
+            _ => true,
+        });
+        if from != to {
+            diff.named_keys.push(ContractNamedKeysDiff::Modified {
+                key: to_key.clone(),
+                from,
+                to,
+            });
+        }
+
+        let values = (
+            diff.named_key_values.iter().find_map(|d| match d {
+                ContractNamedKeyValueDiff::Removed { key, value } if *key == from_key => {
+                    Some(value.clone())
+                }
+                _ => None,
+            }),
+            diff.named_key_values.iter().find_map(|d| match d {
+                ContractNamedKeyValueDiff::Added { key, value } if *key == to_key => {
+                    Some(value.clone())
+                }
+                _ => None,
+            }),
+        );
+        if let (Some(from), Some(to)) = values {
+            diff.named_key_values.retain(|d| match d {
+                ContractNamedKeyValueDiff::Removed { key, .. } => *key != from_key,
+                ContractNamedKeyValueDiff::Added { key, .. } => *key != to_key,
+                _ => true,
+            });
+            if !is_same_value(&from, &to) {
+                diff.named_key_values
+                    .push(ContractNamedKeyValueDiff::Modified {
+                        key: to_key,
+                        from,
+                        to,
+                    });
+            }
+        }
+    }
+    matched
+}
+
+fn ignore_entries(diff: &mut ContractVersionDiff, rule: &DiffRuleSchema) -> u32 {
+    let mut matched = 0;
+    let mut keep = |name: &str| {
+        let ignored = glob_match(&rule.pattern, name);
+        if ignored {
+            matched += 1;
+        }
+        !ignored
+    };
+    match rule.target {
+        DiffRuleTarget::EntryPoint => {
+            diff.entry_points.retain(|d| keep(entry_point_diff_name(d)));
+            if let Some(wasm) = diff.wasm.as_mut() {
+                wasm.entry_points.retain(|d| keep(&d.entry_point));
+            }
+        }
+        DiffRuleTarget::NamedKey => {
+            diff.named_keys.retain(|d| keep(named_key_diff_name(d)));
+            diff.named_key_values
+                .retain(|d| keep(named_key_value_diff_name(d)));
+        }
+    }
+    matched
+}
+
+fn collapse_entries(diff: &mut ContractVersionDiff, rule: &DiffRuleSchema) -> u32 {
+    let mut collapsed = CollapsedDiff {
+        rule_id: rule.id,
+        target: rule.target,
+        pattern: rule.pattern.clone(),
+        added: 0,
+        removed: 0,
+        modified: 0,
+    };
+    match rule.target {
+        DiffRuleTarget::EntryPoint => diff.entry_points.retain(|d| {
+            if !glob_match(&rule.pattern, entry_point_diff_name(d)) {
+                return true;
+            }
+            match d {
+                ContractEntryPointDiff::Added(_) => collapsed.added += 1,
+                ContractEntryPointDiff::Removed(_) => collapsed.removed += 1,
+                ContractEntryPointDiff::Modified { .. } => collapsed.modified += 1,
+            }
+            false
+        }),
+        DiffRuleTarget::NamedKey => {
+            diff.named_keys.retain(|d| {
+                if !glob_match(&rule.pattern, named_key_diff_name(d)) {
+                    return true;
+                }
+                match d {
+                    ContractNamedKeysDiff::Added { .. } => collapsed.added += 1,
+                    ContractNamedKeysDiff::Removed { .. } => collapsed.removed += 1,
+                    ContractNamedKeysDiff::Modified { .. } => collapsed.modified += 1,
+                }
+                false
+            });
+            // Values behind collapsed keys are summarized by the key counts already
+            diff.named_key_values
+                .retain(|d| !glob_match(&rule.pattern, named_key_value_diff_name(d)));
+        }
+    }
+
+    let matched = collapsed.added + collapsed.removed + collapsed.modified;
+    if matched > 0 {
+        match diff.collapsed.iter_mut().find(|c| c.rule_id == rule.id) {
+            Some(existing) => {
+                existing.added += collapsed.added;
+                existing.removed += collapsed.removed;
+                existing.modified += collapsed.modified;
+            }
+            None => diff.collapsed.push(collapsed),
+        }
+    }
+    matched
+}
+
+/// Apply diff rules to a version diff and re-assess the upgrade on the remaining changes.
+///
+/// Renames are applied first so that ignore and collapse rules see the paired entries, every rule
+/// that affected at least one entry is recorded in `applied_rules`. Applying the rules again after
+/// attaching more parts to the diff adds to the existing records instead of repeating them.
+pub fn apply_diff_rules(diff: &mut ContractVersionDiff, rules: &[DiffRuleSchema]) {
+    let ordered = [
+        DiffRuleAction::Rename,
+        DiffRuleAction::Ignore,
+        DiffRuleAction::Collapse,
+    ];
+    for action in ordered {
+        for rule in rules.iter().filter(|r| r.action == action) {
+            let matched = match (rule.action, rule.target) {
+                (DiffRuleAction::Rename, DiffRuleTarget::EntryPoint) => {
+                    rename_entry_points(diff, rule)
+                }
+                (DiffRuleAction::Rename, DiffRuleTarget::NamedKey) => rename_named_keys(diff, rule),
+                (DiffRuleAction::Ignore, _) => ignore_entries(diff, rule),
+                (DiffRuleAction::Collapse, _) => collapse_entries(diff, rule),
+            };
+            if matched == 0 {
+                continue;
+            }
+            match diff.applied_rules.iter_mut().find(|a| a.rule_id == rule.id) {
+                Some(applied) => applied.matched += matched,
+                None => diff.applied_rules.push(AppliedDiffRule {
+                    rule_id: rule.id,
+                    target: rule.target,
+                    action: rule.action,
+                    pattern: rule.pattern.clone(),
+                    matched,
+                }),
+            }
+        }
+    }
+
+    if !diff.applied_rules.is_empty() {
+        diff.assessment = Some(assess_upgrade(diff));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{CLType, EntryPointAccess, EntryPointType, Key, Parameter};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::schema::contract::ContractEntryPointChange;
+
+    fn diff() -> ContractVersionDiff {
+        let meta = serde_json::json!({
+            "contract_hash": "hash-a",
+            "timestamp": "2026-01-01T00:00:00Z",
+            "contract_version": 1,
+            "is_disabled": false,
+            "wasm_hash": "hash-b"
+        });
+        serde_json::from_value(serde_json::json!({
+            "v1": meta,
+            "v2": meta,
+            "contract_package_hash": "hash-c",
+            "entry_points": [],
+            "named_keys": []
+        }))
+        .unwrap()
+    }
+
+    fn rule(
+        target: DiffRuleTarget,
+        action: DiffRuleAction,
+        pattern: &str,
+        replacement: Option<&str>,
+    ) -> DiffRuleSchema {
+        DiffRuleSchema {
+            id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            package_hash: None,
+            target,
+            action,
+            pattern: pattern.to_string(),
+            replacement: replacement.map(str::to_string),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn entry_point(name: &str, arg: &str) -> EntryPoint {
+        EntryPoint::new(
+            name,
+            vec![Parameter::new(arg, CLType::U256)],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Called,
+        )
+    }
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("get_*", "get_balance"));
+        assert!(glob_match("get_*", "get_"));
+        assert!(glob_match("*_v?", "transfer_v2"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("get_*", "set_balance"));
+        assert!(!glob_match("*_v?", "transfer_v10"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+    }
+
+    #[test]
+    fn renames_entry_points_into_modified_ones() {
+        let mut diff = diff();
+        diff.entry_points = vec![
+            ContractEntryPointDiff::Removed(entry_point("transfer_v1", "amount")),
+            ContractEntryPointDiff::Added(entry_point("transfer_v2", "value")),
+        ];
+        let rename = rule(
+            DiffRuleTarget::EntryPoint,
+            DiffRuleAction::Rename,
+            "*_v1",
+            Some("*_v2"),
+        );
+        apply_diff_rules(&mut diff, std::slice::from_ref(&rename));
+
+        assert_eq!(diff.entry_points.len(), 1);
+        let ContractEntryPointDiff::Modified { changes, .. } = &diff.entry_points[0] else {
+            panic!("Expected a modified entry point");
+        };
+        assert_eq!(
+            changes[0],
+            ContractEntryPointChange::ParameterRenamed {
+                from: "amount".to_string(),
+                to: "value".to_string(),
+                cl_type: CLType::U256,
+            }
+        );
+        assert_eq!(diff.applied_rules[0].rule_id, rename.id);
+        assert_eq!(diff.applied_rules[0].matched, 1);
+        assert!(diff.assessment.is_some());
+    }
+
+    #[test]
+    fn drops_renamed_named_keys_keeping_their_value() {
+        let mut diff = diff();
+        let value = Key::Hash([1; 32]);
+        diff.named_keys = vec![
+            ContractNamedKeysDiff::Removed {
+                key: "balances".to_string(),
+                value,
+            },
+            ContractNamedKeysDiff::Added {
+                key: "balances_v2".to_string(),
+                value,
+            },
+        ];
+        let rename = rule(
+            DiffRuleTarget::NamedKey,
+            DiffRuleAction::Rename,
+            "balances",
+            Some("balances_v2"),
+        );
+        apply_diff_rules(&mut diff, &[rename]);
+        assert!(diff.named_keys.is_empty());
+        assert_eq!(diff.applied_rules[0].matched, 1);
+    }
+
+    #[test]
+    fn ignores_and_collapses_after_renaming() {
+        let mut diff = diff();
+        diff.entry_points = vec![
+            ContractEntryPointDiff::Added(entry_point("debug_dump", "a")),
+            ContractEntryPointDiff::Added(entry_point("admin_pause", "a")),
+            ContractEntryPointDiff::Removed(entry_point("admin_stop", "a")),
+            ContractEntryPointDiff::Added(entry_point("transfer", "a")),
+        ];
+        let collapse = rule(
+            DiffRuleTarget::EntryPoint,
+            DiffRuleAction::Collapse,
+            "admin_*",
+            None,
+        );
+        let ignore = rule(
+            DiffRuleTarget::EntryPoint,
+            DiffRuleAction::Ignore,
+            "debug_*",
+            None,
+        );
+        let unused = rule(
+            DiffRuleTarget::NamedKey,
+            DiffRuleAction::Ignore,
+            "cache_*",
+            None,
+        );
+        apply_diff_rules(&mut diff, &[collapse.clone(), ignore, unused]);
+
+        assert_eq!(diff.entry_points.len(), 1);
+        assert_eq!(entry_point_diff_name(&diff.entry_points[0]), "transfer");
+        assert_eq!(diff.collapsed.len(), 1);
+        assert_eq!(diff.collapsed[0].rule_id, collapse.id);
+        assert_eq!((diff.collapsed[0].added, diff.collapsed[0].removed), (1, 1));
+        // Ignore rules run before collapse rules, the rule matching nothing is not recorded
+        let applied: Vec<DiffRuleAction> = diff.applied_rules.iter().map(|a| a.action).collect();
+        assert_eq!(
+            applied,
+            vec![DiffRuleAction::Ignore, DiffRuleAction::Collapse]
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::schema::{
+        contract::{
+            ContractErrorDiff, ContractEventDiff, ContractVersionDiff, ContractVersionSchema,
+        },
+        odra::{ContractVersionOdraSchema, OdraContractSchema, OdraSchemaSource},
+    },
+    services::{
+        contract::{
+            compat::assess_upgrade,
+            wasm::{get_contract_wasm, get_wasm_custom_section},
+        },
+        database::schema::{get_contract_schema, upsert_contract_schema},
+    },
+};
+
+/// Name of the custom section Odra contracts can embed their schema in
+pub const SCHEMA_CUSTOM_SECTION: &str = "casper_contract_schema";
+
+/// Find the definition of a named type in the `types` list of a contract schema.
+///
+/// Types are listed as `{"struct": {"name": ..}}` or `{"enum": {"name": ..}}` objects.
+fn find_schema_type<'a>(types: &'a serde_json::Value, name: &str) -> Option<&'a serde_json::Value> {
+    types.as_array()?.iter().find(|ty| {
+        ty.as_object()
+            .and_then(|o| o.values().next())
+            .and_then(|inner| inner.get("name"))
+            .and_then(|n| n.as_str())
+            == Some(name)
+    })
+}
+
+fn event_type(schema: &OdraContractSchema, ty: &str) -> serde_json::Value {
+    find_schema_type(&schema.types, ty)
+        .cloned()
+        .unwrap_or_else(|| serde_json::Value::String(ty.to_string()))
+}
+
+pub fn get_event_diff(
+    from: &OdraContractSchema,
+    to: &OdraContractSchema,
+) -> Vec<ContractEventDiff> {
+    let mut diffs = vec![];
+    for event in &from.events {
+        let from_ty = event_type(from, &event.ty);
+        match to.events.iter().find(|e| e.name == event.name) {
+            Some(other) => {
+                let to_ty = event_type(to, &other.ty);
+                if from_ty != to_ty {
+                    diffs.push(ContractEventDiff::Modified {
+                        name: event.name.clone(),
+                        from: from_ty,
+                        to: to_ty,
+                    });
+                }
+            }
+            None => diffs.push(ContractEventDiff::Removed {
+                name: event.name.clone(),
+                ty: from_ty,
+            }),
+        }
+    }
+    for event in &to.events {
+        if !from.events.iter().any(|e| e.name == event.name) {
+            diffs.push(ContractEventDiff::Added {
+                name: event.name.clone(),
+                ty: event_type(to, &event.ty),
+            });
+        }
+    }
+    diffs
+}
+
+/// Diff the error enums of two schemas.
+///
+/// Errors are matched by name first, so a renumbered error is reported as such. Errors left unmatched
+/// on both sides that share a discriminant are reported as renamed.
+pub fn get_error_diff(
+    from: &OdraContractSchema,
+    to: &OdraContractSchema,
+) -> Vec<ContractErrorDiff> {
+    let mut diffs = vec![];
+    let mut removed: BTreeMap<u16, String> = BTreeMap::new();
+    let mut added: BTreeMap<u16, String> = BTreeMap::new();
+
+    for error in &from.errors {
+        match to.errors.iter().find(|e| e.name == error.name) {
+            Some(other) if other.discriminant != error.discriminant => {
+                diffs.push(ContractErrorDiff::Renumbered {
+                    name: error.name.clone(),
+                    from: error.discriminant,
+                    to: other.discriminant,
+                })
+            }
+            Some(_) => {}
+            None => {
+                removed.insert(error.discriminant, error.name.clone());
+            }
+        }
+    }
+    for error in &to.errors {
+        if !from.errors.iter().any(|e| e.name == error.name) {
+            added.insert(error.discriminant, error.name.clone());
+        }
+    }
+
+    for (discriminant, name) in removed {
+        match added.remove(&discriminant) {
+            Some(to) => diffs.push(ContractErrorDiff::Renamed {
+                discriminant,
+                from: name,
+                to,
+            }),
+            None => diffs.push(ContractErrorDiff::Removed { name, discriminant }),
+        }
+    }
+    for (discriminant, name) in added {
+        diffs.push(ContractErrorDiff::Added { name, discriminant });
+    }
+    diffs
+}
+
+/// Read the schema embedded in a contract wasm, if any
+pub fn get_embedded_schema(wasm: &[u8]) -> Result<Option<OdraContractSchema>, String> {
+    match get_wasm_custom_section(wasm, SCHEMA_CUSTOM_SECTION)? {
+        Some(section) => serde_json::from_slice(&section)
+            .map(Some)
+            .map_err(|e| format!("Invalid embedded contract schema: {}", e)),
+        None => Ok(None),
+    }
+}
+
+/// Get the schema attached to a contract version.
+///
+/// Versions without an uploaded schema fall back to the one embedded in their Wasm, which is persisted
+/// once discovered.
+pub async fn get_version_schema(
+    pool: &PgPool,
+    node_address: &str,
+    user_id: &Uuid,
+    version: &ContractVersionSchema,
+) -> Result<Option<ContractVersionOdraSchema>, String> {
+    if let Some(schema) = get_contract_schema(
+        pool,
+        &version.contract_package_hash,
+        version.contract_version,
+        user_id,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    {
+        return Ok(Some(schema));
+    }
+
+    let wasm = get_contract_wasm(pool, node_address, &version.contract_wasm_hash).await?;
+    let Some(schema) = get_embedded_schema(&wasm)? else {
+        return Ok(None);
+    };
+    let schema = ContractVersionOdraSchema {
+        contract_package_hash: version.contract_package_hash.clone(),
+        contract_version: version.contract_version,
+        source: OdraSchemaSource::Wasm,
+        schema,
+    };
+    if let Err(e) = upsert_contract_schema(pool, user_id, &schema).await {
+        log::warn!(
+            "Failed to persist embedded schema of {} v{}: {}",
+            version.contract_package_hash,
+            version.contract_version,
+            e
+        );
+    }
+    Ok(Some(schema))
+}
+
+/// Set the event and error diffs of a version diff from two schemas and re-assess the upgrade
+pub fn set_schema_diff(
+    diff: &mut ContractVersionDiff,
+    from: &OdraContractSchema,
+    to: &OdraContractSchema,
+) {
+    diff.events = get_event_diff(from, to);
+    diff.errors = get_error_diff(from, to);
+    diff.assessment = Some(assess_upgrade(diff));
+}
+
+/// Attach the schema diff to a version diff, leaving it empty unless both versions have a schema
+pub async fn attach_schema_diff(
+    pool: &PgPool,
+    node_address: &str,
+    user_id: &Uuid,
+    v1: &ContractVersionSchema,
+    v2: &ContractVersionSchema,
+    diff: &mut ContractVersionDiff,
+) {
+    let from = get_version_schema(pool, node_address, user_id, v1).await;
+    let to = get_version_schema(pool, node_address, user_id, v2).await;
+    match (from, to) {
+        (Ok(Some(from)), Ok(Some(to))) => set_schema_diff(diff, &from.schema, &to.schema),
+        (Err(e), _) | (_, Err(e)) => log::warn!(
+            "Failed to get contract schemas of {} v{} -> v{}: {}",
+            diff.contract_package_hash,
+            v1.contract_version,
+            v2.contract_version,
+            e
+        ),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(errors: &[(&str, u16)], events: serde_json::Value) -> OdraContractSchema {
+        serde_json::from_value(serde_json::json!({
+            "casper_contract_schema_version": 1,
+            "contract_name": "Token",
+            "contract_version": "1.0.0",
+            "types": [
+                {"struct": {"name": "Transfer", "members": [{"name": "amount", "ty": "U256"}]}},
+                {"struct": {"name": "TransferV2", "members": [{"name": "amount", "ty": "U512"}]}}
+            ],
+            "events": events,
+            "errors": errors
+                .iter()
+                .map(|(name, discriminant)| serde_json::json!({
+                    "name": name,
+                    "description": null,
+                    "discriminant": discriminant
+                }))
+                .collect::<Vec<_>>()
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reports_renumbered_errors() {
+        let from = schema(
+            &[("NotAuthorized", 1), ("VersionAlreadyExists", 2)],
+            serde_json::json!([]),
+        );
+        let to = schema(
+            &[("VersionAlreadyExists", 1), ("NotAuthorized", 2)],
+            serde_json::json!([]),
+        );
+        assert_eq!(
+            get_error_diff(&from, &to),
+            vec![
+                ContractErrorDiff::Renumbered {
+                    name: "NotAuthorized".to_string(),
+                    from: 1,
+                    to: 2,
+                },
+                ContractErrorDiff::Renumbered {
+                    name: "VersionAlreadyExists".to_string(),
+                    from: 2,
+                    to: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pairs_unmatched_errors_by_discriminant() {
+        let from = schema(&[("NotOwner", 1), ("Paused", 3)], serde_json::json!([]));
+        let to = schema(
+            &[("NotAuthorized", 1), ("Frozen", 4)],
+            serde_json::json!([]),
+        );
+        assert_eq!(
+            get_error_diff(&from, &to),
+            vec![
+                ContractErrorDiff::Renamed {
+                    discriminant: 1,
+                    from: "NotOwner".to_string(),
+                    to: "NotAuthorized".to_string(),
+                },
+                ContractErrorDiff::Removed {
+                    name: "Paused".to_string(),
+                    discriminant: 3,
+                },
+                ContractErrorDiff::Added {
+                    name: "Frozen".to_string(),
+                    discriminant: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn compares_event_types_by_definition() {
+        let from = schema(
+            &[],
+            serde_json::json!([{"name": "Transfer", "ty": "Transfer"}]),
+        );
+        let to = schema(
+            &[],
+            serde_json::json!([{"name": "Transfer", "ty": "TransferV2"}]),
+        );
+        let diff = get_event_diff(&from, &to);
+        assert_eq!(diff.len(), 1);
+        assert!(matches!(&diff[0], ContractEventDiff::Modified { name, .. } if name == "Transfer"));
+        assert!(get_event_diff(&from, &from).is_empty());
+    }
+}
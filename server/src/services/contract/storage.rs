@@ -0,0 +1,340 @@
+use casper_client::{JsonRpcId, rpcs::DictionaryItemIdentifier};
+use casper_types::{
+    AsymmetricType, CLType, CLValue, Digest, Key, PublicKey, U128, U256, U512, URef,
+    account::AccountHash,
+    bytesrepr::{Bytes, ToBytes},
+};
+use rand::Rng;
+
+use crate::{
+    models::{
+        api::contract::OdraStorageRead,
+        schema::odra::{OdraContractSchema, OdraStorageField, OdraStorageKind, OdraStorageValue},
+    },
+    services::contract::{
+        digest_to_hex, get_state_root_hash, is_value_not_found,
+        package::get_latest_enabled_contract, predeploy::named_cl_type_to_cl_type,
+    },
+    utils::strip_hash_prefix,
+};
+
+/// Named key of the dictionary holding all Odra module storage
+const ODRA_STATE_KEY: &str = "state";
+
+/// Parse an account or contract reference the way Odra addresses are written
+fn parse_key(value: &str) -> Result<Key, String> {
+    let raw = strip_hash_prefix(value);
+    let value = if raw != value {
+        format!("hash-{}", raw)
+    } else {
+        raw
+    };
+    if let Ok(key) = Key::from_formatted_str(&value) {
+        return Ok(key);
+    }
+    let public_key =
+        PublicKey::from_hex(&value).map_err(|_| format!("Invalid address: {}", value))?;
+    Ok(Key::Account(AccountHash::from(&public_key)))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &serde_json::Value) -> Result<T, String> {
+    let rendered = match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    rendered
+        .parse::<T>()
+        .map_err(|_| format!("Invalid number: {}", rendered))
+}
+
+fn parse_big_number(value: &serde_json::Value) -> Result<String, String> {
+    match value {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        other => Err(format!("Invalid number: {}", other)),
+    }
+}
+
+/// Serialize a mapping key from its JSON form, matching how Odra serializes it into the storage key
+fn encode_mapping_key(key_type: &CLType, key: &serde_json::Value) -> Result<Vec<u8>, String> {
+    let bytes = match key_type {
+        CLType::Bool => key.as_bool().ok_or("Expected a boolean key")?.to_bytes(),
+        CLType::U8 => parse_number::<u8>(key)?.to_bytes(),
+        CLType::U32 => parse_number::<u32>(key)?.to_bytes(),
+        CLType::U64 => parse_number::<u64>(key)?.to_bytes(),
+        CLType::I32 => parse_number::<i32>(key)?.to_bytes(),
+        CLType::I64 => parse_number::<i64>(key)?.to_bytes(),
+        CLType::U128 => U128::from_dec_str(&parse_big_number(key)?)
+            .map_err(|e| format!("Invalid U128 key: {:?}", e))?
+            .to_bytes(),
+        CLType::U256 => U256::from_dec_str(&parse_big_number(key)?)
+            .map_err(|e| format!("Invalid U256 key: {:?}", e))?
+            .to_bytes(),
+        CLType::U512 => U512::from_dec_str(&parse_big_number(key)?)
+            .map_err(|e| format!("Invalid U512 key: {:?}", e))?
+            .to_bytes(),
+        CLType::String => key
+            .as_str()
+            .ok_or("Expected a string key")?
+            .to_string()
+            .to_bytes(),
+        // Odra `Address` shares the tag and layout of `Key::Account` and `Key::Hash`
+        CLType::Key => parse_key(key.as_str().ok_or("Expected an address key")?)?.to_bytes(),
+        CLType::PublicKey => PublicKey::from_hex(key.as_str().ok_or("Expected a public key hex")?)
+            .map_err(|e| format!("Invalid public key: {}", e))?
+            .to_bytes(),
+        other => return Err(format!("Unsupported mapping key type: {:?}", other)),
+    };
+    bytes.map_err(|e| format!("Failed to serialize mapping key: {}", e))
+}
+
+/// Derive the `state` dictionary item key of a field, Odra hashes the field index followed by the mapping key
+fn get_dictionary_item_key(field: &OdraStorageField) -> Result<String, String> {
+    let mut storage_key = field.index.to_be_bytes().to_vec();
+    if field.kind == OdraStorageKind::Mapping {
+        let key_type = field
+            .key_type
+            .as_ref()
+            .ok_or_else(|| format!("Mapping {} is missing a key type", field.name))?;
+        let key = field
+            .key
+            .as_ref()
+            .ok_or_else(|| format!("Mapping {} is missing a key", field.name))?;
+        storage_key.extend(encode_mapping_key(key_type, key)?);
+    }
+    digest_to_hex(&Digest::hash(storage_key))
+}
+
+/// Decode an Odra storage value, stored as serialized bytes wrapped in a `Bytes` CLValue
+fn decode_storage_value(
+    stored: &CLValue,
+    value_type: &CLType,
+) -> Result<serde_json::Value, String> {
+    let inner: Bytes = stored
+        .clone()
+        .into_t()
+        .map_err(|e| format!("Unexpected storage value layout: {}", e))?;
+    let value = CLValue::from_components(value_type.clone(), inner.to_vec());
+    serde_json::to_value(&value)
+        .ok()
+        .and_then(|v| v.get("parsed").cloned())
+        .filter(|v| !v.is_null())
+        .ok_or_else(|| format!("Stored value does not decode as {:?}", value_type))
+}
+
+async fn read_storage_field(
+    node_address: &str,
+    state_root_hash: Digest,
+    state_uref: URef,
+    field: &OdraStorageField,
+) -> OdraStorageValue {
+    let mut value = OdraStorageValue {
+        name: field.name.clone(),
+        kind: field.kind,
+        key: field.key.clone(),
+        dictionary_item_key: None,
+        cl_type: format!("{:?}", field.value_type),
+        value: None,
+        error: None,
+    };
+
+    let dictionary_item_key = match get_dictionary_item_key(field) {
+        Ok(key) => key,
+        Err(e) => {
+            value.error = Some(e);
+            return value;
+        }
+    };
+    value.dictionary_item_key = Some(dictionary_item_key.clone());
+
+    let rpc_id = JsonRpcId::from(rand::rng().random::<i64>());
+    let response = casper_client::get_dictionary_item(
+        rpc_id,
+        node_address,
+        casper_client::Verbosity::Low,
+        state_root_hash,
+        DictionaryItemIdentifier::URef {
+            seed_uref: state_uref,
+            dictionary_item_key,
+        },
+    )
+    .await;
+
+    match response {
+        Ok(resp) => match resp.result.stored_value.as_cl_value() {
+            Some(stored) => match decode_storage_value(stored, &field.value_type) {
+                Ok(decoded) => value.value = Some(decoded),
+                Err(e) => value.error = Some(e),
+            },
+            None => value.error = Some("Dictionary item is not a CLValue".to_string()),
+        },
+        // Unset `Var`s and missing mapping entries are not written to the dictionary at all
+        Err(e) if is_value_not_found(&e) => value.error = Some("Value not set".to_string()),
+        Err(e) => value.error = Some(format!("Failed to read value: {}", e)),
+    }
+    value
+}
+
+/// Lay out the storage fields of a schema, indexed by their position in the module struct
+pub fn get_schema_storage_fields(
+    schema: &OdraContractSchema,
+) -> Result<Vec<OdraStorageField>, String> {
+    schema
+        .storage
+        .iter()
+        .enumerate()
+        .map(|(position, field)| {
+            Ok(OdraStorageField {
+                name: field.name.clone(),
+                index: position as u32 + 1,
+                kind: field.kind,
+                value_type: named_cl_type_to_cl_type(&field.ty)?,
+                key_type: field
+                    .key_ty
+                    .as_ref()
+                    .map(named_cl_type_to_cl_type)
+                    .transpose()?,
+                key: None,
+            })
+        })
+        .collect()
+}
+
+/// Pick the fields to read from the schema layout, fields laid out by hand replacing the schema
+/// field of the same name.
+///
+/// Without reads every `Var` is read, along with the mappings laid out by hand with a key.
+pub fn resolve_storage_fields(
+    schema_fields: Vec<OdraStorageField>,
+    overrides: &[OdraStorageField],
+    reads: &[OdraStorageRead],
+) -> Result<Vec<OdraStorageField>, String> {
+    let mut layout = schema_fields;
+    for field in overrides {
+        match layout.iter_mut().find(|f| f.name == field.name) {
+            Some(existing) => *existing = field.clone(),
+            None => layout.push(field.clone()),
+        }
+    }
+
+    if reads.is_empty() {
+        return Ok(layout
+            .into_iter()
+            .filter(|f| f.kind == OdraStorageKind::Var || f.key.is_some())
+            .collect());
+    }
+    reads
+        .iter()
+        .map(|read| {
+            let mut field = layout
+                .iter()
+                .find(|f| f.name == read.name)
+                .cloned()
+                .ok_or_else(|| format!("Unknown storage field: {}", read.name))?;
+            if read.key.is_some() {
+                field.key = read.key.clone();
+            }
+            Ok(field)
+        })
+        .collect()
+}
+
+/// Read `Var` and `Mapping` values of an Odra contract from the `state` dictionary of its latest enabled version
+pub async fn get_odra_storage_values(
+    node_address: &str,
+    package_hash: &str,
+    fields: &[OdraStorageField],
+) -> Result<Vec<OdraStorageValue>, String> {
+    let contract = get_latest_enabled_contract(node_address, package_hash).await?;
+    let state_uref = contract
+        .named_keys()
+        .get(ODRA_STATE_KEY)
+        .and_then(|key| key.as_uref())
+        .copied()
+        .ok_or("No 'state' dictionary found, contract is not an Odra contract")?;
+
+    let state_root_hash = get_state_root_hash(node_address).await?;
+    let state_root_hash = Digest::from_hex(&state_root_hash).map_err(|e| e.to_string())?;
+
+    let mut values = vec![];
+    for field in fields {
+        values.push(read_storage_field(node_address, state_root_hash, state_uref, field).await);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> OdraContractSchema {
+        serde_json::from_value(serde_json::json!({
+            "casper_contract_schema_version": 1,
+            "contract_name": "Observability",
+            "contract_version": "1.0.0",
+            "storage": [
+                {"name": "diffs", "kind": "Mapping", "ty": "String", "key_ty": "String"},
+                {"name": "latest_version", "kind": "Var", "ty": "String"},
+                {"name": "owner", "kind": "Var", "ty": "Key"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn read(name: &str, key: Option<serde_json::Value>) -> OdraStorageRead {
+        OdraStorageRead {
+            name: name.to_string(),
+            key,
+        }
+    }
+
+    #[test]
+    fn lays_out_schema_fields_in_declaration_order() {
+        let fields = get_schema_storage_fields(&schema()).unwrap();
+        let layout: Vec<(&str, u32)> = fields.iter().map(|f| (f.name.as_str(), f.index)).collect();
+        assert_eq!(
+            layout,
+            vec![("diffs", 1), ("latest_version", 2), ("owner", 3)]
+        );
+        assert_eq!(fields[0].key_type, Some(CLType::String));
+        assert_eq!(fields[2].value_type, CLType::Key);
+    }
+
+    #[test]
+    fn reads_every_var_without_reads() {
+        let fields = get_schema_storage_fields(&schema()).unwrap();
+        let resolved = resolve_storage_fields(fields, &[], &[]).unwrap();
+        let names: Vec<&str> = resolved.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["latest_version", "owner"]);
+    }
+
+    #[test]
+    fn reads_mappings_by_name_with_their_key() {
+        let fields = get_schema_storage_fields(&schema()).unwrap();
+        let key = serde_json::json!("v1");
+        let resolved =
+            resolve_storage_fields(fields, &[], &[read("diffs", Some(key.clone()))]).unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].index, 1);
+        assert_eq!(resolved[0].key, Some(key));
+
+        let fields = get_schema_storage_fields(&schema()).unwrap();
+        assert!(resolve_storage_fields(fields, &[], &[read("balances", None)]).is_err());
+    }
+
+    #[test]
+    fn manual_fields_override_the_schema() {
+        let fields = get_schema_storage_fields(&schema()).unwrap();
+        let manual = OdraStorageField {
+            name: "owner".to_string(),
+            index: 7,
+            kind: OdraStorageKind::Var,
+            value_type: CLType::PublicKey,
+            key_type: None,
+            key: None,
+        };
+        let resolved = resolve_storage_fields(fields, &[manual], &[read("owner", None)]).unwrap();
+        assert_eq!(resolved[0].index, 7);
+        assert_eq!(resolved[0].value_type, CLType::PublicKey);
+    }
+}
@@ -1,82 +1,79 @@
 use crate::constants::network::{MAINNET_API_ENDPOINT, TESTNET_API_ENDPOINT};
-use crate::models::api::transaction::{Transaction, TransactionsResponse};
-use crate::services::contract::metadata::get_contract_package_metadata;
+use crate::models::api::transaction::{ContractEntryPoint, TransactionsResponse};
+use crate::models::schema::contract::APIMetaResponse;
+use crate::models::schema::transaction::TransactionTarget;
+use crate::utils::strip_hash_prefix;
 
-/// Fetch transactions (deploys) for a contract package.
+/// Fetch one page of the deploys targeting a contract package or contract from cspr.live, newest first.
 ///
-/// Since `cspr.live` does not have a direct "get deploys by package" endpoint that is reliable for all packages,
-/// this function uses a two-step strategy:
-/// 1. Fetch the Contract Package Metadata to get the Owner's Public Key.
-/// 2. Fetch the Owner's Account Deploys.
-/// 3. Filter the deploys to include only those that interact with the target Contract Package Hash
-///    or any of its Contract Hashes (versions).
-pub async fn get_contract_transactions(
+/// Deploys calling a contract by its contract hash are listed under the contract, so the indexer pages
+/// through the package and every contract hash of its versions.
+pub async fn fetch_transactions_page(
     network: &str,
-    package_hash: &str,
-) -> Result<Vec<Transaction>, String> {
-    // 1. Get Contract Package Metadata to find the Owner
-    let meta = get_contract_package_metadata(network, package_hash).await?;
-    let owner_public_key = meta.owner_public_key;
-
-    // 2. Fetch Owner's Deploys
+    target: &TransactionTarget,
+    page: u32,
+    limit: u64,
+) -> Result<TransactionsResponse, String> {
     let endpoint = match network {
         "mainnet" => MAINNET_API_ENDPOINT,
         "testnet" => TESTNET_API_ENDPOINT,
         _ => return Err(format!("Unsupported network: {}", network)),
     };
 
+    // cspr.live expects RAW hash (no `hash-`)
+    let (resource, hash) = match target {
+        TransactionTarget::ContractPackage(hash) => ("contract-packages", hash),
+        TransactionTarget::Contract(hash) => ("contracts", hash),
+    };
+    let raw_hash = strip_hash_prefix(hash);
+
     let url = format!(
-        "{}/accounts/{}/deploys?limit=100",
-        endpoint, owner_public_key
+        "{}/{}/{}/deploys?page={}&limit={}",
+        endpoint, resource, raw_hash, page, limit
     );
     let client = reqwest::Client::new();
     let resp = client.get(&url).send().await.map_err(|e| e.to_string())?;
 
     if !resp.status().is_success() {
         return Err(format!(
-            "Failed to fetch account deploys for owner {}: {}",
-            owner_public_key,
+            "Failed to fetch deploys of {} {}: {}",
+            resource,
+            raw_hash,
             resp.status()
         ));
     }
 
-    let json_data = resp
-        .json::<serde_json::Value>()
+    resp.json::<TransactionsResponse>()
         .await
-        .map_err(|e| e.to_string())?;
-
-    // Deserialize into our TransactionsResponse model
-    let transactions_response: TransactionsResponse = serde_json::from_value(json_data)
-        .map_err(|e| format!("Failed to parse transactions: {}", e))?;
-
-    // 3. Filter Deploys
-    // We want deploys where `contract_package_hash` matches our target.
-    // Note: The API response often contains a `contract_package` object nested in the transaction.
-    // We should check if that object's hash matches.
-
-    // Normalize our package hash for comparison (remove 'hash-' prefix if present, though API uses raw hex)
-    let target_hash_raw = package_hash.strip_prefix("hash-").unwrap_or(package_hash);
+        .map_err(|e| format!("Failed to parse transactions: {}", e))
+}
 
-    let filtered_transactions: Vec<Transaction> = transactions_response
-        .data
-        .into_iter()
-        .filter(|tx| {
-            // Check direct contract_package_hash field
-            if let Some(ref ph) = tx.contract_package_hash {
-                if ph == target_hash_raw {
-                    return true;
-                }
-            }
+/// Fetch the entry points of a contract from cspr.live, to name the entry point ids of its deploys
+pub async fn fetch_contract_entry_points(
+    network: &str,
+    contract_hash: &str,
+) -> Result<Vec<ContractEntryPoint>, String> {
+    let endpoint = match network {
+        "mainnet" => MAINNET_API_ENDPOINT,
+        "testnet" => TESTNET_API_ENDPOINT,
+        _ => return Err(format!("Unsupported network: {}", network)),
+    };
 
-            // Also check if the transaction object structure from the API has a nested contract_package object
-            // (The Transaction struct I defined captures the flattened fields, but let's double check logic)
-            // Based on my manual curl, the API returns `contract_package_hash` at the top level of the deploy object
-            // or inside a `contract_package` object. My struct only mapped the top level.
-            // Let's stick to the top level for now as per my struct definition.
+    let raw_hash = strip_hash_prefix(contract_hash);
+    let url = format!("{}/contracts/{}/entry-points", endpoint, raw_hash);
+    let client = reqwest::Client::new();
+    let resp = client.get(&url).send().await.map_err(|e| e.to_string())?;
 
-            false
-        })
-        .collect();
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Failed to fetch entry points of contract {}: {}",
+            raw_hash,
+            resp.status()
+        ));
+    }
 
-    Ok(filtered_transactions)
+    resp.json::<APIMetaResponse<Vec<ContractEntryPoint>>>()
+        .await
+        .map(|json| json.data)
+        .map_err(|e| format!("Failed to parse entry points: {}", e))
 }
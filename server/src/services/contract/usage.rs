@@ -0,0 +1,98 @@
+use std::collections::{BTreeSet, HashMap};
+
+use casper_types::EntryPointAccess;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+
+use crate::{
+    models::{
+        api::analytics::{EntryPointUsage, EntryPointUsageReport, EntryPointUsageStatus},
+        schema::contract::{ContractPackageSchema, ContractVersionSchema},
+    },
+    services::database::{
+        analytics::get_version_entry_point_calls, contract::get_contract_versions,
+    },
+};
+
+/// Lowercased public keys of the package owner and of the deployers of its versions
+pub async fn get_package_owners(
+    pool: &PgPool,
+    package: &ContractPackageSchema,
+) -> Result<Vec<String>, String> {
+    let versions = get_contract_versions(pool, &package.package_hash, &package.user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(std::iter::once(package.owner_id.clone())
+        .chain(versions.into_iter().filter_map(|v| v.deployer_public_key))
+        .filter(|key| !key.is_empty())
+        .map(|key| key.to_lowercase())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect())
+}
+
+/// Report how the entry points of a contract version are used.
+///
+/// A public entry point only the owners call is flagged as risky since it is likely meant to be
+/// restricted.
+pub async fn get_entry_point_usage(
+    pool: &PgPool,
+    package: &ContractPackageSchema,
+    version: ContractVersionSchema,
+    idle_days: u32,
+) -> Result<EntryPointUsageReport, String> {
+    let owners = get_package_owners(pool, package).await?;
+    let calls: HashMap<String, _> = get_version_entry_point_calls(
+        pool,
+        &package.network,
+        &package.package_hash,
+        &package.user_id,
+        version.contract_version,
+        &owners,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .into_iter()
+    .map(|c| (c.entry_point_name.clone(), c))
+    .collect();
+
+    let idle_since = Utc::now() - Duration::days(i64::from(idle_days));
+    let mut entry_points: Vec<EntryPointUsage> = version
+        .entry_points
+        .iter()
+        .map(|ep| {
+            let public = matches!(ep.access(), EntryPointAccess::Public);
+            let usage = calls.get(ep.name());
+            let status = match usage {
+                None => EntryPointUsageStatus::Unused,
+                Some(c) if c.last_call < idle_since => EntryPointUsageStatus::Idle,
+                Some(c) if c.owner_calls == c.calls => EntryPointUsageStatus::OwnerOnly,
+                Some(_) => EntryPointUsageStatus::Active,
+            };
+            EntryPointUsage {
+                name: ep.name().to_string(),
+                public,
+                calls: usage.map_or(0, |c| c.calls),
+                callers: usage.map_or(0, |c| c.callers),
+                owner_calls: usage.map_or(0, |c| c.owner_calls),
+                last_call: usage.map(|c| c.last_call),
+                status,
+                risky: public && usage.is_some_and(|c| c.owner_calls == c.calls),
+            }
+        })
+        .collect();
+    entry_points.sort_by(|a, b| {
+        b.risky
+            .cmp(&a.risky)
+            .then(a.calls.cmp(&b.calls))
+            .then(a.name.cmp(&b.name))
+    });
+
+    Ok(EntryPointUsageReport {
+        contract_package_hash: package.package_hash.clone(),
+        contract_version: version.contract_version,
+        idle_days,
+        owners,
+        entry_points,
+    })
+}
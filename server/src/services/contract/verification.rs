@@ -0,0 +1,456 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use casper_types::Digest;
+use chrono::Utc;
+use sqlx::PgPool;
+use tokio::{process::Command, sync::Semaphore};
+
+use crate::{
+    config::Config,
+    models::schema::verification::{ContractVerificationSchema, ContractVerificationStatus},
+    services::{
+        contract::{digest_to_hex, wasm::get_contract_wasm},
+        database::verification::update_contract_verification,
+    },
+};
+
+/// Verification builds are CPU and disk heavy, so only one runs at a time
+static BUILD_SLOTS: Semaphore = Semaphore::const_new(1);
+
+/// Bytes of build output kept with a verification
+const BUILD_LOG_LIMIT: usize = 16 * 1024;
+
+/// Largest source archive accepted for a verification build
+pub const MAX_SOURCE_ARCHIVE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Length of the base64 encoding of the largest source archive
+pub const MAX_ENCODED_SOURCE_ARCHIVE_SIZE: usize = MAX_SOURCE_ARCHIVE_SIZE.div_ceil(3) * 4;
+
+/// Builds accepted but not completed yet, each holds its archive in memory until it runs
+const MAX_QUEUED_BUILDS: usize = 4;
+
+static QUEUED_BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Place of a build in the queue, freed once the build completes
+pub struct QueuedBuild(());
+
+impl Drop for QueuedBuild {
+    fn drop(&mut self) {
+        QUEUED_BUILDS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Take a place in the build queue, `None` while it is full
+pub fn queue_build() -> Option<QueuedBuild> {
+    QUEUED_BUILDS
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+            (queued < MAX_QUEUED_BUILDS).then_some(queued + 1)
+        })
+        .ok()
+        .map(|_| QueuedBuild(()))
+}
+
+/// Largest total size of the files a source archive may extract to, vendored crates included
+const MAX_EXTRACTED_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Variables passed on to the build, everything else is cleared so no host credentials leak into it
+const BUILD_ENV_PASSTHROUGH: [&str; 4] = ["PATH", "HOME", "RUSTUP_HOME", "CARGO_HOME"];
+
+/// Whether a user supplied value can be passed to the build command without being read as a flag
+pub fn is_safe_build_argument(value: &str) -> bool {
+    !value.is_empty()
+        && !value.starts_with('-')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'))
+}
+
+fn log_tail(log: &str) -> String {
+    if log.len() <= BUILD_LOG_LIMIT {
+        return log.to_string();
+    }
+    let mut start = log.len() - BUILD_LOG_LIMIT;
+    while !log.is_char_boundary(start) {
+        start += 1;
+    }
+    log[start..].to_string()
+}
+
+/// Run a build step in the project directory, wrapped in the configured sandbox command
+async fn run_build_step(
+    config: &Config,
+    dir: &Path,
+    args: &[String],
+    envs: &[(&str, String)],
+    log: &mut String,
+) -> Result<String, String> {
+    let mut argv: Vec<String> = config
+        .build_sandbox_command
+        .as_deref()
+        .ok_or("No BUILD_SANDBOX_COMMAND is configured, refusing to build unsandboxed")?
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    argv.extend(args.iter().cloned());
+    let (program, program_args) = argv.split_first().ok_or("Empty build command")?;
+
+    let mut command = Command::new(program);
+    command
+        .args(program_args)
+        .current_dir(dir)
+        .env_clear()
+        .envs(
+            BUILD_ENV_PASSTHROUGH
+                .iter()
+                .filter_map(|k| std::env::var(k).ok().map(|v| (*k, v))),
+        )
+        .env("CARGO_NET_OFFLINE", "true")
+        .env("CARGO_TARGET_DIR", dir.join("target"))
+        .envs(envs.iter().map(|(k, v)| (*k, v)))
+        .kill_on_drop(true);
+
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", args.join(" "), e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    log.push_str(&format!("$ {}\n", args.join(" ")));
+    log.push_str(&stdout);
+    log.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", args.join(" "), output.status));
+    }
+    Ok(stdout)
+}
+
+/// Sum the sizes of the members of a gzipped tarball as listed in its headers, without extracting it
+async fn get_extracted_size(archive_path: &Path) -> Result<u64, String> {
+    let output = Command::new("tar")
+        .arg("-tvzf")
+        .arg(archive_path)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list source archive: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let mut total = 0u64;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        // `<mode> <owner>/<group> <size> <date> <time> <name>`, device members carry no plain size
+        let size = line
+            .split_whitespace()
+            .nth(2)
+            .and_then(|s| s.parse::<u64>().ok())
+            .ok_or_else(|| format!("Unsupported source archive member: {}", line))?;
+        total = total.saturating_add(size);
+    }
+    Ok(total)
+}
+
+/// Reject links and special files among the extracted sources.
+///
+/// A link shipped as `.cargo` or `vendor` would make the build config written below land outside
+/// the work directory, so every path is checked without following links.
+fn check_extracted_sources(source_dir: &Path) -> Result<(), String> {
+    let mut pending = vec![source_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            let file_type = std::fs::symlink_metadata(&path)
+                .map_err(|e| e.to_string())?
+                .file_type();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if !file_type.is_file() {
+                return Err(format!(
+                    "Source archive contains a link or special file: {}",
+                    path.strip_prefix(source_dir).unwrap_or(&path).display()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the project root of an extracted archive, which may wrap the sources in a single directory
+fn find_project_root(source_dir: &Path) -> Result<PathBuf, String> {
+    if source_dir.join("Cargo.toml").is_file() {
+        return Ok(source_dir.to_path_buf());
+    }
+    let entries: Vec<PathBuf> = std::fs::read_dir(source_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    match entries.as_slice() {
+        [dir] if dir.join("Cargo.toml").is_file() => Ok(dir.clone()),
+        _ => Err("Source archive has no Cargo.toml at its root".to_string()),
+    }
+}
+
+/// Build the contract from the submitted archive fully offline and return its Wasm
+async fn build_contract(
+    config: &Config,
+    work_dir: &Path,
+    archive: &[u8],
+    verification: &ContractVerificationSchema,
+    log: &mut String,
+) -> Result<Vec<u8>, String> {
+    if archive.len() > MAX_SOURCE_ARCHIVE_SIZE {
+        return Err(format!(
+            "Source archive exceeds {} bytes",
+            MAX_SOURCE_ARCHIVE_SIZE
+        ));
+    }
+    let source_dir = work_dir.join("source");
+    tokio::fs::create_dir_all(&source_dir)
+        .await
+        .map_err(|e| format!("Failed to create build directory: {}", e))?;
+    let archive_path = work_dir.join("source.tar.gz");
+    tokio::fs::write(&archive_path, archive)
+        .await
+        .map_err(|e| format!("Failed to write source archive: {}", e))?;
+
+    let extracted_size = get_extracted_size(&archive_path).await?;
+    if extracted_size > MAX_EXTRACTED_SIZE {
+        return Err(format!(
+            "Source archive extracts to {} bytes, more than the {} allowed",
+            extracted_size, MAX_EXTRACTED_SIZE
+        ));
+    }
+
+    // Extraction runs unsandboxed, GNU tar refuses absolute and parent directory members itself
+    let output = Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive_path)
+        .args(["--no-same-owner", "--no-same-permissions", "-C"])
+        .arg(&source_dir)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to extract source archive: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    check_extracted_sources(&source_dir)?;
+    let root = find_project_root(&source_dir)?;
+    let vendor_dir = if root.join("vendor").is_dir() {
+        root.join("vendor")
+    } else {
+        config
+            .build_vendor_dir
+            .as_deref()
+            .map(PathBuf::from)
+            .ok_or("Source archive has no vendor directory and no BUILD_VENDOR_DIR is configured")?
+    };
+
+    // Replaces any cargo config the archive ships, so crates can only come from the vendored set
+    let cargo_dir = root.join(".cargo");
+    tokio::fs::create_dir_all(&cargo_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::fs::write(
+        cargo_dir.join("config.toml"),
+        format!(
+            "[source.crates-io]\nreplace-with = \"vendored-sources\"\n\n[source.vendored-sources]\ndirectory = \"{}\"\n\n[net]\noffline = true\n",
+            vendor_dir.display()
+        ),
+    )
+    .await
+    .map_err(|e| format!("Failed to write cargo config: {}", e))?;
+
+    let envs = [("RUSTUP_TOOLCHAIN", verification.toolchain.clone())];
+    let cargo_odra_version = run_build_step(
+        config,
+        &root,
+        &[
+            "cargo".to_string(),
+            "odra".to_string(),
+            "--version".to_string(),
+        ],
+        &envs,
+        log,
+    )
+    .await?;
+    if !cargo_odra_version
+        .split_whitespace()
+        .any(|v| v.trim_start_matches('v') == verification.cargo_odra_version)
+    {
+        return Err(format!(
+            "Requested cargo-odra {} but the build host has {}",
+            verification.cargo_odra_version,
+            cargo_odra_version.trim()
+        ));
+    }
+
+    let mut args = vec![
+        "cargo".to_string(),
+        "odra".to_string(),
+        "build".to_string(),
+        "-c".to_string(),
+        verification.contract_name.clone(),
+    ];
+    if !verification.features.is_empty() {
+        args.push("--features".to_string());
+        args.push(verification.features.join(","));
+    }
+    run_build_step(config, &root, &args, &envs, log).await?;
+
+    let wasm_path = root
+        .join("wasm")
+        .join(format!("{}.wasm", verification.contract_name));
+    tokio::fs::read(&wasm_path)
+        .await
+        .map_err(|e| format!("Build produced no {}: {}", wasm_path.display(), e))
+}
+
+/// Compare a built module with the deployed one, returning the verification status and the hash of
+/// the built module.
+///
+/// The wasm hash of a version is an address assigned at install rather than a hash of the module,
+/// so the build is compared with the deployed bytes.
+fn compare_built_wasm(
+    built: &[u8],
+    deployed: &[u8],
+) -> Result<(ContractVerificationStatus, String), String> {
+    let built_hash = digest_to_hex(&Digest::hash(built))?;
+    let deployed_hash = digest_to_hex(&Digest::hash(deployed))?;
+    let status = if built_hash == deployed_hash {
+        ContractVerificationStatus::Verified
+    } else {
+        ContractVerificationStatus::Mismatch
+    };
+    Ok((status, built_hash))
+}
+
+/// Run a verification build and record whether it reproduces the Wasm deployed on the given node
+pub async fn run_contract_verification(
+    pool: PgPool,
+    config: Config,
+    node_address: String,
+    verification: ContractVerificationSchema,
+    archive: Vec<u8>,
+    _queued: QueuedBuild,
+) {
+    let _permit = match BUILD_SLOTS.acquire().await {
+        Ok(permit) => permit,
+        Err(e) => {
+            log::error!("Build slots closed: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = update_contract_verification(
+        &pool,
+        &verification.id,
+        ContractVerificationStatus::Building,
+        None,
+        None,
+        None,
+    )
+    .await
+    {
+        log::warn!(
+            "Failed to mark verification {} building: {}",
+            verification.id,
+            e
+        );
+    }
+
+    let work_dir = Path::new(&config.build_work_dir).join(verification.id.to_string());
+    let mut build_log = String::new();
+    let result =
+        match get_contract_wasm(&pool, &node_address, &verification.expected_wasm_hash).await {
+            Ok(deployed) => tokio::time::timeout(
+                Duration::from_secs(config.build_timeout_secs),
+                build_contract(&config, &work_dir, &archive, &verification, &mut build_log),
+            )
+            .await
+            .unwrap_or_else(|_| {
+                Err(format!(
+                    "Build timed out after {}s",
+                    config.build_timeout_secs
+                ))
+            })
+            .and_then(|built| compare_built_wasm(&built, &deployed)),
+            Err(e) => Err(format!("Failed to fetch the deployed Wasm: {}", e)),
+        };
+
+    let (status, built_wasm_hash) = match result {
+        Ok((status, hash)) => (status, Some(hash)),
+        Err(e) => {
+            build_log.push_str(&format!("\n{}\n", e));
+            (ContractVerificationStatus::Failed, None)
+        }
+    };
+
+    if let Err(e) = update_contract_verification(
+        &pool,
+        &verification.id,
+        status,
+        built_wasm_hash.as_deref(),
+        Some(&log_tail(&build_log)),
+        Some(Utc::now()),
+    )
+    .await
+    {
+        log::error!("Failed to record verification {}: {}", verification.id, e);
+    }
+    if let Err(e) = tokio::fs::remove_dir_all(&work_dir).await {
+        log::warn!("Failed to clean up {}: {}", work_dir.display(), e);
+    }
+    log::info!(
+        "Verification {} of {} v{} finished: {}",
+        verification.id,
+        verification.contract_package_hash,
+        verification.contract_version,
+        status.as_str()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_the_build_queue() {
+        let queued: Vec<QueuedBuild> = std::iter::from_fn(queue_build).take(10).collect();
+        assert_eq!(queued.len(), MAX_QUEUED_BUILDS);
+        assert!(queue_build().is_none());
+        drop(queued);
+        assert!(queue_build().is_some());
+    }
+
+    fn fixture(value: i32) -> Vec<u8> {
+        wat::parse_str(format!(
+            r#"(module (func (export "call") (result i32) i32.const {value}))"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn verifies_a_build_matching_the_deployed_bytes() {
+        let (status, hash) = compare_built_wasm(&fixture(1), &fixture(1)).unwrap();
+        assert_eq!(status, ContractVerificationStatus::Verified);
+        assert_eq!(hash, digest_to_hex(&Digest::hash(fixture(1))).unwrap());
+    }
+
+    #[test]
+    fn reports_a_build_differing_from_the_deployed_bytes() {
+        let (status, hash) = compare_built_wasm(&fixture(2), &fixture(1)).unwrap();
+        assert_eq!(status, ContractVerificationStatus::Mismatch);
+        assert_eq!(hash, digest_to_hex(&Digest::hash(fixture(2))).unwrap());
+    }
+}
@@ -0,0 +1,679 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use casper_types::Digest;
+use sqlx::PgPool;
+use wasmparser::{ExternalKind, KnownCustom, Name, Operator, Parser, Payload, TypeRef};
+
+use crate::{
+    models::schema::wasm::{
+        ContractWasmDiff, WasmCountDiff, WasmCustomSection, WasmDataSegment,
+        WasmEntryPointLogicDiff, WasmExport, WasmFunctionDiff, WasmFunctionDiffSummary,
+        WasmFunctionMatch, WasmFunctionStatus, WasmFunctionSummary, WasmImport, WasmItemDiff,
+        WasmModuleSummary,
+    },
+    services::{
+        contract::{digest_to_hex, get_state_root_hash},
+        database::wasm::{get_contract_wasm_bytes, insert_contract_wasm_bytes},
+    },
+    utils::strip_hash_prefix,
+};
+
+/// Fetch `ContractWasm` bytes stored under the given wasm hash from global state
+pub async fn fetch_contract_wasm_from_chain(
+    node_address: &str,
+    wasm_hash: &str,
+) -> Result<Vec<u8>, String> {
+    let wasm_key = format!("hash-{}", strip_hash_prefix(wasm_hash));
+    let state_root_hash = get_state_root_hash(node_address).await?;
+
+    let wasm_response = casper_client::cli::query_global_state(
+        "",
+        node_address,
+        0,
+        "",
+        &state_root_hash,
+        &wasm_key,
+        "",
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match wasm_response.result.stored_value.as_contract_wasm() {
+        Some(contract_wasm) => Ok(contract_wasm.clone().take_bytes()),
+        None => Err("The provided hash does not correspond to contract wasm".to_string()),
+    }
+}
+
+/// Get contract wasm bytes, fetching them from chain and caching on first use.
+///
+/// Wasm stored under a hash is immutable, so cached bytes never need to be invalidated.
+pub async fn get_contract_wasm(
+    pool: &PgPool,
+    node_address: &str,
+    wasm_hash: &str,
+) -> Result<Vec<u8>, String> {
+    let wasm_hash = strip_hash_prefix(wasm_hash);
+    if let Some(bytes) = get_contract_wasm_bytes(pool, &wasm_hash)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(bytes);
+    }
+
+    let bytes = fetch_contract_wasm_from_chain(node_address, &wasm_hash).await?;
+    if let Err(e) = insert_contract_wasm_bytes(pool, &wasm_hash, &bytes).await {
+        log::warn!("Failed to cache contract wasm {}: {}", wasm_hash, e);
+    }
+    Ok(bytes)
+}
+
+/// Get the hash of the module bytes behind a contract wasm hash, the same for identical code on
+/// any network
+pub async fn get_contract_wasm_module_hash(
+    pool: &PgPool,
+    node_address: &str,
+    wasm_hash: &str,
+) -> Result<String, String> {
+    let bytes = get_contract_wasm(pool, node_address, wasm_hash).await?;
+    digest_to_hex(&Digest::hash(&bytes))
+}
+
+fn external_kind_name(kind: ExternalKind) -> String {
+    match kind {
+        ExternalKind::Func => "func",
+        ExternalKind::Table => "table",
+        ExternalKind::Memory => "memory",
+        ExternalKind::Global => "global",
+        ExternalKind::Tag => "tag",
+    }
+    .to_string()
+}
+
+fn type_ref_name(ty: TypeRef) -> String {
+    match ty {
+        TypeRef::Func(_) => "func",
+        TypeRef::Table(_) => "table",
+        TypeRef::Memory(_) => "memory",
+        TypeRef::Global(_) => "global",
+        TypeRef::Tag(_) => "tag",
+    }
+    .to_string()
+}
+
+/// Minimum opcode histogram similarity for pairing otherwise unmatched functions
+const FUNCTION_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// Render an operator with the immediates that shift between builds erased.
+///
+/// Function, global and table indices move whenever a function is added or removed, so they say
+/// nothing about the logic of the function itself. Constants are erased too and returned on their
+/// own: `i32.const` mostly carries linear memory addresses that move with the data section, but a
+/// changed fee or error code is still a change, so it is reported apart from instruction changes.
+fn normalize_operator(op: &Operator) -> (String, Option<String>) {
+    let constant = match op {
+        Operator::I32Const { value } => Some(value.to_string()),
+        Operator::I64Const { value } => Some(value.to_string()),
+        Operator::F32Const { value } => Some(value.bits().to_string()),
+        Operator::F64Const { value } => Some(value.bits().to_string()),
+        _ => None,
+    };
+    let token = match op {
+        Operator::Call { .. } => "Call".to_string(),
+        Operator::ReturnCall { .. } => "ReturnCall".to_string(),
+        Operator::CallIndirect { .. } => "CallIndirect".to_string(),
+        Operator::RefFunc { .. } => "RefFunc".to_string(),
+        Operator::GlobalGet { .. } => "GlobalGet".to_string(),
+        Operator::GlobalSet { .. } => "GlobalSet".to_string(),
+        Operator::I32Const { .. } => "I32Const".to_string(),
+        Operator::I64Const { .. } => "I64Const".to_string(),
+        Operator::F32Const { .. } => "F32Const".to_string(),
+        Operator::F64Const { .. } => "F64Const".to_string(),
+        other => format!("{:?}", other),
+    };
+    (token, constant)
+}
+
+/// Parse a Wasm module into the structural summary used for diffing
+pub fn parse_wasm_module(bytes: &[u8]) -> Result<WasmModuleSummary, String> {
+    let mut imports = vec![];
+    let mut exports = vec![];
+    let mut functions = vec![];
+    let mut data_segments = vec![];
+    let mut custom_sections = vec![];
+    let mut function_names: BTreeMap<u32, String> = BTreeMap::new();
+    let mut imported_functions = 0u32;
+    let mut code = vec![];
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload.map_err(|e| format!("Invalid wasm module: {}", e))? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(|e| e.to_string())?;
+                    if matches!(import.ty, TypeRef::Func(_)) {
+                        imported_functions += 1;
+                    }
+                    imports.push(WasmImport {
+                        module: import.module.to_string(),
+                        name: import.name.to_string(),
+                        kind: type_ref_name(import.ty),
+                    });
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(|e| e.to_string())?;
+                    exports.push(WasmExport {
+                        name: export.name.to_string(),
+                        kind: external_kind_name(export.kind),
+                        index: export.index,
+                    });
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let range = body.range();
+                code.extend_from_slice(&bytes[range.start..range.end]);
+
+                let mut instruction_count = 0u32;
+                let mut normalized = String::new();
+                let mut constants = String::new();
+                let mut opcode_histogram: BTreeMap<String, u32> = BTreeMap::new();
+                let mut calls = vec![];
+                let mut reader = body.get_operators_reader().map_err(|e| e.to_string())?;
+                while !reader.eof() {
+                    let op = reader.read().map_err(|e| e.to_string())?;
+                    if let Operator::Call { function_index } = op {
+                        calls.push(function_index);
+                    }
+                    let (token, constant) = normalize_operator(&op);
+                    if let Some(constant) = constant {
+                        constants.push_str(&constant);
+                        constants.push('\n');
+                    }
+                    let opcode = token.split(' ').next().unwrap_or_default().to_string();
+                    *opcode_histogram.entry(opcode).or_insert(0) += 1;
+                    normalized.push_str(&token);
+                    normalized.push('\n');
+                    instruction_count += 1;
+                }
+                functions.push(WasmFunctionSummary {
+                    index: imported_functions + functions.len() as u32,
+                    name: None,
+                    body_size: (range.end - range.start) as u32,
+                    instruction_count,
+                    export_names: vec![],
+                    normalized_hash: digest_to_hex(&Digest::hash(normalized.as_bytes()))?,
+                    constants_hash: digest_to_hex(&Digest::hash(constants.as_bytes()))?,
+                    opcode_histogram,
+                    calls,
+                });
+            }
+            Payload::DataSection(reader) => {
+                for (index, data) in reader.into_iter().enumerate() {
+                    let data = data.map_err(|e| e.to_string())?;
+                    data_segments.push(WasmDataSegment {
+                        index: index as u32,
+                        size: data.data.len() as u32,
+                    });
+                }
+            }
+            Payload::CustomSection(reader) => {
+                if let KnownCustom::Name(names) = reader.as_known() {
+                    for name in names {
+                        if let Ok(Name::Function(map)) = name {
+                            for naming in map.into_iter().flatten() {
+                                function_names.insert(naming.index, naming.name.to_string());
+                            }
+                        }
+                    }
+                }
+                custom_sections.push(WasmCustomSection {
+                    name: reader.name().to_string(),
+                    size: reader.data().len() as u32,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    for function in functions.iter_mut() {
+        function.name = function_names.get(&function.index).cloned();
+        function.export_names = exports
+            .iter()
+            .filter(|e| e.kind == "func" && e.index == function.index)
+            .map(|e| e.name.clone())
+            .collect();
+    }
+
+    Ok(WasmModuleSummary {
+        size: bytes.len() as u32,
+        module_hash: digest_to_hex(&Digest::hash(bytes))?,
+        code_hash: digest_to_hex(&Digest::hash(&code))?,
+        imports,
+        exports,
+        functions,
+        data_segments,
+        custom_sections,
+    })
+}
+
+/// Read the payload of the first custom section with the given name
+pub fn get_wasm_custom_section(bytes: &[u8], name: &str) -> Result<Option<Vec<u8>>, String> {
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::CustomSection(reader) =
+            payload.map_err(|e| format!("Invalid wasm module: {}", e))?
+            && reader.name() == name
+        {
+            return Ok(Some(reader.data().to_vec()));
+        }
+    }
+    Ok(None)
+}
+
+/// Diff two lists of items, matching them by the given identity
+fn diff_items<T, K, F>(from: &[T], to: &[T], identity: F) -> Vec<WasmItemDiff<T>>
+where
+    T: Clone + PartialEq,
+    K: PartialEq,
+    F: Fn(&T) -> K,
+{
+    let mut diffs = vec![];
+    for from_item in from {
+        match to.iter().find(|t| identity(t) == identity(from_item)) {
+            None => diffs.push(WasmItemDiff::Removed(from_item.clone())),
+            Some(to_item) if to_item != from_item => diffs.push(WasmItemDiff::Modified {
+                from: from_item.clone(),
+                to: to_item.clone(),
+            }),
+            _ => {}
+        }
+    }
+    for to_item in to {
+        if !from.iter().any(|f| identity(f) == identity(to_item)) {
+            diffs.push(WasmItemDiff::Added(to_item.clone()));
+        }
+    }
+    diffs
+}
+
+fn function_display_name(function: &WasmFunctionSummary) -> String {
+    function
+        .export_names
+        .first()
+        .or(function.name.as_ref())
+        .cloned()
+        .unwrap_or_else(|| format!("func[{}]", function.index))
+}
+
+/// Share of opcodes two functions have in common, from 0 (disjoint) to 1 (same opcode counts)
+fn opcode_similarity(a: &WasmFunctionSummary, b: &WasmFunctionSummary) -> f64 {
+    let mut common = 0u32;
+    let mut total = 0u32;
+    let opcodes: BTreeSet<&String> = a
+        .opcode_histogram
+        .keys()
+        .chain(b.opcode_histogram.keys())
+        .collect();
+    for opcode in opcodes {
+        let a_count = a.opcode_histogram.get(opcode).copied().unwrap_or(0);
+        let b_count = b.opcode_histogram.get(opcode).copied().unwrap_or(0);
+        common += a_count.min(b_count);
+        total += a_count.max(b_count);
+    }
+    if total == 0 {
+        return 1.0;
+    }
+    common as f64 / total as f64
+}
+
+fn is_exact_match(
+    matched_by: WasmFunctionMatch,
+    from: &WasmFunctionSummary,
+    to: &WasmFunctionSummary,
+) -> bool {
+    match matched_by {
+        WasmFunctionMatch::ExportName => from
+            .export_names
+            .iter()
+            .any(|n| to.export_names.contains(n)),
+        WasmFunctionMatch::DebugName => from.name.is_some() && from.name == to.name,
+        WasmFunctionMatch::InstructionHash => from.normalized_hash == to.normalized_hash,
+        WasmFunctionMatch::Similarity => false,
+    }
+}
+
+/// Pair functions of two modules, by export name, then debug name, then normalized instruction
+/// hash and finally by opcode similarity. Returns positions in `from.functions` mapped to
+/// positions in `to.functions`.
+fn match_functions(
+    from: &WasmModuleSummary,
+    to: &WasmModuleSummary,
+) -> BTreeMap<usize, (usize, WasmFunctionMatch)> {
+    let mut matches: BTreeMap<usize, (usize, WasmFunctionMatch)> = BTreeMap::new();
+    let mut taken: BTreeSet<usize> = BTreeSet::new();
+
+    for matched_by in [
+        WasmFunctionMatch::ExportName,
+        WasmFunctionMatch::DebugName,
+        WasmFunctionMatch::InstructionHash,
+    ] {
+        for (from_pos, from_fn) in from.functions.iter().enumerate() {
+            if matches.contains_key(&from_pos) {
+                continue;
+            }
+            let found = to.functions.iter().enumerate().find(|(to_pos, to_fn)| {
+                !taken.contains(to_pos) && is_exact_match(matched_by, from_fn, to_fn)
+            });
+            if let Some((to_pos, _)) = found {
+                matches.insert(from_pos, (to_pos, matched_by));
+                taken.insert(to_pos);
+            }
+        }
+    }
+
+    for (from_pos, from_fn) in from.functions.iter().enumerate() {
+        if matches.contains_key(&from_pos) {
+            continue;
+        }
+        let best = to
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(to_pos, _)| !taken.contains(to_pos))
+            .map(|(to_pos, to_fn)| (to_pos, opcode_similarity(from_fn, to_fn)))
+            .filter(|(_, similarity)| *similarity >= FUNCTION_SIMILARITY_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((to_pos, _)) = best {
+            matches.insert(from_pos, (to_pos, WasmFunctionMatch::Similarity));
+            taken.insert(to_pos);
+        }
+    }
+
+    matches
+}
+
+/// Get per-function diff of two modules, along with the status of every function of `to`
+fn get_wasm_function_diff(
+    from: &WasmModuleSummary,
+    to: &WasmModuleSummary,
+) -> (
+    WasmFunctionDiffSummary,
+    Vec<WasmFunctionDiff>,
+    Vec<WasmFunctionStatus>,
+) {
+    let matches = match_functions(from, to);
+    let mut summary = WasmFunctionDiffSummary::default();
+    let mut diffs = vec![];
+    let mut to_status = vec![WasmFunctionStatus::Added; to.functions.len()];
+
+    for (from_pos, from_fn) in from.functions.iter().enumerate() {
+        match matches.get(&from_pos) {
+            Some((to_pos, matched_by)) => {
+                let to_fn = &to.functions[*to_pos];
+                let status = if from_fn.normalized_hash != to_fn.normalized_hash {
+                    summary.modified += 1;
+                    WasmFunctionStatus::Modified
+                } else if from_fn.constants_hash != to_fn.constants_hash {
+                    summary.constants_modified += 1;
+                    WasmFunctionStatus::ConstantsModified
+                } else {
+                    summary.unchanged += 1;
+                    WasmFunctionStatus::Unchanged
+                };
+                to_status[*to_pos] = status;
+                if status != WasmFunctionStatus::Unchanged {
+                    diffs.push(WasmFunctionDiff {
+                        name: function_display_name(to_fn),
+                        status,
+                        matched_by: Some(*matched_by),
+                        from_index: Some(from_fn.index),
+                        to_index: Some(to_fn.index),
+                        from_instruction_count: Some(from_fn.instruction_count),
+                        to_instruction_count: Some(to_fn.instruction_count),
+                        instruction_delta: to_fn.instruction_count as i64
+                            - from_fn.instruction_count as i64,
+                    });
+                }
+            }
+            None => {
+                summary.removed += 1;
+                diffs.push(WasmFunctionDiff {
+                    name: function_display_name(from_fn),
+                    status: WasmFunctionStatus::Removed,
+                    matched_by: None,
+                    from_index: Some(from_fn.index),
+                    to_index: None,
+                    from_instruction_count: Some(from_fn.instruction_count),
+                    to_instruction_count: None,
+                    instruction_delta: -(from_fn.instruction_count as i64),
+                });
+            }
+        }
+    }
+
+    for (to_pos, to_fn) in to.functions.iter().enumerate() {
+        if to_status[to_pos] == WasmFunctionStatus::Added {
+            summary.added += 1;
+            diffs.push(WasmFunctionDiff {
+                name: function_display_name(to_fn),
+                status: WasmFunctionStatus::Added,
+                matched_by: None,
+                from_index: None,
+                to_index: Some(to_fn.index),
+                from_instruction_count: None,
+                to_instruction_count: Some(to_fn.instruction_count),
+                instruction_delta: to_fn.instruction_count as i64,
+            });
+        }
+    }
+
+    (summary, diffs, to_status)
+}
+
+/// Attribute function changes to the exported entry points that reach them through direct calls
+fn get_entry_point_logic_diff(
+    from: &WasmModuleSummary,
+    to: &WasmModuleSummary,
+    to_status: &[WasmFunctionStatus],
+) -> Vec<WasmEntryPointLogicDiff> {
+    let positions: BTreeMap<u32, usize> = to
+        .functions
+        .iter()
+        .enumerate()
+        .map(|(pos, f)| (f.index, pos))
+        .collect();
+    let is_func_export = |e: &&WasmExport| e.kind == "func";
+
+    let mut entry_points = vec![];
+    for export in to.exports.iter().filter(is_func_export) {
+        let Some(&start) = positions.get(&export.index) else {
+            // Re-exported host function, there is no logic of our own behind it
+            continue;
+        };
+
+        let mut visited = BTreeSet::from([start]);
+        let mut pending = vec![start];
+        let mut changed_functions = vec![];
+        let mut only_constants = true;
+        while let Some(pos) = pending.pop() {
+            if to_status[pos] != WasmFunctionStatus::Unchanged {
+                changed_functions.push(function_display_name(&to.functions[pos]));
+                only_constants &= to_status[pos] == WasmFunctionStatus::ConstantsModified;
+            }
+            for callee in &to.functions[pos].calls {
+                if let Some(&callee_pos) = positions.get(callee)
+                    && visited.insert(callee_pos)
+                {
+                    pending.push(callee_pos);
+                }
+            }
+        }
+        changed_functions.sort();
+
+        let existed = from
+            .exports
+            .iter()
+            .filter(is_func_export)
+            .any(|e| e.name == export.name);
+        let status = if !existed {
+            WasmFunctionStatus::Added
+        } else if changed_functions.is_empty() {
+            WasmFunctionStatus::Unchanged
+        } else if only_constants {
+            WasmFunctionStatus::ConstantsModified
+        } else {
+            WasmFunctionStatus::Modified
+        };
+        entry_points.push(WasmEntryPointLogicDiff {
+            entry_point: export.name.clone(),
+            status,
+            changed_functions,
+        });
+    }
+
+    for export in from.exports.iter().filter(is_func_export) {
+        if !to
+            .exports
+            .iter()
+            .filter(is_func_export)
+            .any(|e| e.name == export.name)
+        {
+            entry_points.push(WasmEntryPointLogicDiff {
+                entry_point: export.name.clone(),
+                status: WasmFunctionStatus::Removed,
+                changed_functions: vec![],
+            });
+        }
+    }
+
+    entry_points
+}
+
+/// Get structural diff of two parsed Wasm modules
+pub fn get_wasm_module_diff(
+    from_wasm_hash: &str,
+    from: &WasmModuleSummary,
+    to_wasm_hash: &str,
+    to: &WasmModuleSummary,
+) -> ContractWasmDiff {
+    let code_size =
+        |m: &WasmModuleSummary| m.functions.iter().map(|f| f.body_size as u64).sum::<u64>();
+
+    // Export indices shift whenever functions are added, only name and kind identify an export
+    let exports = diff_items(&from.exports, &to.exports, |e| {
+        (e.name.clone(), e.kind.clone())
+    })
+    .into_iter()
+    .filter(|d| !matches!(d, WasmItemDiff::Modified { .. }))
+    .collect();
+
+    let (function_summary, functions, to_status) = get_wasm_function_diff(from, to);
+    let entry_points = get_entry_point_logic_diff(from, to, &to_status);
+
+    ContractWasmDiff {
+        from_wasm_hash: strip_hash_prefix(from_wasm_hash),
+        to_wasm_hash: strip_hash_prefix(to_wasm_hash),
+        is_identical: from.module_hash == to.module_hash,
+        // Data and custom sections change with every build, only function logic counts
+        is_logic_change: function_summary.modified > 0
+            || function_summary.added > 0
+            || function_summary.removed > 0,
+        module_size: WasmCountDiff {
+            from: from.size as u64,
+            to: to.size as u64,
+        },
+        function_count: WasmCountDiff {
+            from: from.functions.len() as u64,
+            to: to.functions.len() as u64,
+        },
+        code_size: WasmCountDiff {
+            from: code_size(from),
+            to: code_size(to),
+        },
+        imports: diff_items(&from.imports, &to.imports, |i| {
+            (i.module.clone(), i.name.clone())
+        }),
+        exports,
+        data_segments: diff_items(&from.data_segments, &to.data_segments, |d| d.index),
+        custom_sections: diff_items(&from.custom_sections, &to.custom_sections, |c| {
+            c.name.clone()
+        }),
+        function_summary,
+        functions,
+        entry_points,
+    }
+}
+
+/// Get structural diff of the Wasm behind two contract wasm hashes, each fetched from its own node
+pub async fn get_contract_wasm_diff(
+    pool: &PgPool,
+    (from_node_address, from_wasm_hash): (&str, &str),
+    (to_node_address, to_wasm_hash): (&str, &str),
+) -> Result<ContractWasmDiff, String> {
+    let from_bytes = get_contract_wasm(pool, from_node_address, from_wasm_hash).await?;
+    let to_bytes = get_contract_wasm(pool, to_node_address, to_wasm_hash).await?;
+    let from = parse_wasm_module(&from_bytes)?;
+    let to = parse_wasm_module(&to_bytes)?;
+    Ok(get_wasm_module_diff(
+        from_wasm_hash,
+        &from,
+        to_wasm_hash,
+        &to,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(call_body: &str, fee: i32, data: &str) -> WasmModuleSummary {
+        let wat = format!(
+            r#"(module
+                (memory 1)
+                (func $fee (result i32) i32.const {fee})
+                (func (export "call") {call_body})
+                (data (i32.const 0) "{data}"))"#
+        );
+        parse_wasm_module(&wat::parse_str(wat).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn identical_modules_match_under_different_hashes() {
+        let from = module("call $fee drop", 10, "v1");
+        let to = module("call $fee drop", 10, "v1");
+        let diff = get_wasm_module_diff("hash-a", &from, "hash-b", &to);
+        assert!(diff.is_identical);
+        assert!(!get_wasm_module_diff("a", &from, "b", &module("nop", 10, "v1")).is_identical);
+    }
+
+    #[test]
+    fn data_changes_are_not_logic_changes() {
+        let from = module("call $fee drop", 10, "v1");
+        let to = module("call $fee drop", 10, "version 2");
+        let diff = get_wasm_module_diff("a", &from, "b", &to);
+        assert!(!diff.is_logic_change);
+        assert_eq!(diff.function_summary.unchanged, 2);
+    }
+
+    #[test]
+    fn constant_changes_are_not_logic_changes() {
+        let from = module("call $fee drop", 10, "v1");
+        let to = module("call $fee drop", 20, "v1");
+        let diff = get_wasm_module_diff("a", &from, "b", &to);
+        assert!(!diff.is_logic_change);
+        assert_eq!(diff.function_summary.constants_modified, 1);
+        let call = diff.entry_points.iter().find(|e| e.entry_point == "call");
+        assert_eq!(
+            call.map(|e| e.status),
+            Some(WasmFunctionStatus::ConstantsModified)
+        );
+    }
+
+    #[test]
+    fn instruction_changes_are_logic_changes() {
+        let from = module("call $fee drop", 10, "v1");
+        let to = module("call $fee call $fee i32.add drop", 10, "v1");
+        let diff = get_wasm_module_diff("a", &from, "b", &to);
+        assert!(diff.is_logic_change);
+        assert_eq!(diff.function_summary.modified, 1);
+    }
+}
@@ -0,0 +1,88 @@
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::schema::alert::AlertSchema;
+
+/// Insert an alert unless one with the same dedup key was already raised, returning whether it was inserted
+pub async fn insert_alert(pool: &PgPool, alert: &AlertSchema) -> Result<bool, Error> {
+    let result = query!(
+        r#"
+        INSERT INTO alerts (id, user_id, package_hash, kind, message, details, dedup_key, acknowledged, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (user_id, dedup_key) DO NOTHING
+        "#,
+        alert.id,
+        alert.user_id,
+        alert.package_hash,
+        alert.kind,
+        alert.message,
+        alert.details,
+        alert.dedup_key,
+        alert.acknowledged,
+        alert.created_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get alerts of a user, newest first, optionally limited to one package and to unacknowledged alerts
+pub async fn get_alerts(
+    pool: &PgPool,
+    user_id: &Uuid,
+    package_hash: Option<&str>,
+    unacknowledged_only: bool,
+) -> Result<Vec<AlertSchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT id, user_id, package_hash, kind, message, details, dedup_key, acknowledged, created_at
+        FROM alerts
+        WHERE user_id = $1
+            AND ($2::TEXT IS NULL OR package_hash = $2)
+            AND (NOT $3 OR NOT acknowledged)
+        ORDER BY created_at DESC
+        "#,
+        user_id,
+        package_hash,
+        unacknowledged_only
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| AlertSchema {
+            id: r.id,
+            user_id: r.user_id,
+            package_hash: r.package_hash,
+            kind: r.kind,
+            message: r.message,
+            details: r.details,
+            dedup_key: r.dedup_key,
+            acknowledged: r.acknowledged,
+            created_at: r.created_at,
+        })
+        .collect())
+}
+
+/// Mark an alert as acknowledged, returning whether an alert of the user was found
+pub async fn acknowledge_alert(
+    pool: &PgPool,
+    user_id: &Uuid,
+    alert_id: &Uuid,
+) -> Result<bool, Error> {
+    let result = query!(
+        r#"
+        UPDATE alerts
+        SET acknowledged = TRUE
+        WHERE id = $1 AND user_id = $2
+        "#,
+        alert_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
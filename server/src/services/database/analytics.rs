@@ -0,0 +1,373 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::api::analytics::{
+    AnalyticsBucket, AnalyticsGroup, CallBucket, CallStats, GroupedCallStats, ImpactedCaller,
+    PackageContractCaller, TopCaller,
+};
+
+fn success_rate(calls: i64, failures: i64) -> f64 {
+    if calls == 0 {
+        0.0
+    } else {
+        (calls - failures) as f64 / calls as f64
+    }
+}
+
+/// Get call statistics of a package over an optional time range, the most called groups first
+pub async fn get_call_stats(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    group: AnalyticsGroup,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<GroupedCallStats>, Error> {
+    let rows = query!(
+        r#"
+        SELECT
+            CASE $4
+                WHEN 'entry_point' THEN entry_point_name
+                WHEN 'version' THEN transaction_contract_version(contract_package_hash, $3, contract_hash, timestamp)::TEXT
+            END AS "group",
+            COUNT(*) AS "calls!",
+            COUNT(*) FILTER (WHERE COALESCE(error_message, '') <> '') AS "failures!",
+            COUNT(DISTINCT caller_public_key) AS "unique_callers!",
+            AVG(cost)::FLOAT8 AS cost_avg,
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY cost::FLOAT8) AS cost_p50,
+            percentile_cont(0.9) WITHIN GROUP (ORDER BY cost::FLOAT8) AS cost_p90,
+            percentile_cont(0.99) WITHIN GROUP (ORDER BY cost::FLOAT8) AS cost_p99
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2
+            AND ($5::TIMESTAMPTZ IS NULL OR timestamp >= $5)
+            AND ($6::TIMESTAMPTZ IS NULL OR timestamp < $6)
+        GROUP BY 1
+        ORDER BY 2 DESC, 1
+        "#,
+        network,
+        contract_package_hash,
+        user_id,
+        group.as_str(),
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| GroupedCallStats {
+            group: r.group,
+            stats: CallStats {
+                calls: r.calls as u64,
+                failures: r.failures as u64,
+                success_rate: success_rate(r.calls, r.failures),
+                unique_callers: r.unique_callers as u64,
+                cost_avg: r.cost_avg,
+                cost_p50: r.cost_p50,
+                cost_p90: r.cost_p90,
+                cost_p99: r.cost_p99,
+            },
+        })
+        .collect())
+}
+
+/// Get the calls of a package per bucket in `[from, to)`, optionally of one entry point
+pub async fn get_call_series(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    bucket: AnalyticsBucket,
+    entry_point: Option<&str>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<CallBucket>, Error> {
+    let rows = query!(
+        r#"
+        SELECT
+            b.bucket AS "bucket!",
+            COUNT(t.deploy_hash) AS "calls!",
+            COUNT(t.deploy_hash) FILTER (WHERE COALESCE(t.error_message, '') <> '') AS "failures!",
+            COUNT(DISTINCT t.caller_public_key) AS "unique_callers!"
+        FROM generate_series(
+            date_trunc($3, $5::TIMESTAMPTZ, 'UTC'),
+            $6::TIMESTAMPTZ - INTERVAL '1 microsecond',
+            ('1 ' || $3)::INTERVAL
+        ) AS b(bucket)
+        LEFT JOIN transactions t
+            ON t.network = $1 AND t.contract_package_hash = $2
+            AND date_trunc($3, t.timestamp, 'UTC') = b.bucket
+            AND t.timestamp >= $5 AND t.timestamp < $6
+            AND ($4::TEXT IS NULL OR t.entry_point_name = $4)
+        GROUP BY b.bucket
+        ORDER BY b.bucket
+        "#,
+        network,
+        contract_package_hash,
+        bucket.as_str(),
+        entry_point,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| CallBucket {
+            bucket: r.bucket,
+            calls: r.calls as u64,
+            failures: r.failures as u64,
+            unique_callers: r.unique_callers as u64,
+        })
+        .collect())
+}
+
+/// Get the callers of a package with the most calls
+pub async fn get_top_callers(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: u64,
+) -> Result<Vec<TopCaller>, Error> {
+    let rows = query!(
+        r#"
+        SELECT
+            caller_public_key,
+            COUNT(*) AS "calls!",
+            COUNT(*) FILTER (WHERE COALESCE(error_message, '') <> '') AS "failures!",
+            SUM(cost)::TEXT AS "total_cost!",
+            MIN(timestamp) AS "first_call!",
+            MAX(timestamp) AS "last_call!"
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2
+            AND ($3::TIMESTAMPTZ IS NULL OR timestamp >= $3)
+            AND ($4::TIMESTAMPTZ IS NULL OR timestamp < $4)
+        GROUP BY caller_public_key
+        ORDER BY 2 DESC, caller_public_key
+        LIMIT $5
+        "#,
+        network,
+        contract_package_hash,
+        from,
+        to,
+        limit as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| TopCaller {
+            caller_public_key: r.caller_public_key,
+            calls: r.calls as u64,
+            failures: r.failures as u64,
+            total_cost: r.total_cost,
+            first_call: r.first_call,
+            last_call: r.last_call,
+        })
+        .collect())
+}
+
+/// Get the costs of the latest successful calls of a package per entry point, for the given versions
+pub async fn get_entry_point_costs(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    versions: &[u32],
+    max_samples: u64,
+) -> Result<Vec<(u32, String, f64)>, Error> {
+    let versions: Vec<i32> = versions.iter().map(|v| *v as i32).collect();
+    let rows = query!(
+        r#"
+        WITH bounds AS (
+            SELECT MIN(age) FILTER (WHERE version = ANY($4)) AS installed_from,
+                MIN(age) FILTER (WHERE version > (SELECT MAX(v) FROM UNNEST($4::INTEGER[]) AS v)) AS superseded_at
+            FROM contract_versions
+            WHERE contract_package_hash = $2 AND user_id = $3
+        ), calls AS (
+            SELECT transaction_contract_version(t.contract_package_hash, $3, t.contract_hash, t.timestamp) AS version,
+                t.entry_point_name, t.cost::FLOAT8 AS cost, t.timestamp
+            FROM transactions t, bounds b
+            WHERE t.network = $1 AND t.contract_package_hash = $2
+                AND t.entry_point_name IS NOT NULL AND COALESCE(t.error_message, '') = ''
+                -- Narrow down by time before resolving versions: none of the versions is called before
+                -- the first is installed, and calls through the package go to later versions once installed
+                AND t.timestamp >= b.installed_from
+                AND (t.contract_hash IS NOT NULL OR b.superseded_at IS NULL OR t.timestamp < b.superseded_at)
+        ), ranked AS (
+            SELECT version, entry_point_name, cost,
+                ROW_NUMBER() OVER (PARTITION BY version, entry_point_name ORDER BY timestamp DESC) AS rank
+            FROM calls
+            WHERE version = ANY($4)
+        )
+        SELECT version AS "version!", entry_point_name AS "entry_point_name!", cost AS "cost!"
+        FROM ranked
+        WHERE rank <= $5
+        "#,
+        network,
+        contract_package_hash,
+        user_id,
+        &versions,
+        max_samples as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.version as u32, r.entry_point_name, r.cost))
+        .collect())
+}
+
+/// Get the callers of the given entry points since `from`, with their calls before `upgraded_at`
+/// and how their calls fared afterwards
+pub async fn get_entry_point_callers(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    entry_points: &[String],
+    upgraded_at: DateTime<Utc>,
+    from: DateTime<Utc>,
+) -> Result<Vec<ImpactedCaller>, Error> {
+    let rows = query!(
+        r#"
+        SELECT
+            caller_public_key,
+            entry_point_name AS "entry_point_name!",
+            COUNT(*) FILTER (WHERE timestamp < $4) AS "calls!",
+            MAX(timestamp) FILTER (WHERE timestamp < $4) AS "last_call!",
+            COUNT(*) FILTER (WHERE timestamp >= $4) AS "calls_after_upgrade!",
+            COUNT(*) FILTER (WHERE timestamp >= $4 AND COALESCE(error_message, '') <> '') AS "failures_after_upgrade!",
+            MIN(timestamp) FILTER (WHERE timestamp >= $4 AND COALESCE(error_message, '') <> '') AS first_failure_after_upgrade
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2
+            AND entry_point_name = ANY($3) AND timestamp >= $5
+        GROUP BY caller_public_key, entry_point_name
+        HAVING COUNT(*) FILTER (WHERE timestamp < $4) > 0
+        ORDER BY 3 DESC, caller_public_key, entry_point_name
+        "#,
+        network,
+        contract_package_hash,
+        entry_points,
+        upgraded_at,
+        from
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ImpactedCaller {
+            caller_public_key: r.caller_public_key,
+            entry_point_name: r.entry_point_name,
+            calls: r.calls as u64,
+            last_call: r.last_call,
+            calls_after_upgrade: r.calls_after_upgrade as u64,
+            failures_after_upgrade: r.failures_after_upgrade as u64,
+            first_failure_after_upgrade: r.first_failure_after_upgrade,
+        })
+        .collect())
+}
+
+/// Get the contracts and session code that called into a package since `from`, with their calls
+/// before `upgraded_at` and how their calls fared afterwards
+pub async fn get_contract_callers(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    upgraded_at: DateTime<Utc>,
+    from: DateTime<Utc>,
+) -> Result<Vec<PackageContractCaller>, Error> {
+    let rows = query!(
+        r#"
+        SELECT
+            caller_contract_hash,
+            COUNT(*) FILTER (WHERE timestamp < $3) AS "calls!",
+            MAX(timestamp) FILTER (WHERE timestamp < $3) AS "last_call!",
+            COUNT(*) FILTER (WHERE timestamp >= $3) AS "calls_after_upgrade!",
+            COUNT(*) FILTER (WHERE timestamp >= $3 AND COALESCE(error_message, '') <> '') AS "failures_after_upgrade!",
+            MIN(timestamp) FILTER (WHERE timestamp >= $3 AND COALESCE(error_message, '') <> '') AS first_failure_after_upgrade
+        FROM indirect_calls
+        WHERE network = $1 AND contract_package_hash = $2 AND timestamp >= $4
+        GROUP BY caller_contract_hash
+        HAVING COUNT(*) FILTER (WHERE timestamp < $3) > 0
+        ORDER BY 2 DESC, caller_contract_hash
+        "#,
+        network,
+        contract_package_hash,
+        upgraded_at,
+        from
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| PackageContractCaller {
+            caller_contract_hash: r.caller_contract_hash,
+            calls: r.calls as u64,
+            last_call: r.last_call,
+            calls_after_upgrade: r.calls_after_upgrade as u64,
+            failures_after_upgrade: r.failures_after_upgrade as u64,
+            first_failure_after_upgrade: r.first_failure_after_upgrade,
+        })
+        .collect())
+}
+
+/// Calls per entry point of one contract version
+pub struct EntryPointCalls {
+    pub entry_point_name: String,
+    pub calls: u64,
+    pub callers: u64,
+    pub owner_calls: u64,
+    pub last_call: DateTime<Utc>,
+}
+
+/// Count the calls of each entry point of a contract version, and those made by the given owners
+pub async fn get_version_entry_point_calls(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    version: u32,
+    owners: &[String],
+) -> Result<Vec<EntryPointCalls>, Error> {
+    let rows = query!(
+        r#"
+        SELECT
+            entry_point_name AS "entry_point_name!",
+            COUNT(*) AS "calls!",
+            COUNT(DISTINCT caller_public_key) AS "callers!",
+            COUNT(*) FILTER (WHERE lower(caller_public_key) = ANY($5)) AS "owner_calls!",
+            MAX(timestamp) AS "last_call!"
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2 AND entry_point_name IS NOT NULL
+            AND transaction_contract_version(contract_package_hash, $3, contract_hash, timestamp) = $4
+        GROUP BY entry_point_name
+        "#,
+        network,
+        contract_package_hash,
+        user_id,
+        version as i32,
+        owners
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| EntryPointCalls {
+            entry_point_name: r.entry_point_name,
+            calls: r.calls as u64,
+            callers: r.callers as u64,
+            owner_calls: r.owner_calls as u64,
+            last_call: r.last_call,
+        })
+        .collect())
+}
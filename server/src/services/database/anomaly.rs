@@ -0,0 +1,364 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::schema::anomaly::{
+    AnomalyMetric, TransactionAnomalySchema, TransactionBaselineSchema,
+};
+
+pub async fn upsert_transaction_baseline(
+    pool: &PgPool,
+    baseline: &TransactionBaselineSchema,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO transaction_baselines (contract_package_hash, user_id, metric, mean, stddev, samples, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (contract_package_hash, user_id, metric) DO UPDATE SET
+            mean = EXCLUDED.mean,
+            stddev = EXCLUDED.stddev,
+            samples = EXCLUDED.samples,
+            updated_at = EXCLUDED.updated_at
+        "#,
+        baseline.contract_package_hash,
+        baseline.user_id,
+        baseline.metric.as_str(),
+        baseline.mean,
+        baseline.stddev,
+        baseline.samples as i64,
+        baseline.updated_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_transaction_baselines(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+) -> Result<Vec<TransactionBaselineSchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT contract_package_hash, user_id, metric, mean, stddev, samples, updated_at
+        FROM transaction_baselines
+        WHERE contract_package_hash = $1 AND user_id = $2
+        ORDER BY metric
+        "#,
+        contract_package_hash,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| {
+            Some(TransactionBaselineSchema {
+                contract_package_hash: r.contract_package_hash,
+                user_id: r.user_id,
+                metric: AnomalyMetric::parse(&r.metric)?,
+                mean: r.mean,
+                stddev: r.stddev,
+                samples: r.samples as u64,
+                updated_at: r.updated_at,
+            })
+        })
+        .collect())
+}
+
+/// Record an anomaly unless the same one was already recorded, returning whether it was inserted
+pub async fn insert_transaction_anomaly(
+    pool: &PgPool,
+    anomaly: &TransactionAnomalySchema,
+) -> Result<bool, Error> {
+    let result = query!(
+        r#"
+        INSERT INTO transaction_anomalies (
+            id, contract_package_hash, user_id, metric, window_start, window_end, observed,
+            expected, p_value, message, evidence, dedup_key, created_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (user_id, dedup_key) DO NOTHING
+        "#,
+        anomaly.id,
+        anomaly.contract_package_hash,
+        anomaly.user_id,
+        anomaly.metric.as_str(),
+        anomaly.window_start,
+        anomaly.window_end,
+        anomaly.observed,
+        anomaly.expected,
+        anomaly.p_value,
+        anomaly.message,
+        anomaly.evidence,
+        anomaly.dedup_key,
+        anomaly.created_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the anomalies of a package, the most recent windows first
+pub async fn get_transaction_anomalies(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    metric: Option<AnomalyMetric>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: u64,
+) -> Result<Vec<TransactionAnomalySchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT
+            id, contract_package_hash, user_id, metric, window_start, window_end, observed,
+            expected, p_value, message, evidence, dedup_key, created_at
+        FROM transaction_anomalies
+        WHERE contract_package_hash = $1 AND user_id = $2
+            AND ($3::TEXT IS NULL OR metric = $3)
+            AND ($4::TIMESTAMPTZ IS NULL OR window_end > $4)
+            AND ($5::TIMESTAMPTZ IS NULL OR window_start < $5)
+        ORDER BY window_start DESC, created_at DESC
+        LIMIT $6
+        "#,
+        contract_package_hash,
+        user_id,
+        metric.map(|m| m.as_str()),
+        from,
+        to,
+        limit as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| {
+            Some(TransactionAnomalySchema {
+                id: r.id,
+                contract_package_hash: r.contract_package_hash,
+                user_id: r.user_id,
+                metric: AnomalyMetric::parse(&r.metric)?,
+                window_start: r.window_start,
+                window_end: r.window_end,
+                observed: r.observed,
+                expected: r.expected,
+                p_value: r.p_value,
+                message: r.message,
+                evidence: r.evidence,
+                dedup_key: r.dedup_key,
+                created_at: r.created_at,
+            })
+        })
+        .collect())
+}
+
+/// Count the callers calling a package for the first time per hour, empty hours included
+pub async fn get_new_caller_series(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, u64)>, Error> {
+    let rows = query!(
+        r#"
+        SELECT b.bucket AS "bucket!", COUNT(f.caller_public_key) AS "new_callers!"
+        FROM generate_series(
+            date_trunc('hour', $3::TIMESTAMPTZ, 'UTC'),
+            $4::TIMESTAMPTZ - INTERVAL '1 microsecond',
+            INTERVAL '1 hour'
+        ) AS b(bucket)
+        LEFT JOIN (
+            SELECT caller_public_key, MIN(timestamp) AS first_call
+            FROM transactions
+            WHERE network = $1 AND contract_package_hash = $2
+            GROUP BY caller_public_key
+        ) f ON date_trunc('hour', f.first_call, 'UTC') = b.bucket
+        GROUP BY b.bucket
+        ORDER BY b.bucket
+        "#,
+        network,
+        contract_package_hash,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.bucket, r.new_callers as u64))
+        .collect())
+}
+
+/// Get the costs of the latest successful calls of a package within a time range
+pub async fn get_cost_samples(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_samples: u64,
+) -> Result<Vec<f64>, Error> {
+    let rows = query!(
+        r#"
+        SELECT cost::FLOAT8 AS "cost!"
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2
+            AND timestamp >= $3 AND timestamp < $4
+            AND COALESCE(error_message, '') = ''
+        ORDER BY timestamp DESC
+        LIMIT $5
+        "#,
+        network,
+        contract_package_hash,
+        from,
+        to,
+        max_samples as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.cost).collect())
+}
+
+/// Calls of one entry point by a caller that is not an owner and never called the package before
+pub struct UnknownCallerCalls {
+    pub caller_public_key: String,
+    pub entry_point_name: String,
+    pub contract_version: Option<u32>,
+    pub calls: u64,
+    pub failures: u64,
+    /// Whether owners called the entry point before
+    pub owner_used: bool,
+    /// Whether callers other than the owners called the entry point before
+    pub others_used: bool,
+}
+
+/// Get the entry points callers unknown before `from` called at least `min_calls` times up to `to`
+#[allow(clippy::too_many_arguments)]
+pub async fn get_unknown_caller_calls(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    owners: &[String],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    min_calls: u64,
+) -> Result<Vec<UnknownCallerCalls>, Error> {
+    let rows = query!(
+        r#"
+        WITH window_calls AS (
+            SELECT
+                caller_public_key,
+                entry_point_name,
+                transaction_contract_version(contract_package_hash, $3, contract_hash, timestamp) AS contract_version,
+                COUNT(*) AS calls,
+                COUNT(*) FILTER (WHERE COALESCE(error_message, '') <> '') AS failures
+            FROM transactions
+            WHERE network = $1 AND contract_package_hash = $2 AND entry_point_name IS NOT NULL
+                AND timestamp >= $5 AND timestamp < $6
+                AND NOT (lower(caller_public_key) = ANY($4))
+            GROUP BY 1, 2, 3
+            HAVING COUNT(*) >= $7
+        )
+        SELECT
+            w.caller_public_key AS "caller_public_key!",
+            w.entry_point_name AS "entry_point_name!",
+            w.contract_version,
+            w.calls AS "calls!",
+            w.failures AS "failures!",
+            EXISTS (
+                SELECT 1 FROM transactions p
+                WHERE p.network = $1 AND p.contract_package_hash = $2
+                    AND p.entry_point_name = w.entry_point_name AND p.timestamp < $5
+                    AND lower(p.caller_public_key) = ANY($4)
+            ) AS "owner_used!",
+            EXISTS (
+                SELECT 1 FROM transactions p
+                WHERE p.network = $1 AND p.contract_package_hash = $2
+                    AND p.entry_point_name = w.entry_point_name AND p.timestamp < $5
+                    AND NOT (lower(p.caller_public_key) = ANY($4))
+            ) AS "others_used!"
+        FROM window_calls w
+        WHERE NOT EXISTS (
+            SELECT 1 FROM transactions p
+            WHERE p.network = $1 AND p.contract_package_hash = $2
+                AND p.caller_public_key = w.caller_public_key AND p.timestamp < $5
+        )
+        ORDER BY w.calls DESC
+        "#,
+        network,
+        contract_package_hash,
+        user_id,
+        owners,
+        from,
+        to,
+        min_calls as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| UnknownCallerCalls {
+            caller_public_key: r.caller_public_key,
+            entry_point_name: r.entry_point_name,
+            contract_version: r.contract_version.map(|v| v as u32),
+            calls: r.calls as u64,
+            failures: r.failures as u64,
+            owner_used: r.owner_used,
+            others_used: r.others_used,
+        })
+        .collect())
+}
+
+/// Get the end of the last hour the anomaly detection checked for a package
+pub async fn get_anomaly_check_cursor(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    let row = query!(
+        r#"
+        SELECT checked_until
+        FROM anomaly_check_cursors
+        WHERE contract_package_hash = $1 AND user_id = $2
+        "#,
+        contract_package_hash,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.checked_until))
+}
+
+pub async fn upsert_anomaly_check_cursor(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    checked_until: DateTime<Utc>,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO anomaly_check_cursors (contract_package_hash, user_id, checked_until, updated_at)
+        VALUES ($1, $2, $3, now())
+        ON CONFLICT (contract_package_hash, user_id)
+        DO UPDATE SET checked_until = EXCLUDED.checked_until, updated_at = now()
+        "#,
+        contract_package_hash,
+        user_id,
+        checked_until
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -64,6 +64,8 @@ pub async fn insert_contract_package(
     Ok(())
 }
 
+/// Insert contract versions, filling in the provenance of versions stored without it, e.g. before
+/// it was recorded, so registering a package again backfills it
 pub async fn insert_contract_package_versions(
     pool: &PgPool,
     contract_versions: Vec<ContractVersionSchema>,
@@ -86,9 +88,17 @@ pub async fn insert_contract_package_versions(
                 entry_points,
                 named_keys,
                 disabled, 
-                age
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            ON CONFLICT (contract_package_hash, version, user_id) DO NOTHING
+                age,
+                deploy_hash,
+                block_height,
+                deployer_public_key,
+                upgrade_cost
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT (contract_package_hash, version, user_id) DO UPDATE SET
+                deploy_hash = COALESCE(contract_versions.deploy_hash, EXCLUDED.deploy_hash),
+                block_height = COALESCE(contract_versions.block_height, EXCLUDED.block_height),
+                deployer_public_key = COALESCE(contract_versions.deployer_public_key, EXCLUDED.deployer_public_key),
+                upgrade_cost = COALESCE(contract_versions.upgrade_cost, EXCLUDED.upgrade_cost)
             "#,
             contract_version.contract_hash,
             contract_version.contract_package_hash,
@@ -100,7 +110,11 @@ pub async fn insert_contract_package_versions(
             serde_json::to_value(&contract_version.entry_points).unwrap(),
             serde_json::to_value(&contract_version.named_keys).unwrap(),
             contract_version.disabled,
-            contract_version.age
+            contract_version.age,
+            contract_version.deploy_hash,
+            contract_version.block_height.map(|h| h as i64),
+            contract_version.deployer_public_key,
+            contract_version.upgrade_cost
         )
         .execute(pool)
         .await?;
@@ -128,7 +142,11 @@ pub async fn get_contract_version(
             entry_points,
             named_keys,
             disabled, 
-            age
+            age,
+            deploy_hash,
+            block_height,
+            deployer_public_key,
+            upgrade_cost
         FROM contract_versions
         WHERE contract_package_hash = $1 AND version = $2 AND user_id = $3
         "#,
@@ -163,6 +181,10 @@ pub async fn get_contract_version(
                 named_keys,
                 disabled: r.disabled,
                 age: r.age,
+                deploy_hash: r.deploy_hash,
+                block_height: r.block_height.map(|h| h as u64),
+                deployer_public_key: r.deployer_public_key,
+                upgrade_cost: r.upgrade_cost,
             }))
         }
         None => Ok(None),
@@ -225,7 +247,11 @@ pub async fn get_contract_versions(
             entry_points,
             named_keys,
             disabled, 
-            age
+            age,
+            deploy_hash,
+            block_height,
+            deployer_public_key,
+            upgrade_cost
         FROM contract_versions
         WHERE contract_package_hash = $1 AND user_id = $2
         ORDER BY version DESC
@@ -255,8 +281,37 @@ pub async fn get_contract_versions(
             named_keys,
             disabled: r.disabled,
             age: r.age,
+            deploy_hash: r.deploy_hash,
+            block_height: r.block_height.map(|h| h as u64),
+            deployer_public_key: r.deployer_public_key,
+            upgrade_cost: r.upgrade_cost,
         });
     }
 
     Ok(versions)
 }
+
+/// Get the tracked packages of every user
+pub async fn get_all_contract_packages(pool: &PgPool) -> Result<Vec<ContractPackageSchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT package_hash, user_id, contract_name, owner_id, network, lock_status, age
+        FROM contract_packages
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ContractPackageSchema {
+            package_hash: r.package_hash,
+            user_id: r.user_id,
+            contract_name: r.contract_name,
+            owner_id: r.owner_id,
+            network: r.network,
+            lock_status: r.lock_status,
+            age: r.age,
+        })
+        .collect())
+}
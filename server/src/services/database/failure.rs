@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::schema::transaction::{ContractErrorCodeSchema, TransactionOutcomeCount};
+
+pub async fn get_contract_error_codes(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+) -> Result<Vec<ContractErrorCodeSchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT code, name, description
+        FROM contract_error_codes
+        WHERE contract_package_hash = $1 AND user_id = $2
+        ORDER BY code
+        "#,
+        contract_package_hash,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ContractErrorCodeSchema {
+            code: r.code as u32,
+            name: r.name,
+            description: r.description,
+        })
+        .collect())
+}
+
+/// Replace the error codes of a package with the given ones
+pub async fn replace_contract_error_codes(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    error_codes: &[ContractErrorCodeSchema],
+) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+    query!(
+        r#"
+        DELETE FROM contract_error_codes
+        WHERE contract_package_hash = $1 AND user_id = $2
+        "#,
+        contract_package_hash,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for error_code in error_codes {
+        query!(
+            r#"
+            INSERT INTO contract_error_codes (contract_package_hash, user_id, code, name, description)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            contract_package_hash,
+            user_id,
+            error_code.code as i32,
+            error_code.name,
+            error_code.description
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Count the indexed transactions of a package per called version, entry point and error message
+pub async fn get_transaction_outcome_counts(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<TransactionOutcomeCount>, Error> {
+    let rows = query!(
+        r#"
+        SELECT transaction_contract_version(contract_package_hash, $3, contract_hash, timestamp) AS version,
+            entry_point_name, NULLIF(error_message, '') AS error_message, COUNT(*) AS "count!"
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2
+            AND ($4::TIMESTAMPTZ IS NULL OR timestamp >= $4)
+            AND ($5::TIMESTAMPTZ IS NULL OR timestamp < $5)
+        GROUP BY 1, 2, 3
+        "#,
+        network,
+        contract_package_hash,
+        user_id,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| TransactionOutcomeCount {
+            contract_version: r.version.map(|v| v as u32),
+            entry_point_name: r.entry_point_name,
+            error_message: r.error_message,
+            count: r.count as u64,
+        })
+        .collect())
+}
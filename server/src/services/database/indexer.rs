@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool, query};
+
+use crate::models::schema::transaction::{
+    ContractPackageWrite, IndirectCall, PackageBackfillCursor,
+};
+
+pub async fn get_block_index_cursor(pool: &PgPool, network: &str) -> Result<Option<u64>, Error> {
+    let row = query!(
+        r#"
+        SELECT next_height
+        FROM block_index_cursors
+        WHERE network = $1
+        "#,
+        network
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.next_height as u64))
+}
+
+/// Get the timestamp of the last block the block indexer read on a network
+pub async fn get_block_indexed_until(
+    pool: &PgPool,
+    network: &str,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    let row = query!(
+        r#"
+        SELECT indexed_until
+        FROM block_index_cursors
+        WHERE network = $1
+        "#,
+        network
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.and_then(|r| r.indexed_until))
+}
+
+/// Move the cursor of a network past a block, along with the block timestamp when it was read
+pub async fn upsert_block_index_cursor(
+    pool: &PgPool,
+    network: &str,
+    next_height: u64,
+    indexed_until: Option<DateTime<Utc>>,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO block_index_cursors (network, next_height, indexed_until, updated_at)
+        VALUES ($1, $2, $3, now())
+        ON CONFLICT (network)
+        DO UPDATE SET next_height = EXCLUDED.next_height, indexed_until = COALESCE(EXCLUDED.indexed_until, block_index_cursors.indexed_until), updated_at = now()
+        "#,
+        network,
+        next_height as i64,
+        indexed_until
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a block that failed to index, counting the attempt when it is already a known gap
+pub async fn upsert_block_index_gap(
+    pool: &PgPool,
+    network: &str,
+    height: u64,
+    error: &str,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO block_index_gaps (network, height, error)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (network, height)
+        DO UPDATE SET error = EXCLUDED.error, attempts = block_index_gaps.attempts + 1, updated_at = now()
+        "#,
+        network,
+        height as i64,
+        error
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the lowest gaps of a network that have attempts left
+pub async fn get_block_index_gaps(
+    pool: &PgPool,
+    network: &str,
+    max_attempts: u32,
+    limit: u64,
+) -> Result<Vec<u64>, Error> {
+    let rows = query!(
+        r#"
+        SELECT height
+        FROM block_index_gaps
+        WHERE network = $1 AND attempts < $2
+        ORDER BY height
+        LIMIT $3
+        "#,
+        network,
+        max_attempts as i32,
+        limit as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.height as u64).collect())
+}
+
+pub async fn delete_block_index_gap(
+    pool: &PgPool,
+    network: &str,
+    height: u64,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        DELETE FROM block_index_gaps
+        WHERE network = $1 AND height = $2
+        "#,
+        network,
+        height as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Schedule the rescan of blocks for a package, widening the range of a rescan already scheduled
+pub async fn upsert_package_backfill_cursor(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    next_height: u64,
+    end_height: u64,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO package_backfill_cursors (network, contract_package_hash, next_height, end_height, updated_at)
+        VALUES ($1, $2, $3, $4, now())
+        ON CONFLICT (network, contract_package_hash)
+        DO UPDATE SET next_height = LEAST(package_backfill_cursors.next_height, EXCLUDED.next_height), end_height = GREATEST(package_backfill_cursors.end_height, EXCLUDED.end_height), updated_at = now()
+        "#,
+        network,
+        contract_package_hash,
+        next_height as i64,
+        end_height as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_package_backfill_cursors(
+    pool: &PgPool,
+    network: &str,
+) -> Result<Vec<PackageBackfillCursor>, Error> {
+    let rows = query!(
+        r#"
+        SELECT contract_package_hash, next_height, end_height
+        FROM package_backfill_cursors
+        WHERE network = $1
+        ORDER BY created_at
+        "#,
+        network
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| PackageBackfillCursor {
+            contract_package_hash: r.contract_package_hash,
+            next_height: r.next_height as u64,
+            end_height: r.end_height as u64,
+        })
+        .collect())
+}
+
+/// Move the rescan of a package forward, or drop it once it passed its end height
+pub async fn advance_package_backfill_cursor(
+    pool: &PgPool,
+    network: &str,
+    cursor: &PackageBackfillCursor,
+) -> Result<(), Error> {
+    if cursor.next_height > cursor.end_height {
+        query!(
+            r#"
+            DELETE FROM package_backfill_cursors
+            WHERE network = $1 AND contract_package_hash = $2 AND end_height = $3
+            "#,
+            network,
+            cursor.contract_package_hash,
+            cursor.end_height as i64
+        )
+        .execute(pool)
+        .await?;
+    } else {
+        query!(
+            r#"
+            UPDATE package_backfill_cursors
+            SET next_height = $3, updated_at = now()
+            WHERE network = $1 AND contract_package_hash = $2
+            "#,
+            network,
+            cursor.contract_package_hash,
+            cursor.next_height as i64
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn insert_contract_package_write(
+    pool: &PgPool,
+    write: &ContractPackageWrite,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO contract_package_writes (network, contract_package_hash, deploy_hash, block_height, timestamp, caller_public_key, cost, contract_hashes)
+        VALUES ($1, $2, $3, $4, $5, $6, COALESCE(NULLIF($7, '')::NUMERIC, 0), $8)
+        ON CONFLICT (network, contract_package_hash, deploy_hash) DO NOTHING
+        "#,
+        write.network,
+        write.contract_package_hash,
+        write.deploy_hash,
+        write.block_height as i64,
+        write.timestamp,
+        write.caller_public_key,
+        write.cost,
+        &write.contract_hashes
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the writes of a package in block order, the first one installed it
+pub async fn get_contract_package_writes(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+) -> Result<Vec<ContractPackageWrite>, Error> {
+    let rows = query!(
+        r#"
+        SELECT network, contract_package_hash, deploy_hash, block_height, timestamp, caller_public_key, cost::TEXT AS "cost!", contract_hashes
+        FROM contract_package_writes
+        WHERE network = $1 AND contract_package_hash = $2
+        ORDER BY block_height
+        "#,
+        network,
+        contract_package_hash
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ContractPackageWrite {
+            network: r.network,
+            contract_package_hash: r.contract_package_hash,
+            deploy_hash: r.deploy_hash,
+            block_height: r.block_height as u64,
+            timestamp: r.timestamp,
+            caller_public_key: r.caller_public_key,
+            cost: r.cost,
+            contract_hashes: r.contract_hashes,
+        })
+        .collect())
+}
+
+pub async fn insert_indirect_call(pool: &PgPool, call: &IndirectCall) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO indirect_calls (network, deploy_hash, contract_package_hash, caller_contract_hash, caller_public_key, timestamp, error_message)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (network, deploy_hash, contract_package_hash)
+        DO UPDATE SET error_message = EXCLUDED.error_message
+        "#,
+        call.network,
+        call.deploy_hash,
+        call.contract_package_hash,
+        call.caller_contract_hash,
+        call.caller_public_key,
+        call.timestamp,
+        call.error_message
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Map every contract seen calling tracked packages of a network to the packages it called
+pub async fn get_caller_contracts(
+    pool: &PgPool,
+    network: &str,
+) -> Result<HashMap<String, Vec<String>>, Error> {
+    let rows = query!(
+        r#"
+        SELECT DISTINCT caller_contract_hash AS "caller_contract_hash!", contract_package_hash
+        FROM indirect_calls
+        WHERE network = $1 AND caller_contract_hash IS NOT NULL
+        "#,
+        network
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut callers: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        callers
+            .entry(row.caller_contract_hash)
+            .or_default()
+            .push(row.contract_package_hash);
+    }
+    Ok(callers)
+}
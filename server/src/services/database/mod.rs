@@ -1 +1,12 @@
+pub(crate) mod alert;
+pub(crate) mod analytics;
+pub(crate) mod anomaly;
 pub(crate) mod contract;
+pub(crate) mod failure;
+pub(crate) mod indexer;
+pub(crate) mod promotion;
+pub(crate) mod rules;
+pub(crate) mod schema;
+pub(crate) mod transaction;
+pub(crate) mod verification;
+pub(crate) mod wasm;
@@ -0,0 +1,95 @@
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::schema::promotion::ContractPackageLinkSchema;
+
+pub async fn insert_contract_package_link(
+    pool: &PgPool,
+    link: &ContractPackageLinkSchema,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO contract_package_links (user_id, testnet_package_hash, mainnet_package_hash, created_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, testnet_package_hash)
+        DO UPDATE SET mainnet_package_hash = EXCLUDED.mainnet_package_hash, created_at = EXCLUDED.created_at
+        "#,
+        link.user_id,
+        link.testnet_package_hash,
+        link.mainnet_package_hash,
+        link.created_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the link a package takes part in, on either its testnet or its mainnet side
+pub async fn get_contract_package_link(
+    pool: &PgPool,
+    user_id: &Uuid,
+    package_hash: &str,
+) -> Result<Option<ContractPackageLinkSchema>, Error> {
+    let row = query!(
+        r#"
+        SELECT user_id, testnet_package_hash, mainnet_package_hash, created_at
+        FROM contract_package_links
+        WHERE user_id = $1 AND (testnet_package_hash = $2 OR mainnet_package_hash = $2)
+        "#,
+        user_id,
+        package_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| ContractPackageLinkSchema {
+        user_id: r.user_id,
+        testnet_package_hash: r.testnet_package_hash,
+        mainnet_package_hash: r.mainnet_package_hash,
+        created_at: r.created_at,
+    }))
+}
+
+pub async fn get_all_contract_package_links(
+    pool: &PgPool,
+) -> Result<Vec<ContractPackageLinkSchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT user_id, testnet_package_hash, mainnet_package_hash, created_at
+        FROM contract_package_links
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ContractPackageLinkSchema {
+            user_id: r.user_id,
+            testnet_package_hash: r.testnet_package_hash,
+            mainnet_package_hash: r.mainnet_package_hash,
+            created_at: r.created_at,
+        })
+        .collect())
+}
+
+/// Delete the link a package takes part in, returning whether one was found
+pub async fn delete_contract_package_link(
+    pool: &PgPool,
+    user_id: &Uuid,
+    package_hash: &str,
+) -> Result<bool, Error> {
+    let result = query!(
+        r#"
+        DELETE FROM contract_package_links
+        WHERE user_id = $1 AND (testnet_package_hash = $2 OR mainnet_package_hash = $2)
+        "#,
+        user_id,
+        package_hash
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
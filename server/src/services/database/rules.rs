@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::schema::rules::{DiffRuleAction, DiffRuleSchema, DiffRuleTarget};
+
+/// Get diff rules of a user, limited to global rules and rules of `package_hash` when it is given
+pub async fn get_diff_rules(
+    pool: &PgPool,
+    user_id: &Uuid,
+    package_hash: Option<&str>,
+) -> Result<Vec<DiffRuleSchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT id, user_id, package_hash, target, action, pattern, replacement, created_at
+        FROM diff_rules
+        WHERE user_id = $1
+            AND ($2::TEXT IS NULL OR package_hash IS NULL OR package_hash = $2)
+        ORDER BY created_at
+        "#,
+        user_id,
+        package_hash
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        // Rows with a target or action unknown to this build are skipped
+        .filter_map(|r| {
+            Some(DiffRuleSchema {
+                id: r.id,
+                user_id: r.user_id,
+                package_hash: r.package_hash,
+                target: DiffRuleTarget::parse(&r.target)?,
+                action: DiffRuleAction::parse(&r.action)?,
+                pattern: r.pattern,
+                replacement: r.replacement,
+                created_at: r.created_at,
+            })
+        })
+        .collect())
+}
+
+pub async fn insert_diff_rule(pool: &PgPool, rule: &DiffRuleSchema) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO diff_rules (id, user_id, package_hash, target, action, pattern, replacement, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+        rule.id,
+        rule.user_id,
+        rule.package_hash,
+        rule.target.as_str(),
+        rule.action.as_str(),
+        rule.pattern,
+        rule.replacement,
+        rule.created_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Update a diff rule, returning its creation time when a rule of the user was found
+pub async fn update_diff_rule(
+    pool: &PgPool,
+    rule: &DiffRuleSchema,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    let row = query!(
+        r#"
+        UPDATE diff_rules
+        SET package_hash = $3, target = $4, action = $5, pattern = $6, replacement = $7
+        WHERE id = $1 AND user_id = $2
+        RETURNING created_at
+        "#,
+        rule.id,
+        rule.user_id,
+        rule.package_hash,
+        rule.target.as_str(),
+        rule.action.as_str(),
+        rule.pattern,
+        rule.replacement
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.created_at))
+}
+
+/// Delete a diff rule, returning whether a rule of the user was found
+pub async fn delete_diff_rule(
+    pool: &PgPool,
+    user_id: &Uuid,
+    rule_id: &Uuid,
+) -> Result<bool, Error> {
+    let result = query!(
+        r#"
+        DELETE FROM diff_rules
+        WHERE id = $1 AND user_id = $2
+        "#,
+        rule_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,61 @@
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::schema::odra::{ContractVersionOdraSchema, OdraSchemaSource};
+
+pub async fn get_contract_schema(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    version: u32,
+    user_id: &Uuid,
+) -> Result<Option<ContractVersionOdraSchema>, Error> {
+    let row = query!(
+        r#"
+        SELECT contract_package_hash, version, contract_schema, source
+        FROM contract_schemas
+        WHERE contract_package_hash = $1 AND version = $2 AND user_id = $3
+        "#,
+        contract_package_hash,
+        version as i32,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    match row {
+        Some(r) => Ok(Some(ContractVersionOdraSchema {
+            contract_package_hash: r.contract_package_hash,
+            contract_version: r.version as u32,
+            source: OdraSchemaSource::parse(&r.source).unwrap_or(OdraSchemaSource::Upload),
+            schema: serde_json::from_value(r.contract_schema)
+                .map_err(|e| Error::Decode(Box::new(e)))?,
+        })),
+        None => Ok(None),
+    }
+}
+
+pub async fn upsert_contract_schema(
+    pool: &PgPool,
+    user_id: &Uuid,
+    schema: &ContractVersionOdraSchema,
+) -> Result<(), Error> {
+    let contract_schema =
+        serde_json::to_value(&schema.schema).map_err(|e| Error::Decode(Box::new(e)))?;
+    query!(
+        r#"
+        INSERT INTO contract_schemas (contract_package_hash, version, user_id, contract_schema, source)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (contract_package_hash, version, user_id)
+        DO UPDATE SET contract_schema = EXCLUDED.contract_schema, source = EXCLUDED.source, created_at = now()
+        "#,
+        schema.contract_package_hash,
+        schema.contract_version as i32,
+        user_id,
+        contract_schema,
+        schema.source.as_str()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{Error, PgPool, query};
+
+use crate::models::{
+    api::transaction::{Transaction, TransactionsQuery},
+    schema::transaction::{TrackedContractPackage, TransactionCursorSchema},
+};
+
+/// Contract packages tracked by any user, with the contract hashes of their versions
+pub async fn get_tracked_contract_packages(
+    pool: &PgPool,
+) -> Result<Vec<TrackedContractPackage>, Error> {
+    let rows = query!(
+        r#"
+        SELECT p.network, p.package_hash,
+            COALESCE(ARRAY_AGG(DISTINCT v.contract_hash) FILTER (WHERE v.contract_hash IS NOT NULL), '{}') AS "contract_hashes!"
+        FROM contract_packages p
+        LEFT JOIN contract_versions v
+            ON v.contract_package_hash = p.package_hash AND v.user_id = p.user_id
+        GROUP BY p.network, p.package_hash
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| TrackedContractPackage {
+            network: r.network,
+            contract_package_hash: r.package_hash,
+            contract_hashes: r.contract_hashes,
+        })
+        .collect())
+}
+
+/// Insert or refresh a transaction, returning whether it was not indexed before
+pub async fn upsert_transaction(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    transaction: &Transaction,
+    timestamp: DateTime<Utc>,
+) -> Result<bool, Error> {
+    let row = query!(
+        r#"
+        INSERT INTO transactions (network, deploy_hash, contract_package_hash, contract_hash, block_hash, block_height, caller_public_key, execution_type_id, entry_point_id, entry_point_name, args, payment_amount, cost, status, timestamp, error_message)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, COALESCE(NULLIF($12, '')::NUMERIC, 0), COALESCE(NULLIF($13, '')::NUMERIC, 0), $14, $15, $16)
+        ON CONFLICT (network, deploy_hash)
+        DO UPDATE SET block_hash = EXCLUDED.block_hash, block_height = EXCLUDED.block_height, entry_point_name = COALESCE(EXCLUDED.entry_point_name, transactions.entry_point_name), cost = EXCLUDED.cost, status = EXCLUDED.status, error_message = EXCLUDED.error_message
+        RETURNING (xmax = 0) AS "inserted!"
+        "#,
+        network,
+        transaction.deploy_hash,
+        contract_package_hash,
+        transaction.contract_hash,
+        transaction.block_hash,
+        transaction.block_height.map(|h| h as i64),
+        transaction.caller_public_key,
+        transaction.execution_type_id.map(|id| id as i64),
+        transaction.entry_point_id.map(|id| id as i64),
+        transaction.entry_point_name,
+        transaction.args,
+        transaction.payment_amount,
+        transaction.cost,
+        transaction.status,
+        timestamp,
+        transaction.error_message
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.inserted)
+}
+
+pub async fn get_transaction_cursor(
+    pool: &PgPool,
+    network: &str,
+    target_hash: &str,
+) -> Result<Option<TransactionCursorSchema>, Error> {
+    let row = query!(
+        r#"
+        SELECT network, target_hash, contract_package_hash, next_page, backfill_complete, newest_timestamp, poll_until, sweep_started_at, indexed_until
+        FROM transaction_cursors
+        WHERE network = $1 AND target_hash = $2
+        "#,
+        network,
+        target_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| TransactionCursorSchema {
+        network: r.network,
+        target_hash: r.target_hash,
+        contract_package_hash: r.contract_package_hash,
+        next_page: r.next_page as u32,
+        backfill_complete: r.backfill_complete,
+        newest_timestamp: r.newest_timestamp,
+        poll_until: r.poll_until,
+        sweep_started_at: r.sweep_started_at,
+        indexed_until: r.indexed_until,
+    }))
+}
+
+pub async fn upsert_transaction_cursor(
+    pool: &PgPool,
+    cursor: &TransactionCursorSchema,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO transaction_cursors (network, target_hash, contract_package_hash, next_page, backfill_complete, newest_timestamp, poll_until, sweep_started_at, indexed_until, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, now())
+        ON CONFLICT (network, target_hash)
+        DO UPDATE SET next_page = EXCLUDED.next_page, backfill_complete = EXCLUDED.backfill_complete, newest_timestamp = EXCLUDED.newest_timestamp, poll_until = EXCLUDED.poll_until, sweep_started_at = EXCLUDED.sweep_started_at, indexed_until = EXCLUDED.indexed_until, updated_at = now()
+        "#,
+        cursor.network,
+        cursor.target_hash,
+        cursor.contract_package_hash,
+        cursor.next_page as i32,
+        cursor.backfill_complete,
+        cursor.newest_timestamp,
+        cursor.poll_until,
+        cursor.sweep_started_at,
+        cursor.indexed_until
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the time up to which every transaction of a package is indexed from the explorer, unset until
+/// each of its targets completed a sweep
+pub async fn get_package_indexed_until(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    let row = query!(
+        r#"
+        SELECT CASE WHEN bool_and(indexed_until IS NOT NULL) THEN MIN(indexed_until) END AS indexed_until
+        FROM transaction_cursors
+        WHERE network = $1 AND contract_package_hash = $2
+        "#,
+        network,
+        contract_package_hash
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.indexed_until)
+}
+
+/// Get a page of indexed transactions of a package, newest first, along with the total matching the filters
+pub async fn get_transactions(
+    pool: &PgPool,
+    network: &str,
+    contract_package_hash: &str,
+    filters: &TransactionsQuery,
+    offset: u64,
+    limit: u64,
+) -> Result<(Vec<Transaction>, u64), Error> {
+    let entry_point_id = filters.entry_point_id.map(|id| id as i64);
+
+    let total = query!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2
+            AND ($3::TEXT IS NULL OR caller_public_key = $3)
+            AND ($4::TEXT IS NULL OR status = $4)
+            AND ($5::TEXT IS NULL OR contract_hash = $5)
+            AND ($6::BIGINT IS NULL OR entry_point_id = $6)
+            AND ($7::TIMESTAMPTZ IS NULL OR timestamp >= $7)
+            AND ($8::TIMESTAMPTZ IS NULL OR timestamp < $8)
+            AND ($9::TEXT IS NULL OR entry_point_name = $9)
+        "#,
+        network,
+        contract_package_hash,
+        filters.caller,
+        filters.status,
+        filters.contract_hash,
+        entry_point_id,
+        filters.from,
+        filters.to,
+        filters.entry_point_name
+    )
+    .fetch_one(pool)
+    .await?
+    .count;
+
+    let rows = query!(
+        r#"
+        SELECT deploy_hash, contract_package_hash, contract_hash, block_hash, block_height, caller_public_key, execution_type_id, entry_point_id, entry_point_name, args,
+            payment_amount::TEXT AS "payment_amount!", cost::TEXT AS "cost!", status, timestamp, error_message
+        FROM transactions
+        WHERE network = $1 AND contract_package_hash = $2
+            AND ($3::TEXT IS NULL OR caller_public_key = $3)
+            AND ($4::TEXT IS NULL OR status = $4)
+            AND ($5::TEXT IS NULL OR contract_hash = $5)
+            AND ($6::BIGINT IS NULL OR entry_point_id = $6)
+            AND ($7::TIMESTAMPTZ IS NULL OR timestamp >= $7)
+            AND ($8::TIMESTAMPTZ IS NULL OR timestamp < $8)
+            AND ($9::TEXT IS NULL OR entry_point_name = $9)
+        ORDER BY timestamp DESC, deploy_hash
+        OFFSET $10 LIMIT $11
+        "#,
+        network,
+        contract_package_hash,
+        filters.caller,
+        filters.status,
+        filters.contract_hash,
+        entry_point_id,
+        filters.from,
+        filters.to,
+        filters.entry_point_name,
+        offset as i64,
+        limit as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let transactions = rows
+        .into_iter()
+        .map(|r| Transaction {
+            deploy_hash: r.deploy_hash,
+            block_hash: r.block_hash,
+            block_height: r.block_height.map(|h| h as u64),
+            caller_public_key: r.caller_public_key,
+            execution_type_id: r.execution_type_id.map(|id| id as u64),
+            contract_package_hash: Some(r.contract_package_hash),
+            contract_hash: r.contract_hash,
+            entry_point_id: r.entry_point_id.map(|id| id as u64),
+            entry_point_name: r.entry_point_name,
+            args: r.args,
+            payment_amount: r.payment_amount,
+            cost: r.cost,
+            status: r.status,
+            timestamp: r.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            error_message: r.error_message,
+            call: None,
+            failure: None,
+        })
+        .collect();
+
+    Ok((transactions, total as u64))
+}
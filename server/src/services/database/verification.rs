@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool, query};
+use uuid::Uuid;
+
+use crate::models::schema::verification::{ContractVerificationSchema, ContractVerificationStatus};
+
+pub async fn insert_contract_verification(
+    pool: &PgPool,
+    verification: &ContractVerificationSchema,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO contract_verifications (id, contract_package_hash, version, user_id, commit_id, contract_name, toolchain, cargo_odra_version, features, status, expected_wasm_hash, built_wasm_hash, build_log, created_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+        "#,
+        verification.id,
+        verification.contract_package_hash,
+        verification.contract_version as i32,
+        verification.user_id,
+        verification.commit_id,
+        verification.contract_name,
+        verification.toolchain,
+        verification.cargo_odra_version,
+        &verification.features,
+        verification.status.as_str(),
+        verification.expected_wasm_hash,
+        verification.built_wasm_hash,
+        verification.build_log,
+        verification.created_at,
+        verification.completed_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record the outcome of a verification build, `completed_at` stays unset while the build is running
+pub async fn update_contract_verification(
+    pool: &PgPool,
+    id: &Uuid,
+    status: ContractVerificationStatus,
+    built_wasm_hash: Option<&str>,
+    build_log: Option<&str>,
+    completed_at: Option<DateTime<Utc>>,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        UPDATE contract_verifications
+        SET status = $2, built_wasm_hash = $3, build_log = $4, completed_at = $5
+        WHERE id = $1
+        "#,
+        id,
+        status.as_str(),
+        built_wasm_hash,
+        build_log,
+        completed_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get verifications of a package, newest first, optionally limited to one version
+pub async fn get_contract_verifications(
+    pool: &PgPool,
+    contract_package_hash: &str,
+    user_id: &Uuid,
+    version: Option<u32>,
+) -> Result<Vec<ContractVerificationSchema>, Error> {
+    let rows = query!(
+        r#"
+        SELECT id, contract_package_hash, version, user_id, commit_id, contract_name, toolchain, cargo_odra_version, features, status, expected_wasm_hash, built_wasm_hash, build_log, created_at, completed_at
+        FROM contract_verifications
+        WHERE contract_package_hash = $1 AND user_id = $2 AND ($3::INTEGER IS NULL OR version = $3)
+        ORDER BY created_at DESC
+        "#,
+        contract_package_hash,
+        user_id,
+        version.map(|v| v as i32)
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ContractVerificationSchema {
+            id: r.id,
+            contract_package_hash: r.contract_package_hash,
+            contract_version: r.version as u32,
+            user_id: r.user_id,
+            commit_id: r.commit_id,
+            contract_name: r.contract_name,
+            toolchain: r.toolchain,
+            cargo_odra_version: r.cargo_odra_version,
+            features: r.features,
+            status: ContractVerificationStatus::parse(&r.status)
+                .unwrap_or(ContractVerificationStatus::Failed),
+            expected_wasm_hash: r.expected_wasm_hash,
+            built_wasm_hash: r.built_wasm_hash,
+            build_log: r.build_log,
+            created_at: r.created_at,
+            completed_at: r.completed_at,
+        })
+        .collect())
+}
+
+/// Mark builds left running by a previous server process as failed
+pub async fn fail_interrupted_contract_verifications(pool: &PgPool) -> Result<u64, Error> {
+    let result = query!(
+        r#"
+        UPDATE contract_verifications
+        SET status = 'Failed', build_log = 'Build interrupted by a server restart', completed_at = now()
+        WHERE completed_at IS NULL
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,129 @@
+use sqlx::{Error, PgPool, query};
+
+pub async fn get_contract_wasm_bytes(
+    pool: &PgPool,
+    wasm_hash: &str,
+) -> Result<Option<Vec<u8>>, Error> {
+    let row = query!(
+        r#"
+        SELECT wasm
+        FROM contract_wasms
+        WHERE wasm_hash = $1
+        "#,
+        wasm_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.wasm))
+}
+
+pub async fn insert_contract_wasm_bytes(
+    pool: &PgPool,
+    wasm_hash: &str,
+    wasm: &[u8],
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO contract_wasms (wasm_hash, wasm, size)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (wasm_hash) DO NOTHING
+        "#,
+        wasm_hash,
+        wasm,
+        wasm.len() as i32
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_wasm_disassembly(pool: &PgPool, wasm_hash: &str) -> Result<Option<String>, Error> {
+    let row = query!(
+        r#"
+        SELECT wat
+        FROM wasm_disassemblies
+        WHERE wasm_hash = $1
+        "#,
+        wasm_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.wat))
+}
+
+pub async fn insert_wasm_disassembly(
+    pool: &PgPool,
+    wasm_hash: &str,
+    wat: &str,
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO wasm_disassemblies (wasm_hash, wat)
+        VALUES ($1, $2)
+        ON CONFLICT (wasm_hash) DO NOTHING
+        "#,
+        wasm_hash,
+        wat
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get a page of a cached WAT diff with the total number of its lines, `None` when not cached
+pub async fn get_wat_diff_page(
+    pool: &PgPool,
+    from_wasm_hash: &str,
+    to_wasm_hash: &str,
+    function: &str,
+    offset: u32,
+    limit: u32,
+) -> Result<Option<(u32, Vec<String>)>, Error> {
+    // Array bounds are 1-based and inclusive, slices past the end are empty
+    let start = offset.saturating_add(1).min(i32::MAX as u32) as i32;
+    let end = offset.saturating_add(limit).min(i32::MAX as u32) as i32;
+    let row = query!(
+        r#"
+        SELECT COALESCE(array_length(lines, 1), 0) AS "total_lines!", lines[$4:$5] AS "lines!"
+        FROM wat_diffs
+        WHERE from_wasm_hash = $1 AND to_wasm_hash = $2 AND function = $3
+        "#,
+        from_wasm_hash,
+        to_wasm_hash,
+        function,
+        start,
+        end
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| (r.total_lines as u32, r.lines)))
+}
+
+pub async fn insert_wat_diff(
+    pool: &PgPool,
+    from_wasm_hash: &str,
+    to_wasm_hash: &str,
+    function: &str,
+    lines: &[String],
+) -> Result<(), Error> {
+    query!(
+        r#"
+        INSERT INTO wat_diffs (from_wasm_hash, to_wasm_hash, function, lines)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (from_wasm_hash, to_wasm_hash, function) DO NOTHING
+        "#,
+        from_wasm_hash,
+        to_wasm_hash,
+        function,
+        lines
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use sqlx::{Error, PgPool};
+
+use crate::{
+    config::IndexerMode,
+    models::schema::contract::ContractPackageSchema,
+    services::{
+        contract::anomaly::{AnomalyDetection, detect_anomalies, get_anomaly_alert},
+        database::{
+            alert::insert_alert,
+            anomaly::{
+                get_anomaly_check_cursor, insert_transaction_anomaly, upsert_anomaly_check_cursor,
+                upsert_transaction_baseline,
+            },
+            contract::get_all_contract_packages,
+            indexer::get_block_indexed_until,
+            transaction::get_package_indexed_until,
+        },
+    },
+};
+
+/// Windows checked per package and run, so a long catch-up does not hold up the other packages
+const MAX_WINDOWS_PER_RUN: u32 = 7 * 24;
+
+/// Get the time up to which the indexer stored every transaction of a package
+async fn get_indexed_until(
+    pool: &PgPool,
+    indexer_mode: IndexerMode,
+    package: &ContractPackageSchema,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    match indexer_mode {
+        IndexerMode::Explorer => {
+            get_package_indexed_until(pool, &package.network, &package.package_hash).await
+        }
+        IndexerMode::Node => get_block_indexed_until(pool, &package.network).await,
+    }
+}
+
+/// Store the baselines of a detection, recording its anomalies and raising an alert for each new one
+async fn record_detection(
+    pool: &PgPool,
+    package: &ContractPackageSchema,
+    detection: AnomalyDetection,
+) {
+    for baseline in detection.baselines {
+        if let Err(e) = upsert_transaction_baseline(pool, &baseline).await {
+            log::warn!(
+                "Failed to store {} baseline of {}: {}",
+                baseline.metric.as_str(),
+                package.package_hash,
+                e
+            );
+        }
+    }
+    for anomaly in detection.anomalies {
+        match insert_transaction_anomaly(pool, &anomaly).await {
+            Ok(true) => {
+                let alert = get_anomaly_alert(&anomaly);
+                if let Err(e) = insert_alert(pool, &alert).await {
+                    log::warn!("Failed to raise alert {}: {}", alert.dedup_key, e);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("Failed to record anomaly {}: {}", anomaly.dedup_key, e),
+        }
+    }
+}
+
+/// Check every complete hour of a package since the last one checked, up to the hour the indexer
+/// has stored every transaction of
+async fn check_package(
+    pool: &PgPool,
+    indexer_mode: IndexerMode,
+    package: &ContractPackageSchema,
+) -> Result<(), String> {
+    let Some(indexed_until) = get_indexed_until(pool, indexer_mode, package)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(());
+    };
+    let last_window_end = indexed_until
+        .duration_trunc(TimeDelta::hours(1))
+        .map_err(|e| e.to_string())?;
+    let mut checked_until =
+        match get_anomaly_check_cursor(pool, &package.package_hash, &package.user_id)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            Some(checked_until) => checked_until,
+            // Newly tracked packages start with the last indexed hour
+            None => last_window_end - TimeDelta::hours(1),
+        };
+
+    for _ in 0..MAX_WINDOWS_PER_RUN {
+        if checked_until >= last_window_end {
+            break;
+        }
+        let window_end = checked_until + TimeDelta::hours(1);
+        let detection = detect_anomalies(pool, package, window_end).await?;
+        record_detection(pool, package, detection).await;
+        // Saved per window so a restart resumes after the last hour checked
+        upsert_anomaly_check_cursor(pool, &package.package_hash, &package.user_id, window_end)
+            .await
+            .map_err(|e| e.to_string())?;
+        checked_until = window_end;
+    }
+    Ok(())
+}
+
+/// Check the hours of every tracked package indexed since its last check against its baselines
+pub async fn detect_transaction_anomalies(
+    pool: &PgPool,
+    indexer_mode: IndexerMode,
+) -> Result<(), String> {
+    let packages = get_all_contract_packages(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for package in &packages {
+        if let Err(e) = check_package(pool, indexer_mode, package).await {
+            log::warn!(
+                "Failed to detect anomalies of {}: {}",
+                package.package_hash,
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Detect transaction anomalies at the given interval
+pub async fn run_anomaly_detection(pool: PgPool, indexer_mode: IndexerMode, interval: Duration) {
+    loop {
+        if let Err(e) = detect_transaction_anomalies(&pool, indexer_mode).await {
+            log::error!("Anomaly detection failed: {}", e);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,386 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::{
+    models::schema::transaction::{ContractPackageWrite, IndirectCall, TransactionTarget},
+    services::{
+        contract::block::{
+            NodeTransaction, get_latest_block_height, get_node_block, get_node_transaction,
+        },
+        database::{
+            indexer::{
+                advance_package_backfill_cursor, delete_block_index_gap, get_block_index_cursor,
+                get_block_index_gaps, get_caller_contracts, get_contract_package_writes,
+                get_package_backfill_cursors, insert_contract_package_write, insert_indirect_call,
+                upsert_block_index_cursor, upsert_block_index_gap, upsert_package_backfill_cursor,
+            },
+            transaction::{get_tracked_contract_packages, upsert_transaction},
+        },
+    },
+    utils::strip_hash_prefix,
+};
+
+/// Blocks read per network and run before tracked packages are reloaded
+const MAX_BLOCKS_PER_RUN: u64 = 500;
+
+/// Gaps retried per network and run
+const MAX_GAPS_PER_RUN: u64 = 50;
+
+/// Attempts after which a gap is left for manual inspection
+const MAX_GAP_ATTEMPTS: u32 = 5;
+
+/// Network the block indexer reads and the node it reads from
+pub struct IndexedNetwork {
+    pub network: String,
+    pub node_address: String,
+    /// Height to start from when the network has no cursor yet, the latest block when unset
+    pub start_height: Option<u64>,
+}
+
+/// Index the transactions of one block, returning the number of transactions stored and the block
+/// timestamp.
+///
+/// Calls to tracked packages and their contracts go to the transactions table, calls reaching them
+/// through other contracts or session code to the indirect calls, and writes to any contract
+/// package are kept so packages installed after the start height can be registered. Contracts
+/// added to a tracked package are tracked from the block that added them.
+async fn index_block(
+    pool: &PgPool,
+    network: &IndexedNetwork,
+    height: u64,
+    tracked: &mut HashMap<String, String>,
+    callers: &mut HashMap<String, Vec<String>>,
+) -> Result<(u64, DateTime<Utc>), String> {
+    let block = get_node_block(&network.node_address, height).await?;
+    let mut stored = 0;
+
+    for transaction_hash in &block.transaction_hashes {
+        // Transactions of a finalized block are executed, a missing result means the node has not
+        // stored it yet, so the block is failed and retried as a gap rather than indexed without it
+        let Some(mut node_transaction) =
+            get_node_transaction(&network.node_address, transaction_hash).await?
+        else {
+            return Err(format!(
+                "Node has no execution result for transaction {}",
+                transaction_hash
+            ));
+        };
+        node_transaction
+            .transaction
+            .block_hash
+            .get_or_insert_with(|| block.hash.clone());
+        node_transaction
+            .transaction
+            .block_height
+            .get_or_insert(block.height);
+
+        for package_hash in &node_transaction.written_packages {
+            let write = ContractPackageWrite {
+                network: network.network.clone(),
+                contract_package_hash: package_hash.clone(),
+                deploy_hash: node_transaction.transaction.deploy_hash.clone(),
+                block_height: block.height,
+                timestamp: node_transaction.timestamp,
+                caller_public_key: node_transaction.transaction.caller_public_key.clone(),
+                cost: node_transaction.transaction.cost.clone(),
+                contract_hashes: node_transaction.written_contracts.clone(),
+            };
+            insert_contract_package_write(pool, &write)
+                .await
+                .map_err(|e| e.to_string())?;
+            if tracked.contains_key(package_hash) {
+                for contract_hash in &node_transaction.written_contracts {
+                    tracked.insert(contract_hash.clone(), package_hash.clone());
+                }
+            }
+        }
+
+        let target_hash = node_transaction.target.as_ref().map(|target| target.hash());
+        let Some(package_hash) = target_hash.and_then(|hash| tracked.get(hash)) else {
+            stored +=
+                index_indirect_calls(pool, network, &node_transaction, tracked, callers).await?;
+            continue;
+        };
+        if let Some(TransactionTarget::Contract(_)) = &node_transaction.target {
+            node_transaction.transaction.contract_package_hash = Some(package_hash.clone());
+        }
+        upsert_transaction(
+            pool,
+            &network.network,
+            package_hash,
+            &node_transaction.transaction,
+            node_transaction.timestamp,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+        stored += 1;
+    }
+    Ok((stored, block.timestamp))
+}
+
+/// Store the calls of a transaction that reached tracked packages through another contract or
+/// session code, returning the number of calls stored.
+///
+/// The packages are found among the keys the execution touched. A failed execution keeps no
+/// effects besides the payment, so a failed call to a contract seen calling tracked packages
+/// before counts as a failed call to each of them.
+async fn index_indirect_calls(
+    pool: &PgPool,
+    network: &IndexedNetwork,
+    node_transaction: &NodeTransaction,
+    tracked: &HashMap<String, String>,
+    callers: &mut HashMap<String, Vec<String>>,
+) -> Result<u64, String> {
+    let transaction = &node_transaction.transaction;
+    let caller_contract_hash = node_transaction
+        .target
+        .as_ref()
+        .map(|target| target.hash().to_string());
+
+    let mut packages: Vec<String> = vec![];
+    for package_hash in node_transaction
+        .touched_keys
+        .iter()
+        .filter_map(|key| tracked.get(key))
+    {
+        if !packages.contains(package_hash) {
+            packages.push(package_hash.clone());
+        }
+    }
+    let failed = transaction
+        .error_message
+        .as_ref()
+        .is_some_and(|e| !e.is_empty());
+    if failed && let Some(known) = caller_contract_hash.as_ref().and_then(|h| callers.get(h)) {
+        for package_hash in known {
+            if !packages.contains(package_hash) {
+                packages.push(package_hash.clone());
+            }
+        }
+    }
+
+    for package_hash in &packages {
+        let call = IndirectCall {
+            network: network.network.clone(),
+            deploy_hash: transaction.deploy_hash.clone(),
+            contract_package_hash: package_hash.clone(),
+            caller_contract_hash: caller_contract_hash.clone(),
+            caller_public_key: transaction.caller_public_key.clone(),
+            timestamp: node_transaction.timestamp,
+            error_message: transaction.error_message.clone(),
+        };
+        insert_indirect_call(pool, &call)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(hash) = &caller_contract_hash {
+            let known = callers.entry(hash.clone()).or_default();
+            if !known.contains(package_hash) {
+                known.push(package_hash.clone());
+            }
+        }
+    }
+    Ok(packages.len() as u64)
+}
+
+/// Map every tracked package and contract hash of a network to its package hash
+async fn get_tracked_hashes(
+    pool: &PgPool,
+    network: &str,
+) -> Result<HashMap<String, String>, String> {
+    let packages = get_tracked_contract_packages(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut tracked = HashMap::new();
+    for package in packages.into_iter().filter(|p| p.network == network) {
+        let package_hash = strip_hash_prefix(&package.contract_package_hash);
+        for contract_hash in &package.contract_hashes {
+            tracked.insert(strip_hash_prefix(contract_hash), package_hash.clone());
+        }
+        tracked.insert(package_hash.clone(), package_hash);
+    }
+    Ok(tracked)
+}
+
+/// Retry failed blocks, then read the next blocks up to the node's latest one, returning whether the
+/// indexer caught up with it
+pub async fn index_network_blocks(pool: &PgPool, network: &IndexedNetwork) -> Result<bool, String> {
+    let mut tracked = get_tracked_hashes(pool, &network.network).await?;
+    let mut callers = get_caller_contracts(pool, &network.network)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let gaps = get_block_index_gaps(pool, &network.network, MAX_GAP_ATTEMPTS, MAX_GAPS_PER_RUN)
+        .await
+        .map_err(|e| e.to_string())?;
+    for height in gaps {
+        let result = match index_block(pool, network, height, &mut tracked, &mut callers).await {
+            Ok(_) => delete_block_index_gap(pool, &network.network, height).await,
+            Err(e) => upsert_block_index_gap(pool, &network.network, height, &e).await,
+        };
+        result.map_err(|e| e.to_string())?;
+    }
+
+    let latest_height = get_latest_block_height(&network.node_address).await?;
+    let next_height = match get_block_index_cursor(pool, &network.network)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        Some(height) => height,
+        None => network.start_height.unwrap_or(latest_height),
+    };
+
+    let end_height = latest_height.min(next_height.saturating_add(MAX_BLOCKS_PER_RUN - 1));
+    for height in next_height..=end_height {
+        let indexed_until =
+            match index_block(pool, network, height, &mut tracked, &mut callers).await {
+                Ok((count, timestamp)) => {
+                    if count > 0 {
+                        log::info!(
+                            "Indexed {} transactions from {} block {}",
+                            count,
+                            network.network,
+                            height
+                        );
+                    }
+                    Some(timestamp)
+                }
+                // A failed block becomes a gap so the indexer keeps up with the chain
+                Err(e) => {
+                    log::warn!(
+                        "Failed to index {} block {}: {}",
+                        network.network,
+                        height,
+                        e
+                    );
+                    upsert_block_index_gap(pool, &network.network, height, &e)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    None
+                }
+            };
+        // Saved per block so a restart resumes after the last block read
+        upsert_block_index_cursor(pool, &network.network, height + 1, indexed_until)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    backfill_packages(pool, network, &mut callers).await?;
+    Ok(end_height >= latest_height)
+}
+
+/// Schedule the rescan of the blocks the indexer read before a package was registered, from the
+/// given height up to the block the indexer is at
+pub async fn schedule_package_backfill(
+    pool: &PgPool,
+    network: &str,
+    package_hash: &str,
+    from_height: u64,
+) -> Result<(), String> {
+    let Some(next_height) = get_block_index_cursor(pool, network)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        // The indexer has not read any block yet, it tracks the package from its first one
+        return Ok(());
+    };
+    if from_height >= next_height {
+        return Ok(());
+    }
+    upsert_package_backfill_cursor(
+        pool,
+        network,
+        &strip_hash_prefix(package_hash),
+        from_height,
+        next_height - 1,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Rescan blocks for the packages registered after the indexer passed them, tracking only those
+/// packages so the transactions of the others are not read twice.
+///
+/// A failed block stops the rescan of its package until the next run, the indexer has no gap for
+/// blocks behind its cursor.
+async fn backfill_packages(
+    pool: &PgPool,
+    network: &IndexedNetwork,
+    callers: &mut HashMap<String, Vec<String>>,
+) -> Result<(), String> {
+    let cursors = get_package_backfill_cursors(pool, &network.network)
+        .await
+        .map_err(|e| e.to_string())?;
+    for mut cursor in cursors {
+        let package_hash = cursor.contract_package_hash.clone();
+        // The package may not be registered yet when its install is looked for, its contracts
+        // are then known from the writes read so far
+        let mut tracked = HashMap::from([(package_hash.clone(), package_hash.clone())]);
+        for write in get_contract_package_writes(pool, &network.network, &package_hash)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            for contract_hash in write.contract_hashes {
+                tracked.insert(contract_hash, package_hash.clone());
+            }
+        }
+
+        let end_height = cursor
+            .end_height
+            .min(cursor.next_height.saturating_add(MAX_BLOCKS_PER_RUN - 1));
+        while cursor.next_height <= end_height {
+            match index_block(pool, network, cursor.next_height, &mut tracked, callers).await {
+                Ok((count, _)) => {
+                    if count > 0 {
+                        log::info!(
+                            "Backfilled {} transactions of {} from {} block {}",
+                            count,
+                            package_hash,
+                            network.network,
+                            cursor.next_height
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Failed to backfill {} from {} block {}: {}",
+                        package_hash,
+                        network.network,
+                        cursor.next_height,
+                        e
+                    );
+                    break;
+                }
+            }
+            cursor.next_height += 1;
+        }
+        advance_package_backfill_cursor(pool, &network.network, &cursor)
+            .await
+            .map_err(|e| e.to_string())?;
+        if cursor.next_height > cursor.end_height {
+            log::info!(
+                "Backfill of {} on {} is complete",
+                package_hash,
+                network.network
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Keep the transactions table up to date from the nodes of the given networks, waiting for new
+/// blocks only once every network caught up
+pub async fn run_block_indexer(pool: PgPool, networks: Vec<IndexedNetwork>, interval: Duration) {
+    loop {
+        let mut caught_up = true;
+        for network in &networks {
+            match index_network_blocks(&pool, network).await {
+                Ok(network_caught_up) => caught_up &= network_caught_up,
+                Err(e) => log::error!("Block indexing of {} failed: {}", network.network, e),
+            }
+        }
+        if caught_up {
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
@@ -150,7 +150,8 @@ pub async fn write_contract_diff_versions_to_chain(
         };
 
         // 1️⃣ Compute diff
-        let diff = get_contract_version_diff(prev.clone(), contract_version.clone()).await?;
+        // Stored diffs are shared by every user of the package, so no user's rules go into them
+        let diff = get_contract_version_diff(prev.clone(), contract_version.clone(), &[]).await?;
         let diff_str = serde_json::to_string(&diff).map_err(|_| "Failed to serialize diff")?;
 
         if diff_str.len() > 32_000 {
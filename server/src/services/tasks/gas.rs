@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::services::{
+    contract::gas::{find_gas_regressions, get_gas_regression_alerts},
+    database::{
+        alert::insert_alert,
+        contract::{get_all_contract_packages, get_contract_versions},
+    },
+};
+
+/// Compare the costs of every pair of adjacent versions of the tracked packages and raise alerts
+/// for the regressions found
+pub async fn check_gas_regressions(pool: &PgPool) -> Result<(), String> {
+    let packages = get_all_contract_packages(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for package in &packages {
+        let mut versions: Vec<u32> =
+            get_contract_versions(pool, &package.package_hash, &package.user_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .iter()
+                .map(|v| v.contract_version)
+                .collect();
+        versions.sort_unstable();
+
+        for pair in versions.windows(2) {
+            let (v1, v2) = (pair[0], pair[1]);
+            let regressions = match find_gas_regressions(
+                pool,
+                &package.network,
+                &package.package_hash,
+                &package.user_id,
+                v1,
+                v2,
+            )
+            .await
+            {
+                Ok(regressions) => regressions,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to compare costs of {} v{} -> v{}: {}",
+                        package.package_hash,
+                        v1,
+                        v2,
+                        e
+                    );
+                    continue;
+                }
+            };
+            for alert in get_gas_regression_alerts(
+                package.user_id,
+                &package.package_hash,
+                v1,
+                v2,
+                &regressions,
+            ) {
+                if let Err(e) = insert_alert(pool, &alert).await {
+                    log::warn!("Failed to raise alert {}: {}", alert.dedup_key, e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check for gas regressions at the given interval
+pub async fn run_gas_regression_checks(pool: PgPool, interval: Duration) {
+    loop {
+        if let Err(e) = check_gas_regressions(&pool).await {
+            log::error!("Gas regression check failed: {}", e);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
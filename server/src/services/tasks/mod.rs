@@ -1 +1,6 @@
+pub(crate) mod anomaly;
+pub(crate) mod block;
 pub(crate) mod contract;
+pub(crate) mod gas;
+pub(crate) mod promotion;
+pub(crate) mod transaction;
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::services::{
+    contract::promotion::{evaluate_promotion, get_promotion_drift_alerts},
+    database::{alert::insert_alert, promotion::get_all_contract_package_links},
+};
+
+/// Evaluate every linked package pair and raise alerts for the drift found
+pub async fn check_promotion_drift(
+    pool: &PgPool,
+    node_addresses: (&str, &str),
+) -> Result<(), String> {
+    let links = get_all_contract_package_links(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for link in &links {
+        let status = match evaluate_promotion(pool, node_addresses, link).await {
+            Ok(status) => status,
+            Err(e) => {
+                log::warn!(
+                    "Failed to evaluate promotion of {} -> {}: {}",
+                    link.testnet_package_hash,
+                    link.mainnet_package_hash,
+                    e
+                );
+                continue;
+            }
+        };
+        for alert in get_promotion_drift_alerts(link.user_id, &status) {
+            if let Err(e) = insert_alert(pool, &alert).await {
+                log::warn!("Failed to raise alert {}: {}", alert.dedup_key, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check linked package pairs for promotion drift at the given interval, reading Wasm from the
+/// given testnet and mainnet nodes
+pub async fn run_promotion_drift_checks(
+    pool: PgPool,
+    (testnet_node_address, mainnet_node_address): (String, String),
+    interval: Duration,
+) {
+    loop {
+        if let Err(e) =
+            check_promotion_drift(&pool, (&testnet_node_address, &mainnet_node_address)).await
+        {
+            log::error!("Promotion drift check failed: {}", e);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
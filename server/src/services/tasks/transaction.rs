@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::{
+    models::schema::transaction::{
+        TrackedContractPackage, TransactionCursorSchema, TransactionTarget,
+    },
+    services::{
+        contract::{call::EntryPointNames, transaction::fetch_transactions_page},
+        database::transaction::{
+            get_tracked_contract_packages, get_transaction_cursor, upsert_transaction,
+            upsert_transaction_cursor,
+        },
+    },
+};
+
+const TRANSACTIONS_PAGE_SIZE: u64 = 100;
+
+/// Pages fetched per target and run, so one large backfill does not hold up the other packages
+const MAX_PAGES_PER_RUN: u32 = 20;
+
+/// Index the next pages of one target and return the number of newly indexed transactions.
+///
+/// Until the backfill completes, pages are walked from the cursor towards the oldest transaction. New
+/// transactions only shift older ones to later pages, so resumed pages overlap but never skip any.
+/// Afterwards pages are polled from the first one until reaching the newest transaction indexed
+/// before. A poll that runs out of pages first keeps its page and that timestamp on the cursor, and
+/// the next run resumes from there the same way the backfill does.
+async fn index_target(
+    pool: &PgPool,
+    package: &TrackedContractPackage,
+    target: &TransactionTarget,
+    entry_point_names: &mut EntryPointNames,
+) -> Result<u64, String> {
+    let mut cursor = get_transaction_cursor(pool, &package.network, target.hash())
+        .await
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| TransactionCursorSchema {
+            network: package.network.clone(),
+            target_hash: target.hash().to_string(),
+            contract_package_hash: package.contract_package_hash.clone(),
+            next_page: 1,
+            backfill_complete: false,
+            newest_timestamp: None,
+            poll_until: None,
+            sweep_started_at: None,
+            indexed_until: None,
+        });
+    let poll_until = if cursor.backfill_complete {
+        cursor.poll_until.or(cursor.newest_timestamp)
+    } else {
+        None
+    };
+    let mut page = cursor.next_page;
+    let mut indexed = 0;
+
+    for _ in 0..MAX_PAGES_PER_RUN {
+        // Transactions after the first page of a sweep is fetched are left for the next sweep
+        if page == 1 {
+            cursor.sweep_started_at = Some(Utc::now());
+        }
+        let mut response =
+            fetch_transactions_page(&package.network, target, page, TRANSACTIONS_PAGE_SIZE).await?;
+
+        let mut inserted = 0;
+        let mut reached_known = false;
+        for transaction in &mut response.data {
+            let timestamp = match DateTime::parse_from_rfc3339(&transaction.timestamp) {
+                Ok(timestamp) => timestamp.with_timezone(&Utc),
+                Err(e) => {
+                    log::warn!(
+                        "Skipping deploy {} with invalid timestamp: {}",
+                        transaction.deploy_hash,
+                        e
+                    );
+                    continue;
+                }
+            };
+            transaction.entry_point_name = entry_point_names
+                .resolve(&package.network, transaction)
+                .await;
+            if upsert_transaction(
+                pool,
+                &package.network,
+                &package.contract_package_hash,
+                transaction,
+                timestamp,
+            )
+            .await
+            .map_err(|e| e.to_string())?
+            {
+                inserted += 1;
+            }
+            reached_known |= poll_until.is_some_and(|until| timestamp <= until);
+            cursor.newest_timestamp = cursor.newest_timestamp.max(Some(timestamp));
+        }
+        indexed += inserted;
+
+        let last_page = (response.data.len() as u64) < TRANSACTIONS_PAGE_SIZE
+            || u64::from(page) >= response.page_count;
+        let caught_up = last_page || (cursor.backfill_complete && reached_known);
+        if caught_up {
+            cursor.backfill_complete = true;
+            cursor.next_page = 1;
+            cursor.poll_until = None;
+            cursor.indexed_until = cursor.sweep_started_at;
+        } else {
+            cursor.next_page = page + 1;
+            cursor.poll_until = poll_until;
+        }
+        // Saved per page so a restart resumes where the backfill or poll stopped
+        upsert_transaction_cursor(pool, &cursor)
+            .await
+            .map_err(|e| e.to_string())?;
+        if caught_up {
+            break;
+        }
+        page += 1;
+    }
+
+    upsert_transaction_cursor(pool, &cursor)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(indexed)
+}
+
+/// Index the transactions of every tracked package and the contracts of its versions once
+pub async fn index_transactions(pool: &PgPool) -> Result<(), String> {
+    let packages = get_tracked_contract_packages(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut entry_point_names = EntryPointNames::default();
+
+    for package in &packages {
+        let targets = std::iter::once(TransactionTarget::ContractPackage(
+            package.contract_package_hash.clone(),
+        ))
+        .chain(
+            package
+                .contract_hashes
+                .iter()
+                .map(|hash| TransactionTarget::Contract(hash.clone())),
+        );
+        for target in targets {
+            match index_target(pool, package, &target, &mut entry_point_names).await {
+                Ok(0) => {}
+                Ok(count) => log::info!(
+                    "Indexed {} transactions of {} via {}",
+                    count,
+                    package.contract_package_hash,
+                    target.hash()
+                ),
+                Err(e) => log::warn!(
+                    "Failed to index transactions of {} via {}: {}",
+                    package.contract_package_hash,
+                    target.hash(),
+                    e
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Keep the transactions table up to date, polling cspr.live at the given interval
+pub async fn run_transaction_indexer(pool: PgPool, interval: Duration) {
+    loop {
+        if let Err(e) = index_transactions(&pool).await {
+            log::error!("Transaction indexing failed: {}", e);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
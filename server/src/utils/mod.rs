@@ -2,20 +2,39 @@ use crate::{
     config::{self, AppState},
     middleware::cors::get_cors_config,
     routers::{
+        alert::{acknowledge_user_alert, list_alerts},
         contract::{
-            get_contract_details, get_contract_diff, get_contract_transactions,
-            get_contracts_overview, get_diff_analysis, register_contract,
+            compare_contract_versions, get_contract_changelog, get_contract_details,
+            get_contract_diff, get_contract_history, get_contract_promotion_status,
+            get_contract_storage, get_contract_transactions, get_contract_version_schema,
+            get_contract_version_verifications, get_contract_wat_diff_page, get_contracts_overview,
+            get_diff_analysis, get_local_contract_diff, link_contract_package, register_contract,
+            unlink_contract_package, upload_contract_version_schema, verify_contract_version,
         },
         health::health_check,
+        rules::{create_diff_rule, edit_diff_rule, list_diff_rules, remove_diff_rule},
+        transaction::{
+            get_entry_point_call_stats, get_entry_point_failures, get_package_call_series,
+            get_package_call_stats, get_package_top_callers, get_upgrade_impact,
+            get_version_call_stats, get_version_entry_point_usage, get_version_failures,
+            list_contract_error_codes, list_transaction_anomalies, list_transaction_baselines,
+            set_contract_error_codes,
+        },
     },
+    services::contract::verification::MAX_ENCODED_SOURCE_ARCHIVE_SIZE,
 };
 use axum::{
     Router,
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    routing::{get, post, put},
 };
 use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
 use std::sync::Arc;
 
+/// Source archives ship their vendored dependencies, which far exceed the default body limit. The
+/// archive is base64 encoded, the rest of the request is a few build settings
+const VERIFICATION_UPLOAD_LIMIT: usize = MAX_ENCODED_SOURCE_ARCHIVE_SIZE + 64 * 1024;
+
 pub fn create_router(app_state: Arc<AppState>) -> Router {
     let cors = get_cors_config();
     let router: Router = Router::new()
@@ -36,10 +55,113 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/api/v1/u/{user_id}/contract-package/{package_hash}/transactions",
             get(get_contract_transactions),
         )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/failures/entry-points",
+            get(get_entry_point_failures),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/failures/versions",
+            get(get_version_failures),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/analytics",
+            get(get_package_call_stats),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/analytics/entry-points",
+            get(get_entry_point_call_stats),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/analytics/versions",
+            get(get_version_call_stats),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/analytics/calls",
+            get(get_package_call_series),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/analytics/callers",
+            get(get_package_top_callers),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/impact",
+            get(get_upgrade_impact),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/versions/{version}/entry-point-usage",
+            get(get_version_entry_point_usage),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/anomalies",
+            get(list_transaction_anomalies),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/anomalies/baselines",
+            get(list_transaction_baselines),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/error-codes",
+            get(list_contract_error_codes).put(set_contract_error_codes),
+        )
         .route(
             "/api/v1/u/{user_id}/contract-package/{package_hash}/diff",
             get(get_contract_diff),
         )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/link",
+            post(link_contract_package).delete(unlink_contract_package),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/promotion",
+            get(get_contract_promotion_status),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/compare",
+            get(compare_contract_versions),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/changelog",
+            get(get_contract_changelog),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/diff/wat",
+            get(get_contract_wat_diff_page),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/diff/local",
+            post(get_local_contract_diff),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/history",
+            get(get_contract_history),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/storage",
+            post(get_contract_storage),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/versions/{version}/schema",
+            get(get_contract_version_schema).post(upload_contract_version_schema),
+        )
+        .route(
+            "/api/v1/u/{user_id}/contract-package/{package_hash}/versions/{version}/verify",
+            get(get_contract_version_verifications)
+                .post(verify_contract_version)
+                .layer(DefaultBodyLimit::max(VERIFICATION_UPLOAD_LIMIT)),
+        )
+        .route("/api/v1/u/{user_id}/alerts", get(list_alerts))
+        .route(
+            "/api/v1/u/{user_id}/alerts/{alert_id}/acknowledge",
+            post(acknowledge_user_alert),
+        )
+        .route(
+            "/api/v1/u/{user_id}/diff-rules",
+            get(list_diff_rules).post(create_diff_rule),
+        )
+        .route(
+            "/api/v1/u/{user_id}/diff-rules/{rule_id}",
+            put(edit_diff_rule).delete(remove_diff_rule),
+        )
         .route(
             "/api/v1/u/{user_id}/contract-package/diff/analyze",
             post(get_diff_analysis),
@@ -49,6 +171,28 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
     router
 }
 
+/// Prefixes of formatted hashes. Nodes format package, contract and wasm hashes differently per
+/// protocol version, the longer prefixes come first so `contract-package-` is not read as `contract-`
+const HASH_PREFIXES: [&str; 8] = [
+    "contract-package-",
+    "contract-wasm-",
+    "contract-",
+    "package-",
+    "entity-contract-",
+    "hash-",
+    "deploy-",
+    "transaction-",
+];
+
+/// Strip the prefix of a formatted hash, leaving bare hex as is
+pub fn strip_hash_prefix(input: &str) -> String {
+    HASH_PREFIXES
+        .iter()
+        .find_map(|prefix| input.strip_prefix(prefix))
+        .unwrap_or(input)
+        .to_string()
+}
+
 pub async fn create_db_pool() -> Pool<Postgres> {
     let config = config::load_config();
     PgPoolOptions::new()
@@ -32,10 +32,13 @@ import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import type {
   ChartDataPoint,
   ContractData,
+  ContractEntryPointChange,
   ContractEntryPointDiff,
   ContractNamedKeysDiff,
+  ContractUpgradeAssessment,
   ContractVersionData,
   ContractVersionDiff,
+  ContractVersionDiffMeta,
   EntryPoint,
   Key,
   ResponseData,
@@ -242,6 +245,25 @@ const formatAccess = (access: any) => {
   return "Unknown";
 };
 
+const formatCLType = (clType: unknown) =>
+  typeof clType === "string" ? clType : JSON.stringify(clType);
+
+const formatEntryPointChange = (change: ContractEntryPointChange) => {
+  if ("ParameterAdded" in change)
+    return `Parameter ${change.ParameterAdded.name}: ${formatCLType(change.ParameterAdded.cl_type)} added`;
+  if ("ParameterRemoved" in change)
+    return `Parameter ${change.ParameterRemoved.name}: ${formatCLType(change.ParameterRemoved.cl_type)} removed`;
+  if ("ParameterRenamed" in change)
+    return `Parameter ${change.ParameterRenamed.from} renamed to ${change.ParameterRenamed.to}`;
+  if ("ParameterTypeChanged" in change)
+    return `Parameter ${change.ParameterTypeChanged.name} type changed from ${formatCLType(change.ParameterTypeChanged.from)} to ${formatCLType(change.ParameterTypeChanged.to)}`;
+  if ("ReturnTypeChanged" in change)
+    return `Return type changed from ${formatCLType(change.ReturnTypeChanged.from)} to ${formatCLType(change.ReturnTypeChanged.to)}`;
+  if ("AccessChanged" in change)
+    return `Access changed from ${formatAccess(change.AccessChanged.from)} to ${formatAccess(change.AccessChanged.to)}`;
+  return `Type changed from ${change.EntryPointTypeChanged.from} to ${change.EntryPointTypeChanged.to}`;
+};
+
 const KeyValueCard = ({
   title,
   value,
@@ -298,6 +320,48 @@ const DetailCard = ({
   </div>
 );
 
+const UpgradeProvenance = ({ meta }: { meta: ContractVersionDiffMeta }) =>
+  meta.deployer_public_key ? (
+    <div className="grid grid-cols-1 md:grid-cols-4 gap-3 w-full">
+      <DetailCard
+        title={`v${meta.contract_version} Deployed By`}
+        value={meta.deployer_public_key}
+      />
+      <DetailCard title="Deploy Hash" value={meta.deploy_hash ?? "-"} />
+      <DetailCard
+        title="Block Height"
+        value={meta.block_height?.toString() ?? "-"}
+      />
+      <DetailCard title="Upgrade Cost" value={meta.upgrade_cost ?? "-"} />
+    </div>
+  ) : null;
+
+const UpgradeAssessment = ({
+  assessment,
+}: {
+  assessment?: ContractUpgradeAssessment | null;
+}) =>
+  assessment ? (
+    <div className="flex items-center gap-2.5 w-full">
+      <Badge
+        className={`inline-flex gap-1 items-center px-2.5 py-1 rounded-full text-xs font-medium ${
+          assessment.severity === "Breaking"
+            ? "badge-error"
+            : assessment.severity === "PotentiallyBreaking"
+              ? "badge-warning"
+              : "badge-success"
+        }`}
+      >
+        {assessment.severity === "PotentiallyBreaking"
+          ? "Potentially Breaking"
+          : assessment.severity}
+      </Badge>
+      <span className="text-sm text-muted-foreground">
+        Suggested {assessment.semver_bump.toLowerCase()} version bump
+      </span>
+    </div>
+  ) : null;
+
 const ParameterCard = ({
   title,
   args,
@@ -373,6 +437,8 @@ const EntryPointTab = ({
                     : diff.Modified.from;
 
               const entryPointTo = "Modified" in diff ? diff.Modified.to : null;
+              const changes =
+                "Modified" in diff ? (diff.Modified.changes ?? []) : [];
 
               return (
                 <AccordionItem key={idx} value={`ep-${idx}`}>
@@ -404,6 +470,21 @@ const EntryPointTab = ({
                   <AccordionContent className="px-5 py-4 bg-background/30">
                     <ScrollArea className="h-56 pr-4">
                       <div className="space-y-5">
+                        {changes.length > 0 && (
+                          <div>
+                            <h4 className="text-xs font-semibold text-muted-foreground uppercase tracking-wide mb-3 px-1">
+                              Changes
+                            </h4>
+                            <ul className="space-y-1 px-1 text-sm font-mono">
+                              {changes.map((change, changeIdx) => (
+                                <li key={changeIdx}>
+                                  {formatEntryPointChange(change)}
+                                </li>
+                              ))}
+                            </ul>
+                          </div>
+                        )}
+
                         {/* Original Version */}
                         <div>
                           <h4 className="text-xs font-semibold text-muted-foreground uppercase tracking-wide mb-3 px-1">
@@ -905,6 +986,8 @@ const DiffTab = ({ contractData }: { contractData: ContractData }) => {
                     AI Analysis
                   </TabsTrigger>
                 </TabsList>
+                <UpgradeAssessment assessment={fetchedDiffData.assessment} />
+                <UpgradeProvenance meta={fetchedDiffData.v2} />
               </CardHeader>
               <CardContent className="h-full">
                 <TabsContent value="entry_point" className="h-full w-full">
@@ -1297,7 +1380,7 @@ export default function ContractPage() {
         if (!userId) throw new Error("User not authenticated");
 
         const res = await fetch(
-          `/api/v1/u/${userId}/contract-package/${packageHash}/transactions`,
+          `/api/v1/u/${userId}/contract-package/${packageHash}/transactions?limit=100`,
           {
             method: "GET",
             headers: { "Content-Type": "application/json" },
@@ -1310,8 +1393,8 @@ export default function ContractPage() {
 
         const json = await res.json();
         if (json.success && json.data) {
-          setTransactions(json.data);
-          formatTransactionTimestamps(json.data);
+          setTransactions(json.data.data);
+          formatTransactionTimestamps(json.data.data);
         } else {
           throw new Error(json.error || "Failed to load transactions");
         }
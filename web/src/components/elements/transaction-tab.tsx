@@ -68,7 +68,7 @@ export function TransactionsTab({ packageHash }: { packageHash: string }) {
         if (!userId) throw new Error("User not authenticated");
 
         const res = await fetch(
-          `/api/v1/u/${userId}/contract-package/${packageHash}/transactions`,
+          `/api/v1/u/${userId}/contract-package/${packageHash}/transactions?limit=100`,
           {
             method: "GET",
             headers: { "Content-Type": "application/json" },
@@ -81,7 +81,7 @@ export function TransactionsTab({ packageHash }: { packageHash: string }) {
 
         const json = await res.json();
         if (json.success && json.data) {
-          setTransactions(json.data);
+          setTransactions(json.data.data);
         } else {
           throw new Error(json.error || "Failed to load transactions");
         }
@@ -153,9 +153,7 @@ export function TransactionsTab({ packageHash }: { packageHash: string }) {
               {transactions.map((tx) => (
                 <TableRow key={tx.deploy_hash}>
                   <TableCell>
-                    {tx.status?.toLowerCase() === "executed" ||
-                    tx.status?.toLowerCase() === "processed" ||
-                    !tx.error_message ? (
+                    {!tx.failure && !tx.error_message ? (
                       <Badge
                         variant="outline"
                         className="bg-green-500/10 text-green-500 border-green-500/20 hover:bg-green-500/20"
@@ -167,9 +165,10 @@ export function TransactionsTab({ packageHash }: { packageHash: string }) {
                       <Badge
                         variant="destructive"
                         className="bg-red-500/10 text-red-500 border-red-500/20 hover:bg-red-500/20"
+                        title={tx.failure?.message ?? tx.error_message}
                       >
                         <AlertCircle className="w-3 h-3 mr-1" />
-                        Failed
+                        {tx.failure?.error_name ?? "Failed"}
                       </Badge>
                     )}
                   </TableCell>
@@ -179,7 +178,19 @@ export function TransactionsTab({ packageHash }: { packageHash: string }) {
                     </span>
                   </TableCell>
                   <TableCell>
-                    {tx.entry_point_id ? (
+                    {tx.call ? (
+                      <Badge
+                        variant="secondary"
+                        className="font-mono text-xs"
+                        title={tx.call.display}
+                      >
+                        v{tx.call.contract_version} {tx.call.entry_point_name}
+                      </Badge>
+                    ) : tx.entry_point_name ? (
+                      <Badge variant="secondary" className="font-mono text-xs">
+                        {tx.entry_point_name}
+                      </Badge>
+                    ) : tx.entry_point_id ? (
                       <Badge variant="secondary" className="font-mono text-xs">
                         ID: {tx.entry_point_id}
                       </Badge>
@@ -57,6 +57,10 @@ export interface ContractVersionDiffMeta {
   contract_version: number;
   is_disabled: boolean;
   wasm_hash: string;
+  deploy_hash?: string | null;
+  block_height?: number | null;
+  deployer_public_key?: string | null;
+  upgrade_cost?: string | null;
 }
 
 export interface ContractVersionDiff {
@@ -65,12 +69,57 @@ export interface ContractVersionDiff {
   contract_package_hash: string;
   entry_points: ContractEntryPointDiff[];
   named_keys: ContractNamedKeysDiff[];
+  assessment?: ContractUpgradeAssessment | null;
+  gas_regressions?: GasRegression[];
+}
+
+export interface GasRegression {
+  entry_point_name: string;
+  from_samples: number;
+  to_samples: number;
+  from_median: number;
+  to_median: number;
+  increase: number;
+  p_value: number;
+}
+
+export type ContractChangeCompatibility =
+  | "Compatible"
+  | "PotentiallyBreaking"
+  | "Breaking";
+
+export interface ContractChangeClassification {
+  subject: string;
+  change: string;
+  compatibility: ContractChangeCompatibility;
+  reason: string;
+}
+
+export interface ContractUpgradeAssessment {
+  severity: ContractChangeCompatibility;
+  semver_bump: "Patch" | "Minor" | "Major";
+  changes: ContractChangeClassification[];
 }
 
 export type ContractEntryPointDiff =
   | { Added: EntryPoint }
   | { Removed: EntryPoint }
-  | { Modified: { from: EntryPoint; to: EntryPoint } };
+  | {
+      Modified: {
+        from: EntryPoint;
+        to: EntryPoint;
+        changes?: ContractEntryPointChange[];
+      };
+    };
+
+export type ContractEntryPointChange =
+  | { ParameterAdded: { name: string; cl_type: unknown } }
+  | { ParameterRemoved: { name: string; cl_type: unknown } }
+  | { ParameterRenamed: { from: string; to: string; cl_type: unknown } }
+  | { ParameterTypeChanged: { name: string; from: unknown; to: unknown } }
+  | { ReturnTypeChanged: { from: unknown; to: unknown } }
+  | { AccessChanged: { from: EntryPointAccess; to: EntryPointAccess } }
+  | { EntryPointTypeChanged: { from: EntryPointType; to: EntryPointType } };
 
 export type ContractNamedKeysDiff =
   | { Added: { key: string; value: Key } }
@@ -115,12 +164,44 @@ export interface Transaction {
   contract_package_hash?: string;
   contract_hash?: string;
   entry_point_id?: number;
+  entry_point_name?: string;
   args?: any;
   payment_amount: string;
   cost: string;
   status: string;
   timestamp: string;
   error_message?: string;
+  call?: DecodedCall | null;
+  failure?: TransactionFailure | null;
+}
+
+export type TransactionFailureKind =
+  | "OutOfGas"
+  | "UserError"
+  | "MissingKey"
+  | "InvalidArgs"
+  | "Other";
+
+export interface TransactionFailure {
+  kind: TransactionFailureKind;
+  code?: number | null;
+  error_name?: string | null;
+  message: string;
+}
+
+export interface DecodedArg {
+  name: string;
+  cl_type?: any;
+  value: any;
+  matches_signature: boolean;
+}
+
+export interface DecodedCall {
+  contract_version: number;
+  entry_point_name: string;
+  args: DecodedArg[];
+  missing_args: string[];
+  display: string;
 }
 
 export interface ChartDataPoint {